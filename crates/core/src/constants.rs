@@ -0,0 +1,2 @@
+/// The number of seconds in a (non-leap) year, used to annualize yields.
+pub const SECONDS_PER_YEAR: u64 = 31_536_000;
@@ -1,3 +1,7 @@
+pub mod constants;
+pub mod math;
 pub mod types;
 
+pub use constants::*;
+pub use math::*;
 pub use types::*;
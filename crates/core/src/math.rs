@@ -0,0 +1,118 @@
+use ethnum::U256;
+
+const BPS_DENOMINATOR: u16 = 10_000;
+
+/// Computes the Q64.64 sqrt price reached when the price moves by
+/// `price_impact_bps`, down if `price_down` and up otherwise. The result is
+/// rounded up if `round_up` and floored otherwise, saturates at `u128::MAX`
+/// and is not clamped to the price bounds of a pool.
+///
+/// Returns `None` if the price would move down by more than 100%.
+pub fn sqrt_price_after_impact(
+    sqrt_price_x64: u128,
+    price_impact_bps: u16,
+    price_down: bool,
+    round_up: bool,
+) -> Option<u128> {
+    let bps_denominator = U256::from(BPS_DENOMINATOR);
+    let factor = if price_down {
+        U256::from(BPS_DENOMINATOR.checked_sub(price_impact_bps)?)
+    } else {
+        bps_denominator + U256::from(price_impact_bps)
+    };
+
+    // Split the product to keep it within 256 bits
+    let price = U256::from(sqrt_price_x64) * U256::from(sqrt_price_x64);
+    let remainder = price % bps_denominator * factor;
+    let remainder = if round_up {
+        (remainder + bps_denominator - 1) / bps_denominator
+    } else {
+        remainder / bps_denominator
+    };
+    let Some(target_price) = (price / bps_denominator)
+        .checked_mul(factor)
+        .and_then(|target_price| target_price.checked_add(remainder))
+    else {
+        return Some(u128::MAX);
+    };
+
+    let mut target_sqrt_price = integer_sqrt(target_price);
+    if round_up && target_sqrt_price * target_sqrt_price < target_price {
+        target_sqrt_price += 1;
+    }
+    Some(target_sqrt_price.try_into().unwrap_or(u128::MAX))
+}
+
+/// Computes the floor of the square root of `value`.
+fn integer_sqrt(value: U256) -> U256 {
+    if value < U256::from(2u8) {
+        return value;
+    }
+    let mut x = U256::ONE << ((256 - value.leading_zeros()) / 2 + 1);
+    loop {
+        let y = (x + value / x) >> 1;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const Q64: u128 = 1 << 64;
+
+    #[test]
+    fn test_integer_sqrt() {
+        for value in [0u128, 1, 2, 3, 4, 15, 16, 17, 1 << 64, u64::MAX as u128, u128::MAX] {
+            let root = integer_sqrt(U256::from(value));
+            assert!(root * root <= U256::from(value));
+            assert!((root + 1) * (root + 1) > U256::from(value));
+        }
+        assert_eq!(integer_sqrt(U256::MAX), U256::from(u128::MAX));
+    }
+
+    #[test]
+    fn test_sqrt_price_after_impact() {
+        // A price of 1 moving by 21%, i.e. a sqrt price of 1.1 or 0.888...
+        assert_eq!(sqrt_price_after_impact(Q64, 0, true, false), Some(Q64));
+        assert_eq!(sqrt_price_after_impact(Q64, 0, false, false), Some(Q64));
+        assert_eq!(sqrt_price_after_impact(Q64, 2_100, false, false), Some(Q64 * 11 / 10));
+        let sqrt_price = sqrt_price_after_impact(Q64, 2_100, true, false).unwrap();
+        assert_eq!(
+            sqrt_price,
+            integer_sqrt(U256::from(Q64) * U256::from(Q64) * 79 / 100).as_u128()
+        );
+
+        assert_eq!(sqrt_price_after_impact(Q64, 10_000, true, false), Some(0));
+        assert_eq!(sqrt_price_after_impact(Q64, 10_001, true, false), None);
+        assert_eq!(sqrt_price_after_impact(u128::MAX, 1, false, false), Some(u128::MAX));
+    }
+
+    #[test]
+    fn test_sqrt_price_after_impact_round_up() {
+        // Exact results are not rounded
+        assert_eq!(sqrt_price_after_impact(Q64, 0, true, true), Some(Q64));
+        assert_eq!(sqrt_price_after_impact(10 * Q64, 2_100, false, true), Some(11 * Q64));
+
+        for (sqrt_price, price_impact_bps, price_down) in
+            [(Q64, 100, false), (Q64, 100, true), (Q64 / 3, 7, false), (Q64 * 1_000 + 1, 1, true)]
+        {
+            let floor = sqrt_price_after_impact(sqrt_price, price_impact_bps, price_down, false);
+            let ceil = sqrt_price_after_impact(sqrt_price, price_impact_bps, price_down, true);
+            assert_eq!(ceil, floor.map(|floor| floor + 1));
+
+            let factor = if price_down {
+                10_000 - u128::from(price_impact_bps)
+            } else {
+                10_000 + u128::from(price_impact_bps)
+            };
+            let ceil = U256::from(ceil.unwrap());
+            let price = U256::from(sqrt_price) * U256::from(sqrt_price);
+            assert!(ceil * ceil * 10_000 >= price * factor);
+            assert!((ceil - 1) * (ceil - 1) * 10_000 < price * factor);
+        }
+    }
+}
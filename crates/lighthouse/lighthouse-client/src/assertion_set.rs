@@ -0,0 +1,273 @@
+use lighthouse::{types::LogLevel, AccountDataAssertion};
+use solana_sdk::instruction::Instruction;
+
+use crate::{
+    error::{LighthouseClientError, Result},
+    planner::merge_assertions,
+    types::{AssertionSet, AssertionSetEntry, PlannedAssertion},
+};
+
+/// The version of the assertion set schema, bumped on breaking changes.
+pub const ASSERTION_SET_VERSION: u8 = 1;
+
+impl AssertionSet {
+    pub fn new(assertions: Vec<AssertionSetEntry>) -> Self {
+        Self { version: ASSERTION_SET_VERSION, log_level: None, assertions }
+    }
+
+    pub fn log_level(mut self, log_level: LogLevel) -> Self {
+        self.log_level = Some(log_level);
+        self
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let assertion_set = serde_json::from_str::<Self>(json)
+            .map_err(|e| LighthouseClientError::InvalidAssertionSet(e.to_string()))?;
+        assertion_set.check_version()?;
+
+        Ok(assertion_set)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| LighthouseClientError::InvalidAssertionSet(e.to_string()))
+    }
+
+    /// The assertions, e.g. to add to an `AssertionPlanBuilder`.
+    pub fn planned_assertions(&self) -> Vec<PlannedAssertion> {
+        self.assertions.iter().cloned().map(PlannedAssertion::from).collect()
+    }
+
+    /// The assertion instructions. The assertions of the same kind on the
+    /// same account are merged into a single instruction.
+    pub fn instructions(&self) -> Result<Vec<Instruction>> {
+        self.check_version()?;
+
+        let (instructions, _) = merge_assertions(
+            &self.planned_assertions(),
+            self.log_level.unwrap_or(LogLevel::Silent),
+        );
+
+        Ok(instructions)
+    }
+
+    fn check_version(&self) -> Result<()> {
+        if self.version != ASSERTION_SET_VERSION {
+            return Err(LighthouseClientError::UnsupportedAssertionSetVersion(self.version));
+        }
+
+        Ok(())
+    }
+}
+
+impl From<AssertionSetEntry> for PlannedAssertion {
+    fn from(entry: AssertionSetEntry) -> Self {
+        match entry {
+            AssertionSetEntry::AccountInfo { target_account, assertion } => {
+                PlannedAssertion::AccountInfo { target_account, assertion }
+            }
+            AssertionSetEntry::AccountData { target_account, offset, assertion } => {
+                PlannedAssertion::AccountData {
+                    target_account,
+                    assertion: AccountDataAssertion { offset: offset.into(), assertion },
+                }
+            }
+            AssertionSetEntry::TokenAccount { target_account, assertion } => {
+                PlannedAssertion::TokenAccount { target_account, assertion }
+            }
+            AssertionSetEntry::MintAccount { target_account, assertion } => {
+                PlannedAssertion::MintAccount { target_account, assertion }
+            }
+            AssertionSetEntry::StakeAccount { target_account, assertion } => {
+                PlannedAssertion::StakeAccount { target_account, assertion }
+            }
+            AssertionSetEntry::UpgradeableLoaderAccount { target_account, assertion } => {
+                PlannedAssertion::UpgradeableLoaderAccount { target_account, assertion }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lighthouse::{
+        instructions::{
+            AssertAccountDataMultiBuilder, AssertMintAccountBuilder, AssertTokenAccountMultiBuilder,
+        },
+        types::{
+            DataValueAssertion, EquatableOperator, IntegerOperator, MintAccountAssertion,
+            TokenAccountAssertion,
+        },
+    };
+    use solana_sdk::pubkey::Pubkey;
+
+    use super::*;
+
+    fn assertion_set_json(token_account: &Pubkey, mint: &Pubkey, pool: &Pubkey) -> String {
+        format!(
+            r#"{{
+                "version": 1,
+                "log_level": "PlaintextMessage",
+                "assertions": [
+                    {{
+                        "TokenAccount": {{
+                            "target_account": "{token_account}",
+                            "assertion": {{
+                                "Amount": {{ "value": 1000, "operator": "GreaterThanOrEqual" }}
+                            }}
+                        }}
+                    }},
+                    {{
+                        "MintAccount": {{
+                            "target_account": "{mint}",
+                            "assertion": {{
+                                "MintAuthority": {{ "value": null, "operator": "Equal" }}
+                            }}
+                        }}
+                    }},
+                    {{
+                        "AccountData": {{
+                            "target_account": "{pool}",
+                            "offset": 8,
+                            "assertion": {{ "U8": {{ "value": 1, "operator": "Equal" }} }}
+                        }}
+                    }},
+                    {{
+                        "TokenAccount": {{
+                            "target_account": "{token_account}",
+                            "assertion": {{
+                                "Mint": {{ "value": "{mint}", "operator": "Equal" }}
+                            }}
+                        }}
+                    }},
+                    {{
+                        "AccountData": {{
+                            "target_account": "{pool}",
+                            "offset": 300,
+                            "assertion": {{
+                                "U128": {{
+                                    "value": 340282366920938463463374607431768211455,
+                                    "operator": "LessThanOrEqual"
+                                }}
+                            }}
+                        }}
+                    }}
+                ]
+            }}"#
+        )
+    }
+
+    #[test]
+    fn loads_assertion_set_instructions() {
+        let (token_account, mint, pool) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let assertion_set =
+            AssertionSet::from_json(&assertion_set_json(&token_account, &mint, &pool)).unwrap();
+
+        assert_eq!(
+            assertion_set.instructions().unwrap(),
+            vec![
+                AssertTokenAccountMultiBuilder::new()
+                    .target_account(token_account)
+                    .log_level(LogLevel::PlaintextMessage)
+                    .assertions(
+                        vec![
+                            TokenAccountAssertion::Amount {
+                                value: 1000,
+                                operator: IntegerOperator::GreaterThanOrEqual,
+                            },
+                            TokenAccountAssertion::Mint {
+                                value: mint,
+                                operator: EquatableOperator::Equal,
+                            },
+                        ]
+                        .into()
+                    )
+                    .instruction(),
+                AssertMintAccountBuilder::new()
+                    .target_account(mint)
+                    .log_level(LogLevel::PlaintextMessage)
+                    .assertion(MintAccountAssertion::MintAuthority {
+                        value: None,
+                        operator: EquatableOperator::Equal,
+                    })
+                    .instruction(),
+                AssertAccountDataMultiBuilder::new()
+                    .target_account(pool)
+                    .log_level(LogLevel::PlaintextMessage)
+                    .assertions(
+                        vec![
+                            AccountDataAssertion {
+                                offset: 8u64.into(),
+                                assertion: DataValueAssertion::U8 {
+                                    value: 1,
+                                    operator: IntegerOperator::Equal,
+                                },
+                            },
+                            AccountDataAssertion {
+                                offset: 300u64.into(),
+                                assertion: DataValueAssertion::U128 {
+                                    value: u128::MAX,
+                                    operator: IntegerOperator::LessThanOrEqual,
+                                },
+                            },
+                        ]
+                        .into()
+                    )
+                    .instruction(),
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trips_assertion_set_json() {
+        let (token_account, mint, pool) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let assertion_set =
+            AssertionSet::from_json(&assertion_set_json(&token_account, &mint, &pool)).unwrap();
+
+        assert_eq!(
+            AssertionSet::from_json(&assertion_set.to_json().unwrap()).unwrap(),
+            assertion_set
+        );
+
+        let assertion_set = AssertionSet::new(vec![AssertionSetEntry::AccountData {
+            target_account: pool,
+            offset: 8,
+            assertion: DataValueAssertion::Bytes {
+                value: vec![1, 2, 3].into(),
+                operator: EquatableOperator::Equal,
+            },
+        }]);
+        let json = assertion_set.to_json().unwrap();
+
+        assert!(!json.contains("log_level"));
+        assert_eq!(AssertionSet::from_json(&json).unwrap(), assertion_set);
+    }
+
+    #[test]
+    fn rejects_invalid_assertion_sets() {
+        assert!(matches!(
+            AssertionSet::from_json(r#"{ "version": 2, "assertions": [] }"#),
+            Err(LighthouseClientError::UnsupportedAssertionSetVersion(2))
+        ));
+        assert!(matches!(
+            AssertionSet::from_json(r#"{ "version": 1, "assertions": [], "log": "Silent" }"#),
+            Err(LighthouseClientError::InvalidAssertionSet(_))
+        ));
+        assert!(matches!(
+            AssertionSet::from_json(
+                r#"{
+                    "version": 1,
+                    "assertions": [{
+                        "AccountInfo": {
+                            "target_account": "not a pubkey",
+                            "assertion": { "Executable": { "value": true, "operator": "Equal" } }
+                        }
+                    }]
+                }"#
+            ),
+            Err(LighthouseClientError::InvalidAssertionSet(_))
+        ));
+    }
+}
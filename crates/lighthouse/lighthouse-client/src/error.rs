@@ -4,6 +4,28 @@ use thiserror::Error;
 pub enum LighthouseClientError {
     #[error("Invalid memory ID")]
     InvalidMemoryId(u8),
+    #[error("No free memory ID")]
+    NoFreeMemoryId,
+    #[error("Memory write range exceeds u16")]
+    InvalidWriteLength,
+    #[error("Invalid assertion result log: {0}")]
+    InvalidAssertionLog(String),
+    #[error("Transaction too large: {0} bytes")]
+    TransactionTooLarge(usize),
+    #[error("Compute unit limit exceeded: {0} compute units")]
+    ComputeUnitLimitExceeded(u32),
+    #[error("Failed to compile transaction: {0}")]
+    CompileError(solana_sdk::message::CompileError),
+    #[error("Invalid slippage tolerance")]
+    InvalidSlippageTolerance(u16),
+    #[error("Invalid transfer fee")]
+    InvalidTransferFee(u16),
+    #[error("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[error("Invalid assertion set: {0}")]
+    InvalidAssertionSet(String),
+    #[error("Unsupported assertion set version: {0}")]
+    UnsupportedAssertionSetVersion(u8),
 }
 
 pub type Result<T> = std::result::Result<T, LighthouseClientError>;
@@ -1,24 +1,46 @@
 use lighthouse::{
     find_memory_pda,
     instructions::{AssertAccountDeltaBuilder, MemoryCloseBuilder, MemoryWriteBuilder},
-    types::{AccountDeltaAssertion, LogLevel},
+    types::{
+        AccountDeltaAssertion, AccountInfoDeltaAssertion, AccountInfoField,
+        DataValueDeltaAssertion, LogLevel, WriteType,
+    },
     ID,
 };
 use solana_sdk::pubkey::Pubkey;
 
-use crate::{error::Result, types::AccountDeltaInstructions};
+use crate::{
+    error::{LighthouseClientError, Result},
+    types::{AccountDeltaInstructions, DeltaAssertion},
+};
 
+/// Builds the instructions snapshotting accounts into a memory account before
+/// a set of instructions, and asserting on the changes of the accounts after
+/// them. All the accounts share a single memory account, each snapshot is
+/// written at its own offset.
 pub struct AccountDeltaInstructionsBuilder {
     pub payer: Pubkey,
     pub account: Pubkey,
     pub memory_id: Option<u8>,
+    pub used_memory_ids: Vec<u8>,
     pub program_id: Option<Pubkey>,
-    pub assertions: Vec<AccountDeltaAssertion>,
+    /// The assertions on `account`.
+    pub assertions: Vec<DeltaAssertion>,
+    /// The assertions on the other accounts.
+    pub account_assertions: Vec<(Pubkey, DeltaAssertion)>,
 }
 
 impl AccountDeltaInstructionsBuilder {
     pub fn new(payer: Pubkey, account: Pubkey) -> Self {
-        Self { memory_id: None, payer, account, program_id: None, assertions: vec![] }
+        Self {
+            memory_id: None,
+            used_memory_ids: vec![],
+            payer,
+            account,
+            program_id: None,
+            assertions: vec![],
+            account_assertions: vec![],
+        }
     }
 
     pub fn memory_id(mut self, memory_id: u8) -> Self {
@@ -26,6 +48,14 @@ impl AccountDeltaInstructionsBuilder {
         self
     }
 
+    /// The memory ids already in use by the payer, e.g. by other builders in
+    /// the same transaction. Without an explicit memory id, the lowest free
+    /// one is picked.
+    pub fn used_memory_ids(mut self, used_memory_ids: &[u8]) -> Self {
+        self.used_memory_ids = used_memory_ids.to_vec();
+        self
+    }
+
     pub fn program_id(mut self, program_id: Pubkey) -> Self {
         self.program_id = Some(program_id);
         self
@@ -33,35 +63,141 @@ impl AccountDeltaInstructionsBuilder {
 }
 
 impl AccountDeltaInstructionsBuilder {
-    pub fn add_assertion(mut self, assertion: AccountDeltaAssertion) -> Self {
+    pub fn add_assertion(mut self, assertion: DeltaAssertion) -> Self {
         self.assertions.push(assertion);
         self
     }
 
+    pub fn add_account_assertion(mut self, account: Pubkey, assertion: DeltaAssertion) -> Self {
+        if account == self.account {
+            self.assertions.push(assertion);
+        } else {
+            self.account_assertions.push((account, assertion));
+        }
+        self
+    }
+
     pub fn build(self) -> Result<AccountDeltaInstructions> {
-        let memory_id = self.memory_id.unwrap_or(0);
+        let memory_id = match self.memory_id {
+            Some(memory_id) if self.used_memory_ids.contains(&memory_id) => {
+                return Err(LighthouseClientError::InvalidMemoryId(memory_id));
+            }
+            Some(memory_id) => memory_id,
+            None => get_free_memory_id(&self.used_memory_ids)?,
+        };
         let program_id = self.program_id.unwrap_or(ID);
-        let assertions = self.assertions;
 
         let (memory_pda, memory_bump) = find_memory_pda(self.payer, memory_id);
+        let memory_write = |source_account: Pubkey, write_offset: u64, write_type: WriteType| {
+            MemoryWriteBuilder::new()
+                .payer(self.payer)
+                .source_account(source_account)
+                .program_id(program_id)
+                .memory(memory_pda)
+                .memory_id(memory_id)
+                .memory_bump(memory_bump)
+                .write_offset(write_offset.into())
+                .write_type(write_type)
+                .instruction()
+        };
+        let assert_delta = |account: Pubkey, assertion: AccountDeltaAssertion| {
+            AssertAccountDeltaBuilder::new()
+                .account_a(memory_pda)
+                .account_b(account)
+                .assertion(assertion)
+                .log_level(LogLevel::PlaintextMessage)
+                .instruction()
+        };
 
-        let before_ix = vec![MemoryWriteBuilder::new()
-            .payer(self.payer)
-            .source_account(self.account)
-            .program_id(program_id)
-            .memory(memory_pda)
-            .memory_id(memory_id)
-            .instruction()];
+        let all_assertions = self
+            .assertions
+            .iter()
+            .map(|assertion| (self.account, assertion))
+            .chain(self.account_assertions.iter().map(|(account, assertion)| (*account, assertion)))
+            .collect::<Vec<_>>();
+        let mut accounts: Vec<Pubkey> = vec![];
+        for (account, _) in &all_assertions {
+            if !accounts.contains(account) {
+                accounts.push(*account);
+            }
+        }
+
+        let mut before_ix = vec![];
         let mut after_ix = vec![];
-        for assertion in assertions {
-            after_ix.push(
-                AssertAccountDeltaBuilder::new()
-                    .account_a(memory_pda)
-                    .account_b(self.account)
-                    .assertion(assertion)
-                    .log_level(LogLevel::PlaintextMessage)
-                    .instruction(),
-            );
+        let mut write_offset = 0u64;
+        for account in &accounts {
+            let assertions = all_assertions
+                .iter()
+                .filter(|(assertion_account, _)| assertion_account == account)
+                .map(|(_, assertion)| *assertion);
+
+            // The account data is snapshotted over the smallest range covering
+            // all the data assertions
+            let data_assertions = assertions
+                .clone()
+                .filter_map(|assertion| match assertion {
+                    DeltaAssertion::Data { offset, assertion } => Some((*offset, assertion)),
+                    DeltaAssertion::AccountInfo(_) => None,
+                })
+                .collect::<Vec<_>>();
+            if let Some(data_start) = data_assertions.iter().map(|(offset, _)| *offset).min() {
+                let data_end = data_assertions
+                    .iter()
+                    .map(|(offset, assertion)| offset + data_value_delta_length(assertion))
+                    .max()
+                    .unwrap_or(data_start);
+                let offset = u16::try_from(data_start)
+                    .map_err(|_| LighthouseClientError::InvalidWriteLength)?;
+                let data_length = u16::try_from(data_end - data_start)
+                    .map_err(|_| LighthouseClientError::InvalidWriteLength)?;
+
+                before_ix.push(memory_write(
+                    *account,
+                    write_offset,
+                    WriteType::AccountData { offset, data_length },
+                ));
+                for (b_offset, assertion) in &data_assertions {
+                    after_ix.push(assert_delta(
+                        *account,
+                        AccountDeltaAssertion::Data {
+                            a_offset: (write_offset + b_offset - data_start).into(),
+                            b_offset: (*b_offset).into(),
+                            assertion: (*assertion).clone(),
+                        },
+                    ));
+                }
+                write_offset += u64::from(data_length);
+            }
+
+            // Every account info field is snapshotted once, after the data
+            let mut fields: Vec<(AccountInfoField, u64)> = vec![];
+            for assertion in assertions {
+                let DeltaAssertion::AccountInfo(assertion) = assertion else {
+                    continue;
+                };
+                let field = account_info_delta_field(assertion);
+                let a_offset = match fields.iter().find(|(snapshot, _)| *snapshot == field) {
+                    Some((_, a_offset)) => *a_offset,
+                    None => {
+                        let a_offset = write_offset;
+                        before_ix.push(memory_write(
+                            *account,
+                            a_offset,
+                            WriteType::AccountInfoField(field),
+                        ));
+                        fields.push((field, a_offset));
+                        write_offset += account_info_field_length(field);
+                        a_offset
+                    }
+                };
+                after_ix.push(assert_delta(
+                    *account,
+                    AccountDeltaAssertion::AccountInfo {
+                        a_offset: a_offset.into(),
+                        assertion: assertion.clone(),
+                    },
+                ));
+            }
         }
 
         let close_ix = MemoryCloseBuilder::new()
@@ -74,6 +210,52 @@ impl AccountDeltaInstructionsBuilder {
 
         after_ix.push(close_ix);
 
-        Ok(AccountDeltaInstructions { memory_id, account: self.account, before_ix, after_ix })
+        #[allow(deprecated)]
+        let delta_instructions = AccountDeltaInstructions {
+            memory_id,
+            account: self.account,
+            accounts,
+            before_ix,
+            after_ix,
+        };
+        Ok(delta_instructions)
+    }
+}
+
+/// Get the lowest memory id not in use.
+pub fn get_free_memory_id(used_memory_ids: &[u8]) -> Result<u8> {
+    (0..=u8::MAX)
+        .find(|memory_id| !used_memory_ids.contains(memory_id))
+        .ok_or(LighthouseClientError::NoFreeMemoryId)
+}
+
+// Private functions
+
+fn data_value_delta_length(assertion: &DataValueDeltaAssertion) -> u64 {
+    match assertion {
+        DataValueDeltaAssertion::U8 { .. } | DataValueDeltaAssertion::I8 { .. } => 1,
+        DataValueDeltaAssertion::U16 { .. } | DataValueDeltaAssertion::I16 { .. } => 2,
+        DataValueDeltaAssertion::U32 { .. } | DataValueDeltaAssertion::I32 { .. } => 4,
+        DataValueDeltaAssertion::U64 { .. } | DataValueDeltaAssertion::I64 { .. } => 8,
+        DataValueDeltaAssertion::Bytes { length, .. } => u64::from(*length),
+    }
+}
+
+fn account_info_delta_field(assertion: &AccountInfoDeltaAssertion) -> AccountInfoField {
+    match assertion {
+        AccountInfoDeltaAssertion::Lamports { .. } => AccountInfoField::Lamports,
+        AccountInfoDeltaAssertion::DataLength { .. } => AccountInfoField::DataLength,
+        AccountInfoDeltaAssertion::Owner { .. } => AccountInfoField::Owner,
+        AccountInfoDeltaAssertion::RentEpoch { .. } => AccountInfoField::RentEpoch,
+    }
+}
+
+fn account_info_field_length(field: AccountInfoField) -> u64 {
+    match field {
+        AccountInfoField::Key | AccountInfoField::Owner => 32,
+        AccountInfoField::Lamports | AccountInfoField::DataLength | AccountInfoField::RentEpoch => {
+            8
+        }
+        AccountInfoField::Executable => 1,
     }
 }
@@ -1,3 +1,8 @@
+pub mod assertion_set;
 pub mod error;
 pub mod instructions_builder;
+pub mod planner;
+pub mod report;
+pub mod safety_checks;
+pub mod slippage_guard;
 pub mod types;
@@ -0,0 +1,530 @@
+use lighthouse::{
+    instructions::{
+        AssertAccountDataBuilder, AssertAccountDataMultiBuilder, AssertAccountInfoBuilder,
+        AssertAccountInfoMultiBuilder, AssertMintAccountBuilder, AssertMintAccountMultiBuilder,
+        AssertStakeAccountBuilder, AssertStakeAccountMultiBuilder, AssertTokenAccountBuilder,
+        AssertTokenAccountMultiBuilder, AssertUpgradeableLoaderAccountBuilder,
+        AssertUpgradeableLoaderAccountMultiBuilder,
+    },
+    types::LogLevel,
+};
+use solana_sdk::{
+    compute_budget,
+    hash::Hash,
+    instruction::Instruction,
+    message::{legacy, v0, AddressLookupTableAccount, VersionedMessage},
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::{LighthouseClientError, Result},
+    types::{AssertionPlan, PlannedAssertion},
+};
+
+// The compute unit estimates are upper bounds of the consumption of the
+// Lighthouse program, measured on token, mint, account info and account data
+// assertions in `lighthouse-test`.
+
+/// Estimated compute units of a Lighthouse assertion instruction, on top of
+/// its assertions.
+pub const ASSERTION_INSTRUCTION_COMPUTE_UNITS: u32 = 1_000;
+/// Estimated compute units of a single assertion.
+pub const ASSERTION_COMPUTE_UNITS: u32 = 800;
+/// Estimated compute units of logging the result of a single assertion.
+pub const LOGGED_ASSERTION_COMPUTE_UNITS: u32 = 1_700;
+
+const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+const SET_COMPUTE_UNIT_LIMIT_DISCRIMINATOR: u8 = 2;
+const SIGNATURE_LENGTH: usize = 64;
+
+/// Plans the assertion instructions appended to a set of base instructions,
+/// so the transaction stays within the packet size and its compute unit
+/// limit.
+///
+/// Assertions are added by descending priority, in insertion order for equal
+/// priorities. The assertions of the same kind on the same account are merged
+/// into their `*Multi` instruction, and an assertion which would push the
+/// transaction past a limit is dropped.
+///
+/// The transaction size is estimated as a legacy transaction, or as a v0
+/// transaction when address lookup tables are given.
+pub struct AssertionPlanBuilder {
+    pub payer: Pubkey,
+    pub base_ixs: Vec<Instruction>,
+    /// The compute units consumed by the base instructions, e.g. from a
+    /// simulation.
+    pub base_compute_units: u32,
+    pub address_lookup_table_accounts: Vec<AddressLookupTableAccount>,
+    pub max_transaction_size: usize,
+    pub compute_unit_limit: Option<u32>,
+    pub log_level: LogLevel,
+    pub assertions: Vec<(u8, PlannedAssertion)>,
+}
+
+impl AssertionPlanBuilder {
+    pub fn new(payer: Pubkey, base_ixs: Vec<Instruction>, base_compute_units: u32) -> Self {
+        Self {
+            payer,
+            base_ixs,
+            base_compute_units,
+            address_lookup_table_accounts: vec![],
+            max_transaction_size: PACKET_DATA_SIZE,
+            compute_unit_limit: None,
+            log_level: LogLevel::Silent,
+            assertions: vec![],
+        }
+    }
+
+    pub fn address_lookup_table_accounts(
+        mut self,
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> Self {
+        self.address_lookup_table_accounts = address_lookup_table_accounts.to_vec();
+        self
+    }
+
+    pub fn max_transaction_size(mut self, max_transaction_size: usize) -> Self {
+        self.max_transaction_size = max_transaction_size;
+        self
+    }
+
+    /// Overrides the compute unit limit of the transaction. Defaults to the
+    /// `SetComputeUnitLimit` instruction of the base instructions, or else to
+    /// the runtime default for the planned instructions.
+    pub fn compute_unit_limit(mut self, compute_unit_limit: u32) -> Self {
+        self.compute_unit_limit = Some(compute_unit_limit);
+        self
+    }
+
+    pub fn log_level(mut self, log_level: LogLevel) -> Self {
+        self.log_level = log_level;
+        self
+    }
+}
+
+impl AssertionPlanBuilder {
+    /// Adds an assertion, higher priorities are planned first.
+    pub fn add_assertion(mut self, priority: u8, assertion: PlannedAssertion) -> Self {
+        self.assertions.push((priority, assertion));
+        self
+    }
+
+    pub fn build(self) -> Result<AssertionPlan> {
+        let base_size = self.transaction_size(&[])?;
+        if base_size > self.max_transaction_size {
+            return Err(LighthouseClientError::TransactionTooLarge(base_size));
+        }
+        let base_compute_unit_limit = self.transaction_compute_unit_limit(&[]);
+        if self.base_compute_units > base_compute_unit_limit {
+            return Err(LighthouseClientError::ComputeUnitLimitExceeded(self.base_compute_units));
+        }
+
+        // A stable sort keeps the insertion order of equal priorities
+        let mut assertions = self.assertions.clone();
+        assertions.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+        let mut planned: Vec<PlannedAssertion> = vec![];
+        let mut dropped = vec![];
+        let mut plan = AssertionPlan {
+            instructions: vec![],
+            dropped: vec![],
+            transaction_size: base_size,
+            compute_units: self.base_compute_units,
+            compute_unit_limit: base_compute_unit_limit,
+        };
+        for (_, assertion) in assertions {
+            planned.push(assertion);
+            let (instructions, assertion_compute_units) =
+                merge_assertions(&planned, self.log_level);
+
+            let compute_units = self.base_compute_units.saturating_add(assertion_compute_units);
+            let compute_unit_limit = self.transaction_compute_unit_limit(&instructions);
+            // An assertion adding accounts past the limits of the message does not fit
+            // either
+            let transaction_size = self.transaction_size(&instructions).ok();
+
+            match transaction_size {
+                Some(transaction_size)
+                    if transaction_size <= self.max_transaction_size
+                        && compute_units <= compute_unit_limit =>
+                {
+                    plan = AssertionPlan {
+                        instructions,
+                        dropped: vec![],
+                        transaction_size,
+                        compute_units,
+                        compute_unit_limit,
+                    };
+                }
+                _ => dropped.extend(planned.pop()),
+            }
+        }
+        plan.dropped = dropped;
+
+        Ok(plan)
+    }
+}
+
+impl AssertionPlanBuilder {
+    fn transaction_size(&self, assertion_ixs: &[Instruction]) -> Result<usize> {
+        let ixs = [self.base_ixs.as_slice(), assertion_ixs].concat();
+        let message = if self.address_lookup_table_accounts.is_empty() {
+            VersionedMessage::Legacy(legacy::Message::new(&ixs, Some(&self.payer)))
+        } else {
+            VersionedMessage::V0(
+                v0::Message::try_compile(
+                    &self.payer,
+                    &ixs,
+                    &self.address_lookup_table_accounts,
+                    Hash::default(),
+                )
+                .map_err(LighthouseClientError::CompileError)?,
+            )
+        };
+
+        let signatures = usize::from(message.header().num_required_signatures);
+        Ok(compact_u16_length(signatures)
+            + signatures * SIGNATURE_LENGTH
+            + message.serialize().len())
+    }
+
+    fn transaction_compute_unit_limit(&self, assertion_ixs: &[Instruction]) -> u32 {
+        if let Some(compute_unit_limit) = self.compute_unit_limit {
+            return compute_unit_limit;
+        }
+
+        let set_compute_unit_limit = self.base_ixs.iter().find_map(|ix| {
+            match (compute_budget::check_id(&ix.program_id), ix.data.as_slice()) {
+                (true, [SET_COMPUTE_UNIT_LIMIT_DISCRIMINATOR, limit @ ..]) => {
+                    Some(u32::from_le_bytes(limit.try_into().ok()?))
+                }
+                _ => None,
+            }
+        });
+
+        set_compute_unit_limit.unwrap_or_else(|| {
+            // Without a limit, the runtime grants a default limit to every
+            // instruction other than the compute budget ones
+            let instructions = self
+                .base_ixs
+                .iter()
+                .chain(assertion_ixs)
+                .filter(|ix| !compute_budget::check_id(&ix.program_id))
+                .count() as u32;
+            instructions
+                .saturating_mul(DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT)
+                .min(MAX_COMPUTE_UNIT_LIMIT)
+        })
+    }
+}
+
+/// Merges the assertions into instructions, in the order of their first
+/// assertion, and estimates their compute units.
+pub(crate) fn merge_assertions(
+    assertions: &[PlannedAssertion],
+    log_level: LogLevel,
+) -> (Vec<Instruction>, u32) {
+    let mut groups: Vec<Vec<&PlannedAssertion>> = vec![];
+    for assertion in assertions {
+        match groups.iter_mut().find(|group| can_merge(group[0], assertion)) {
+            Some(group) => group.push(assertion),
+            None => groups.push(vec![assertion]),
+        }
+    }
+
+    let mut compute_units = 0u32;
+    let instructions = groups
+        .iter()
+        .map(|group| {
+            let (instruction, group_compute_units) = group_instruction(group, log_level);
+            compute_units = compute_units.saturating_add(group_compute_units);
+            instruction
+        })
+        .collect();
+
+    (instructions, compute_units)
+}
+
+// Private functions
+
+fn group_instruction(group: &[&PlannedAssertion], log_level: LogLevel) -> (Instruction, u32) {
+    macro_rules! assertion_instruction {
+        ($variant:ident, $builder:ident, $multi_builder:ident, $target_account:expr) => {{
+            let assertions = group
+                .iter()
+                .filter_map(|assertion| match assertion {
+                    PlannedAssertion::$variant { assertion, .. } => Some(assertion.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            match assertions.as_slice() {
+                [assertion] => $builder::new()
+                    .target_account($target_account)
+                    .log_level(log_level)
+                    .assertion(assertion.clone())
+                    .instruction(),
+                _ => $multi_builder::new()
+                    .target_account($target_account)
+                    .log_level(log_level)
+                    .assertions(assertions.into())
+                    .instruction(),
+            }
+        }};
+    }
+
+    let instruction = match group[0] {
+        PlannedAssertion::AccountInfo { target_account, .. } => assertion_instruction!(
+            AccountInfo,
+            AssertAccountInfoBuilder,
+            AssertAccountInfoMultiBuilder,
+            *target_account
+        ),
+        PlannedAssertion::TokenAccount { target_account, .. } => assertion_instruction!(
+            TokenAccount,
+            AssertTokenAccountBuilder,
+            AssertTokenAccountMultiBuilder,
+            *target_account
+        ),
+        PlannedAssertion::MintAccount { target_account, .. } => assertion_instruction!(
+            MintAccount,
+            AssertMintAccountBuilder,
+            AssertMintAccountMultiBuilder,
+            *target_account
+        ),
+        PlannedAssertion::StakeAccount { target_account, .. } => assertion_instruction!(
+            StakeAccount,
+            AssertStakeAccountBuilder,
+            AssertStakeAccountMultiBuilder,
+            *target_account
+        ),
+        PlannedAssertion::UpgradeableLoaderAccount { target_account, .. } => {
+            assertion_instruction!(
+                UpgradeableLoaderAccount,
+                AssertUpgradeableLoaderAccountBuilder,
+                AssertUpgradeableLoaderAccountMultiBuilder,
+                *target_account
+            )
+        }
+        PlannedAssertion::AccountData { target_account, .. } => {
+            let assertions = group
+                .iter()
+                .filter_map(|assertion| match assertion {
+                    PlannedAssertion::AccountData { assertion, .. } => Some(assertion.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            match assertions.as_slice() {
+                [assertion] => AssertAccountDataBuilder::new()
+                    .target_account(*target_account)
+                    .log_level(log_level)
+                    .offset(assertion.offset.clone())
+                    .assertion(assertion.assertion.clone())
+                    .instruction(),
+                _ => AssertAccountDataMultiBuilder::new()
+                    .target_account(*target_account)
+                    .log_level(log_level)
+                    .assertions(assertions.into())
+                    .instruction(),
+            }
+        }
+        PlannedAssertion::Instruction { instruction, compute_units } => {
+            return (instruction.clone(), *compute_units);
+        }
+    };
+
+    let assertion_compute_units = match log_level {
+        LogLevel::Silent => ASSERTION_COMPUTE_UNITS,
+        _ => ASSERTION_COMPUTE_UNITS + LOGGED_ASSERTION_COMPUTE_UNITS,
+    };
+    let compute_units = (group.len() as u32)
+        .saturating_mul(assertion_compute_units)
+        .saturating_add(ASSERTION_INSTRUCTION_COMPUTE_UNITS);
+
+    (instruction, compute_units)
+}
+
+fn can_merge(a: &PlannedAssertion, b: &PlannedAssertion) -> bool {
+    match (a, b) {
+        (
+            PlannedAssertion::AccountInfo { target_account: a, .. },
+            PlannedAssertion::AccountInfo { target_account: b, .. },
+        )
+        | (
+            PlannedAssertion::TokenAccount { target_account: a, .. },
+            PlannedAssertion::TokenAccount { target_account: b, .. },
+        )
+        | (
+            PlannedAssertion::MintAccount { target_account: a, .. },
+            PlannedAssertion::MintAccount { target_account: b, .. },
+        )
+        | (
+            PlannedAssertion::StakeAccount { target_account: a, .. },
+            PlannedAssertion::StakeAccount { target_account: b, .. },
+        )
+        | (
+            PlannedAssertion::UpgradeableLoaderAccount { target_account: a, .. },
+            PlannedAssertion::UpgradeableLoaderAccount { target_account: b, .. },
+        )
+        | (
+            PlannedAssertion::AccountData { target_account: a, .. },
+            PlannedAssertion::AccountData { target_account: b, .. },
+        ) => a == b,
+        _ => false,
+    }
+}
+
+fn compact_u16_length(value: usize) -> usize {
+    match value {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lighthouse::types::{
+        AccountInfoAssertion, EquatableOperator, IntegerOperator, TokenAccountAssertion,
+    };
+    use solana_sdk::{
+        compute_budget::ComputeBudgetInstruction, instruction::AccountMeta, system_program,
+    };
+
+    use super::*;
+
+    fn token_amount(target_account: Pubkey, value: u64) -> PlannedAssertion {
+        PlannedAssertion::TokenAccount {
+            target_account,
+            assertion: TokenAccountAssertion::Amount {
+                value,
+                operator: IntegerOperator::GreaterThanOrEqual,
+            },
+        }
+    }
+
+    fn owner(target_account: Pubkey) -> PlannedAssertion {
+        PlannedAssertion::AccountInfo {
+            target_account,
+            assertion: AccountInfoAssertion::Owner {
+                value: system_program::ID,
+                operator: EquatableOperator::Equal,
+            },
+        }
+    }
+
+    fn base_ixs(payer: Pubkey) -> Vec<Instruction> {
+        vec![Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[0; 32],
+            vec![AccountMeta::new(payer, true), AccountMeta::new(Pubkey::new_unique(), false)],
+        )]
+    }
+
+    #[test]
+    fn merges_assertions_into_multi_instructions() {
+        let payer = Pubkey::new_unique();
+        let (token_account, other_token_account) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let plan = AssertionPlanBuilder::new(payer, base_ixs(payer), 10_000)
+            .add_assertion(0, token_amount(token_account, 1))
+            .add_assertion(0, owner(token_account))
+            .add_assertion(0, token_amount(token_account, 2))
+            .add_assertion(1, token_amount(other_token_account, 3))
+            .build()
+            .unwrap();
+
+        let expected = vec![
+            AssertTokenAccountBuilder::new()
+                .target_account(other_token_account)
+                .assertion(TokenAccountAssertion::Amount {
+                    value: 3,
+                    operator: IntegerOperator::GreaterThanOrEqual,
+                })
+                .instruction(),
+            AssertTokenAccountMultiBuilder::new()
+                .target_account(token_account)
+                .assertions(
+                    vec![1, 2]
+                        .into_iter()
+                        .map(|value| TokenAccountAssertion::Amount {
+                            value,
+                            operator: IntegerOperator::GreaterThanOrEqual,
+                        })
+                        .collect::<Vec<_>>()
+                        .into(),
+                )
+                .instruction(),
+        ];
+        assert_eq!(plan.instructions[..2], expected);
+        assert_eq!(plan.instructions.len(), 3);
+        assert!(plan.dropped.is_empty());
+        assert_eq!(
+            plan.compute_units,
+            10_000 + 3 * ASSERTION_INSTRUCTION_COMPUTE_UNITS + 4 * ASSERTION_COMPUTE_UNITS
+        );
+        assert_eq!(plan.compute_unit_limit, 4 * DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT);
+    }
+
+    #[test]
+    fn drops_low_priority_assertions_to_fit_transaction_size() {
+        let payer = Pubkey::new_unique();
+        let builder = AssertionPlanBuilder::new(payer, base_ixs(payer), 10_000);
+        let base_size = builder.transaction_size(&[]).unwrap();
+
+        let accounts = (0..4).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let plan = builder
+            .max_transaction_size(base_size + 200)
+            .add_assertion(0, owner(accounts[0]))
+            .add_assertion(2, owner(accounts[1]))
+            .add_assertion(1, owner(accounts[2]))
+            .add_assertion(2, owner(accounts[3]))
+            .build()
+            .unwrap();
+
+        assert!(plan.transaction_size <= base_size + 200);
+        assert_eq!(
+            plan.instructions.iter().map(|ix| ix.accounts[0].pubkey).collect::<Vec<_>>(),
+            vec![accounts[1], accounts[3]]
+        );
+        assert_eq!(plan.dropped, vec![owner(accounts[2]), owner(accounts[0])]);
+    }
+
+    #[test]
+    fn drops_assertions_past_compute_unit_limit() {
+        let payer = Pubkey::new_unique();
+        let mut base_ixs = base_ixs(payer);
+        base_ixs.insert(0, ComputeBudgetInstruction::set_compute_unit_limit(14_000));
+        let token_account = Pubkey::new_unique();
+        let expensive_ix = Instruction::new_with_bytes(lighthouse::ID, &[0], vec![]);
+
+        let plan = AssertionPlanBuilder::new(payer, base_ixs, 10_000)
+            .add_assertion(
+                1,
+                PlannedAssertion::Instruction { instruction: expensive_ix, compute_units: 5_000 },
+            )
+            .add_assertion(0, token_amount(token_account, 1))
+            .build()
+            .unwrap();
+
+        assert_eq!(plan.compute_unit_limit, 14_000);
+        assert_eq!(
+            plan.compute_units,
+            10_000 + ASSERTION_INSTRUCTION_COMPUTE_UNITS + ASSERTION_COMPUTE_UNITS
+        );
+        assert_eq!(plan.instructions.len(), 1);
+        assert_eq!(plan.dropped.len(), 1);
+        assert!(matches!(plan.dropped[0], PlannedAssertion::Instruction { .. }));
+    }
+
+    #[test]
+    fn rejects_oversized_base_transaction() {
+        let payer = Pubkey::new_unique();
+
+        assert!(matches!(
+            AssertionPlanBuilder::new(payer, base_ixs(payer), 0).max_transaction_size(100).build(),
+            Err(LighthouseClientError::TransactionTooLarge(_))
+        ));
+    }
+}
@@ -0,0 +1,326 @@
+use std::fmt::{self, Display};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshDeserialize;
+use lighthouse::{
+    types::{AssertionResult, LogLevel},
+    ID,
+};
+use solana_sdk::instruction::Instruction;
+
+use crate::{
+    error::{LighthouseClientError, Result},
+    types::{AssertionLabels, AssertionReport, AssertionReportEntry},
+};
+
+impl AssertionLabels {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn label(mut self, instruction: &Instruction, labels: Vec<String>) -> Self {
+        self.0.push((instruction.clone(), labels));
+        self
+    }
+}
+
+impl AssertionReportEntry {
+    pub fn passed(&self) -> bool {
+        match &self.result {
+            AssertionResult::U8(_, _, _, passed)
+            | AssertionResult::U16(_, _, _, passed)
+            | AssertionResult::U32(_, _, _, passed)
+            | AssertionResult::U64(_, _, _, passed)
+            | AssertionResult::U128(_, _, _, passed)
+            | AssertionResult::I8(_, _, _, passed)
+            | AssertionResult::I16(_, _, _, passed)
+            | AssertionResult::I32(_, _, _, passed)
+            | AssertionResult::I64(_, _, _, passed)
+            | AssertionResult::I128(_, _, _, passed)
+            | AssertionResult::Pubkey(_, _, _, passed)
+            | AssertionResult::Bytes(_, _, _, passed)
+            | AssertionResult::Bool(_, _, _, passed) => *passed,
+        }
+    }
+}
+
+impl Display for AssertionReportEntry {
+    /// Renders the entry as e.g. `Whirlpool.sqrt_price expected >= X, got Y`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (actual, expected, operator) = match &self.result {
+            AssertionResult::U8(actual, expected, operator, _) => {
+                (format_value(actual), format_value(expected), *operator)
+            }
+            AssertionResult::U16(actual, expected, operator, _) => {
+                (format_value(actual), format_value(expected), *operator)
+            }
+            AssertionResult::U32(actual, expected, operator, _) => {
+                (format_value(actual), format_value(expected), *operator)
+            }
+            AssertionResult::U64(actual, expected, operator, _) => {
+                (format_value(actual), format_value(expected), *operator)
+            }
+            AssertionResult::U128(actual, expected, operator, _) => {
+                (format_value(actual), format_value(expected), *operator)
+            }
+            AssertionResult::I8(actual, expected, operator, _) => {
+                (format_value(actual), format_value(expected), *operator)
+            }
+            AssertionResult::I16(actual, expected, operator, _) => {
+                (format_value(actual), format_value(expected), *operator)
+            }
+            AssertionResult::I32(actual, expected, operator, _) => {
+                (format_value(actual), format_value(expected), *operator)
+            }
+            AssertionResult::I64(actual, expected, operator, _) => {
+                (format_value(actual), format_value(expected), *operator)
+            }
+            AssertionResult::I128(actual, expected, operator, _) => {
+                (format_value(actual), format_value(expected), *operator)
+            }
+            AssertionResult::Pubkey(actual, expected, operator, _) => {
+                (format_value(actual), format_value(expected), *operator)
+            }
+            AssertionResult::Bytes(actual, expected, operator, _) => {
+                (format!("{:?}", actual), format!("{:?}", expected), *operator)
+            }
+            AssertionResult::Bool(actual, expected, operator, _) => {
+                (format_value(actual), format_value(expected), *operator)
+            }
+        };
+        write!(
+            f,
+            "{} expected {} {}, got {}",
+            self.label,
+            operator_symbol(operator),
+            expected,
+            actual
+        )
+    }
+}
+
+impl AssertionReport {
+    pub fn passed(&self) -> bool { self.entries.iter().all(|entry| entry.passed()) }
+
+    pub fn failures(&self) -> Vec<&AssertionReportEntry> {
+        self.entries.iter().filter(|entry| !entry.passed()).collect()
+    }
+}
+
+impl Display for AssertionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            let status = if entry.passed() { "passed" } else { "failed" };
+            writeln!(f, "{}: {}", status, entry)?;
+        }
+        Ok(())
+    }
+}
+
+/// Correlate the assertion results in the logs of a transaction with the
+/// assertion instructions that produced them.
+///
+/// Results are only logged by assertions with an encoded log level
+/// (`EncodedMessage` or `FailedEncodedMessage`), and a failed transaction only
+/// has the results up to the failing assertion. Assertions without labels are
+/// labelled by their position, e.g. `instruction 2 assertion 0`.
+///
+/// At the `FailedEncodedMessage` log level only the failing result is logged,
+/// so its position does not tell which assertion of the instruction failed. It
+/// is labelled with all the labels of the instruction, e.g.
+/// `Whirlpool.liquidity or Whirlpool.sqrt_price`, or `instruction 2` without
+/// labels.
+///
+/// # Parameters
+/// - `instructions` - The instructions of the transaction, in order
+/// - `labels` - The labels of the assertion instructions
+/// - `logs` - The logs of the transaction
+pub fn parse_assertion_report(
+    instructions: &[Instruction],
+    labels: &AssertionLabels,
+    logs: &[String],
+) -> Result<AssertionReport> {
+    let mut entries = vec![];
+    let mut instruction_index: Option<usize> = None;
+    let mut result_index = 0;
+    let mut depth = 0usize;
+
+    for log in logs {
+        if let Some(encoded) = log.strip_prefix("Program data: ") {
+            let Some(index) = instruction_index else {
+                continue;
+            };
+            let Some(instruction) = instructions.get(index) else {
+                continue;
+            };
+            // Only the results logged by Lighthouse itself, not by programs it
+            // invokes or by other programs' events
+            if depth != 1 || instruction.program_id != ID {
+                continue;
+            }
+
+            let encoded = encoded.split_whitespace().next().unwrap_or_default();
+            let data = STANDARD
+                .decode(encoded)
+                .map_err(|e| LighthouseClientError::InvalidAssertionLog(e.to_string()))?;
+            let result = AssertionResult::try_from_slice(&data)
+                .map_err(|e| LighthouseClientError::InvalidAssertionLog(e.to_string()))?;
+
+            let label = result_label(instruction, index, labels, result_index);
+            entries.push(AssertionReportEntry { instruction_index: index, label, result });
+            result_index += 1;
+        } else if let Some(program_log) = log.strip_prefix("Program ") {
+            if program_log.ends_with(" invoke [1]") {
+                instruction_index = Some(instruction_index.map_or(0, |index| index + 1));
+                result_index = 0;
+                depth = 1;
+            } else if program_log.contains(" invoke [") {
+                depth += 1;
+            } else if program_log.ends_with(" success") || program_log.contains(" failed: ") {
+                depth = depth.saturating_sub(1);
+            }
+        }
+    }
+
+    Ok(AssertionReport { entries })
+}
+
+/// Correlate the assertion results of a failed litesvm transaction with the
+/// assertion instructions that produced them.
+///
+/// # Parameters
+/// - `instructions` - The instructions of the transaction, in order
+/// - `labels` - The labels of the assertion instructions
+/// - `failed_transaction` - The failed transaction
+#[cfg(feature = "litesvm")]
+pub fn parse_failed_transaction_assertion_report(
+    instructions: &[Instruction],
+    labels: &AssertionLabels,
+    failed_transaction: &litesvm::types::FailedTransactionMetadata,
+) -> Result<AssertionReport> {
+    parse_assertion_report(instructions, labels, &failed_transaction.meta.logs)
+}
+
+// Private functions
+
+fn result_label(
+    instruction: &Instruction,
+    instruction_index: usize,
+    labels: &AssertionLabels,
+    result_index: usize,
+) -> String {
+    let instruction_labels = labels
+        .0
+        .iter()
+        .find(|(labelled, _)| labelled == instruction)
+        .map(|(_, labels)| labels.as_slice());
+    // The log level follows the discriminator in the data of the assertion
+    // instructions
+    let failures_only = instruction.data.get(1) == Some(&(LogLevel::FailedEncodedMessage as u8));
+
+    match (instruction_labels, failures_only) {
+        (Some(instruction_labels), true) if !instruction_labels.is_empty() => {
+            instruction_labels.join(" or ")
+        }
+        (_, true) => format!("instruction {}", instruction_index),
+        (Some(instruction_labels), false) if result_index < instruction_labels.len() => {
+            instruction_labels[result_index].clone()
+        }
+        _ => format!("instruction {} assertion {}", instruction_index, result_index),
+    }
+}
+
+fn format_value<T: Display>(value: &Option<T>) -> String {
+    value.as_ref().map_or_else(|| "None".to_string(), |value| value.to_string())
+}
+
+fn operator_symbol(operator: u8) -> &'static str {
+    // The integer operators extend the equatable operators
+    match operator {
+        0 => "==",
+        1 => "!=",
+        2 => ">",
+        3 => "<",
+        4 => ">=",
+        5 => "<=",
+        6 => "contains",
+        7 => "does not contain",
+        _ => "?",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::pubkey::Pubkey;
+
+    use super::*;
+
+    fn encode(result: &AssertionResult) -> String {
+        format!("Program data: {}", STANDARD.encode(borsh::to_vec(result).unwrap()))
+    }
+
+    #[test]
+    fn correlates_results_with_instructions() {
+        let other_program = Pubkey::new_unique();
+        let instructions = vec![
+            Instruction::new_with_bytes(other_program, &[], vec![]),
+            Instruction::new_with_bytes(ID, &[0], vec![]),
+            Instruction::new_with_bytes(ID, &[1], vec![]),
+        ];
+        let logs = vec![
+            format!("Program {} invoke [1]", other_program),
+            encode(&AssertionResult::U8(Some(1), Some(1), 0, true)),
+            format!("Program {} success", other_program),
+            format!("Program {} invoke [1]", ID),
+            encode(&AssertionResult::U128(Some(10), Some(11), 4, false)),
+            encode(&AssertionResult::I32(Some(-1), Some(0), 5, true)),
+            format!("Program {} success", ID),
+            format!("Program {} invoke [1]", ID),
+            encode(&AssertionResult::U64(Some(5), Some(5), 0, true)),
+            format!("Program {} failed: custom program error: 0x1", ID),
+        ];
+        let labels = AssertionLabels::new()
+            .label(&instructions[1], vec!["Whirlpool.sqrt_price".to_string()]);
+
+        let report = parse_assertion_report(&instructions, &labels, &logs).unwrap();
+
+        assert_eq!(report.entries.len(), 3);
+        assert_eq!(
+            report.entries.iter().map(|entry| entry.instruction_index).collect::<Vec<_>>(),
+            vec![1, 1, 2]
+        );
+        assert!(!report.passed());
+        assert_eq!(report.failures().len(), 1);
+        assert_eq!(report.entries[0].to_string(), "Whirlpool.sqrt_price expected >= 11, got 10");
+        assert_eq!(
+            report.entries[1].to_string(),
+            "instruction 1 assertion 1 expected <= 0, got -1"
+        );
+        assert_eq!(report.entries[2].to_string(), "instruction 2 assertion 0 expected == 5, got 5");
+    }
+
+    #[test]
+    fn labels_failures_only_results_with_all_labels() {
+        let data = [0, LogLevel::FailedEncodedMessage as u8];
+        let instructions = vec![
+            Instruction::new_with_bytes(ID, &data, vec![]),
+            Instruction::new_with_bytes(ID, &data, vec![]),
+        ];
+        let logs = vec![
+            format!("Program {} invoke [1]", ID),
+            format!("Program {} success", ID),
+            format!("Program {} invoke [1]", ID),
+            encode(&AssertionResult::U64(Some(5), Some(6), 0, false)),
+            format!("Program {} failed: custom program error: 0x1", ID),
+        ];
+        let labels = AssertionLabels::new()
+            .label(&instructions[1], vec!["Pool.liquidity".to_string(), "Pool.fee".to_string()]);
+
+        let report = parse_assertion_report(&instructions, &labels, &logs).unwrap();
+        assert_eq!(
+            report.entries[0].to_string(),
+            "Pool.liquidity or Pool.fee expected == 6, got 5"
+        );
+
+        let report = parse_assertion_report(&instructions, &AssertionLabels::new(), &logs).unwrap();
+        assert_eq!(report.entries[0].to_string(), "instruction 1 expected == 6, got 5");
+    }
+}
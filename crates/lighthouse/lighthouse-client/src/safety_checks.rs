@@ -0,0 +1,203 @@
+use lighthouse::types::{
+    AccountInfoDeltaAssertion, DataValueDeltaAssertion, EquatableOperator, IntegerOperator,
+    LogLevel, MintAccountAssertion, TokenAccountAssertion,
+};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    error::Result,
+    instructions_builder::AccountDeltaInstructionsBuilder,
+    planner::merge_assertions,
+    types::{DeltaAssertion, PlannedAssertion, SafetyCheckInstructions},
+};
+
+/// The offset of the owner of a token account. Token-2022 accounts share the
+/// base layout of classic token accounts, their extensions come after it.
+const TOKEN_ACCOUNT_OWNER_OFFSET: u64 = 32;
+
+/// Builds the instructions checking token accounts, mints and balances
+/// around a set of instructions, e.g. a swap or a transfer.
+///
+/// The token and mint checks only read the base account layout, so they hold
+/// for classic token and Token-2022 accounts alike, whatever their extensions.
+/// The checks of the same kind on the same account are merged into a single
+/// instruction. The change checks snapshot the accounts into the memory
+/// account of the payer, which must then sign the transaction.
+pub struct SafetyChecksBuilder {
+    pub payer: Pubkey,
+    pub memory_id: Option<u8>,
+    pub used_memory_ids: Vec<u8>,
+    pub log_level: LogLevel,
+    pub assertions: Vec<PlannedAssertion>,
+    pub delta_assertions: Vec<(Pubkey, DeltaAssertion)>,
+}
+
+impl SafetyChecksBuilder {
+    pub fn new(payer: Pubkey) -> Self {
+        Self {
+            payer,
+            memory_id: None,
+            used_memory_ids: vec![],
+            log_level: LogLevel::Silent,
+            assertions: vec![],
+            delta_assertions: vec![],
+        }
+    }
+
+    pub fn memory_id(mut self, memory_id: u8) -> Self {
+        self.memory_id = Some(memory_id);
+        self
+    }
+
+    pub fn used_memory_ids(mut self, used_memory_ids: &[u8]) -> Self {
+        self.used_memory_ids = used_memory_ids.to_vec();
+        self
+    }
+
+    pub fn log_level(mut self, log_level: LogLevel) -> Self {
+        self.log_level = log_level;
+        self
+    }
+}
+
+impl SafetyChecksBuilder {
+    /// The token account holds at least `amount` tokens.
+    pub fn expect_token_balance_at_least(self, token_account: Pubkey, amount: u64) -> Self {
+        self.token_account_assertion(
+            token_account,
+            TokenAccountAssertion::Amount {
+                value: amount,
+                operator: IntegerOperator::GreaterThanOrEqual,
+            },
+        )
+    }
+
+    /// The token account holds `mint` tokens.
+    pub fn expect_token_mint(self, token_account: Pubkey, mint: Pubkey) -> Self {
+        self.token_account_assertion(
+            token_account,
+            TokenAccountAssertion::Mint { value: mint, operator: EquatableOperator::Equal },
+        )
+    }
+
+    /// The token account is owned by `owner`.
+    pub fn expect_token_owner(self, token_account: Pubkey, owner: Pubkey) -> Self {
+        self.token_account_assertion(
+            token_account,
+            TokenAccountAssertion::Owner { value: owner, operator: EquatableOperator::Equal },
+        )
+    }
+
+    /// The token account has no delegate.
+    pub fn expect_no_delegate(self, token_account: Pubkey) -> Self {
+        self.token_account_assertion(
+            token_account,
+            TokenAccountAssertion::Delegate { value: None, operator: EquatableOperator::Equal },
+        )
+    }
+
+    /// The token account has no close authority.
+    pub fn expect_no_close_authority(self, token_account: Pubkey) -> Self {
+        self.token_account_assertion(
+            token_account,
+            TokenAccountAssertion::CloseAuthority {
+                value: None,
+                operator: EquatableOperator::Equal,
+            },
+        )
+    }
+
+    /// The owner of the token account is the same before and after the
+    /// checked instructions.
+    pub fn expect_owner_unchanged(mut self, token_account: Pubkey) -> Self {
+        self.delta_assertions.push((
+            token_account,
+            DeltaAssertion::Data {
+                offset: TOKEN_ACCOUNT_OWNER_OFFSET,
+                assertion: DataValueDeltaAssertion::Bytes {
+                    length: 32,
+                    operator: EquatableOperator::Equal,
+                },
+            },
+        ));
+        self
+    }
+
+    /// The mint has no mint authority, i.e. its supply is fixed.
+    pub fn expect_mint_authority_none(self, mint: Pubkey) -> Self {
+        self.mint_account_assertion(
+            mint,
+            MintAccountAssertion::MintAuthority { value: None, operator: EquatableOperator::Equal },
+        )
+    }
+
+    /// The mint has no freeze authority.
+    pub fn expect_freeze_authority_none(self, mint: Pubkey) -> Self {
+        self.mint_account_assertion(
+            mint,
+            MintAccountAssertion::FreezeAuthority {
+                value: None,
+                operator: EquatableOperator::Equal,
+            },
+        )
+    }
+
+    /// The lamports of the account change by at least `min` and at most `max`
+    /// over the checked instructions, negative for a decrease.
+    pub fn expect_sol_balance_change(mut self, account: Pubkey, min: i64, max: i64) -> Self {
+        for (value, operator) in
+            [(min, IntegerOperator::GreaterThanOrEqual), (max, IntegerOperator::LessThanOrEqual)]
+        {
+            self.delta_assertions.push((
+                account,
+                DeltaAssertion::AccountInfo(AccountInfoDeltaAssertion::Lamports {
+                    value: value.into(),
+                    operator,
+                }),
+            ));
+        }
+        self
+    }
+
+    pub fn build(self) -> Result<SafetyCheckInstructions> {
+        let (mut after_ix, _) = merge_assertions(&self.assertions, self.log_level);
+
+        let Some((account, _)) = self.delta_assertions.first() else {
+            return Ok(SafetyCheckInstructions { before_ix: vec![], after_ix });
+        };
+        let mut delta_builder = AccountDeltaInstructionsBuilder::new(self.payer, *account)
+            .used_memory_ids(&self.used_memory_ids);
+        if let Some(memory_id) = self.memory_id {
+            delta_builder = delta_builder.memory_id(memory_id);
+        }
+        for (account, assertion) in self.delta_assertions {
+            delta_builder = delta_builder.add_account_assertion(account, assertion);
+        }
+        let delta = delta_builder.build()?;
+        after_ix.extend(delta.after_ix);
+
+        Ok(SafetyCheckInstructions { before_ix: delta.before_ix, after_ix })
+    }
+}
+
+// Private functions
+
+impl SafetyChecksBuilder {
+    fn token_account_assertion(
+        mut self,
+        target_account: Pubkey,
+        assertion: TokenAccountAssertion,
+    ) -> Self {
+        self.assertions.push(PlannedAssertion::TokenAccount { target_account, assertion });
+        self
+    }
+
+    fn mint_account_assertion(
+        mut self,
+        target_account: Pubkey,
+        assertion: MintAccountAssertion,
+    ) -> Self {
+        self.assertions.push(PlannedAssertion::MintAccount { target_account, assertion });
+        self
+    }
+}
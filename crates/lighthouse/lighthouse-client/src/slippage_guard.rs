@@ -0,0 +1,212 @@
+use lighthouse::{
+    instructions::AssertTokenAccountBuilder,
+    registry::{
+        AssertClmmPoolStateBuilder, AssertWhirlpoolBuilder, ClmmPoolStateAssertion,
+        WhirlpoolAssertion,
+    },
+    types::{
+        DataValueDeltaAssertion, EquatableOperator, IntegerOperator, LogLevel,
+        TokenAccountAssertion,
+    },
+};
+#[cfg(feature = "orca")]
+use orca_whirlpools_client::types::ExactInSwapQuote;
+#[cfg(feature = "raydium")]
+use raydium_clmm_client::{
+    math::token::{
+        apply_transfer_fee, get_max_amount_with_slippage_tolerance,
+        get_min_amount_with_slippage_tolerance, reverse_apply_transfer_fee,
+    },
+    types::{SwapV2Preview, TransferFee},
+};
+use solana_client_core::math::sqrt_price_after_impact;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::{
+    error::{LighthouseClientError, Result},
+    instructions_builder::AccountDeltaInstructionsBuilder,
+    types::{
+        DeltaAssertion, GuardedPool, SlippageGuardBounds, SlippageGuardInstructions,
+        SlippageGuardParams,
+    },
+};
+
+const BPS_DENOMINATOR: u16 = 10_000;
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: u64 = 64;
+
+/// Build the Lighthouse instructions that enforce the bounds of a swap
+/// on-chain, in addition to the swap's own threshold:
+/// - the token accounts hold the expected mints,
+/// - the input balance decreases by at most `max_amount_in`,
+/// - the output balance increases by at least `min_amount_out`,
+/// - the pool price did not move more than `price_tolerance_bps` past the
+///   quoted price after the swap.
+///
+/// The swap instructions go between `before_ix` and `after_ix`. The balance
+/// snapshots are written to the memory account `memory_id` of the payer, which
+/// must sign the transaction.
+pub fn prepare_slippage_guard_instructions(
+    params: SlippageGuardParams,
+    bounds: SlippageGuardBounds,
+) -> Result<SlippageGuardInstructions> {
+    let SlippageGuardParams {
+        payer,
+        pool,
+        token_account_in,
+        mint_in,
+        token_account_out,
+        mint_out,
+        memory_id,
+    } = params;
+
+    if bounds.price_tolerance_bps > BPS_DENOMINATOR {
+        return Err(LighthouseClientError::InvalidSlippageTolerance(bounds.price_tolerance_bps));
+    }
+
+    let balance_delta = AccountDeltaInstructionsBuilder::new(payer, token_account_in)
+        .memory_id(memory_id)
+        .add_assertion(token_amount_delta_assertion(
+            -i128::from(bounds.max_amount_in),
+            IntegerOperator::GreaterThanOrEqual,
+        ))
+        .add_account_assertion(
+            token_account_out,
+            token_amount_delta_assertion(
+                i128::from(bounds.min_amount_out),
+                IntegerOperator::GreaterThanOrEqual,
+            ),
+        )
+        .build()?;
+
+    let mut before_ix = vec![
+        token_mint_assertion(token_account_in, mint_in),
+        token_mint_assertion(token_account_out, mint_out),
+    ];
+    before_ix.extend(balance_delta.before_ix);
+
+    let mut after_ix = vec![pool_price_assertion(pool, &bounds)?];
+    after_ix.extend(balance_delta.after_ix);
+
+    Ok(SlippageGuardInstructions { before_ix, after_ix })
+}
+
+/// Get the slippage guard bounds of an Orca exact input swap quote. The quote
+/// amounts include transfer fees, so they bound the token account balances.
+#[cfg(feature = "orca")]
+pub fn get_orca_slippage_guard_bounds(
+    quote: &ExactInSwapQuote,
+    a_to_b: bool,
+    price_tolerance_bps: u16,
+) -> SlippageGuardBounds {
+    SlippageGuardBounds {
+        max_amount_in: quote.token_in,
+        min_amount_out: quote.token_min_out,
+        sqrt_price_after: quote.sqrt_price_after,
+        price_decreasing: a_to_b,
+        price_tolerance_bps,
+    }
+}
+
+/// Get the slippage guard bounds of a Raydium swap preview. The slippage
+/// tolerance applies to the output amount of an exact input swap, and to the
+/// input amount of an exact output swap.
+///
+/// The preview amounts are pool amounts: the transfer fee of the input mint is
+/// added to the input amount and the transfer fee of the output mint is taken
+/// from the output amount to bound the token account balances. An exact input
+/// swap should send `max_amount_in`, the smallest amount the pool receives the
+/// previewed input amount from.
+#[cfg(feature = "raydium")]
+pub fn get_raydium_slippage_guard_bounds(
+    preview: &SwapV2Preview,
+    zero_for_one: bool,
+    is_base_input: bool,
+    transfer_fee_in: Option<TransferFee>,
+    transfer_fee_out: Option<TransferFee>,
+    slippage_tolerance_bps: u16,
+    price_tolerance_bps: u16,
+) -> Result<SlippageGuardBounds> {
+    if slippage_tolerance_bps > BPS_DENOMINATOR {
+        return Err(LighthouseClientError::InvalidSlippageTolerance(slippage_tolerance_bps));
+    }
+    let transfer_fee_in = transfer_fee_in.unwrap_or_default();
+    let transfer_fee_out = transfer_fee_out.unwrap_or_default();
+    for transfer_fee in [transfer_fee_in, transfer_fee_out] {
+        if transfer_fee.fee_bps > BPS_DENOMINATOR {
+            return Err(LighthouseClientError::InvalidTransferFee(transfer_fee.fee_bps));
+        }
+    }
+
+    let amount_in = reverse_apply_transfer_fee(preview.amount_in, transfer_fee_in)
+        .map_err(|_| LighthouseClientError::ArithmeticOverflow)?;
+    let amount_out = apply_transfer_fee(preview.amount_out, transfer_fee_out)
+        .map_err(|_| LighthouseClientError::ArithmeticOverflow)?;
+    let (max_amount_in, min_amount_out) = if is_base_input {
+        let min_amount_out =
+            get_min_amount_with_slippage_tolerance(amount_out, slippage_tolerance_bps)
+                .map_err(|_| LighthouseClientError::ArithmeticOverflow)?;
+        (amount_in, min_amount_out)
+    } else {
+        let max_amount_in =
+            get_max_amount_with_slippage_tolerance(amount_in, slippage_tolerance_bps)
+                .map_err(|_| LighthouseClientError::ArithmeticOverflow)?;
+        (max_amount_in, amount_out)
+    };
+
+    Ok(SlippageGuardBounds {
+        max_amount_in,
+        min_amount_out,
+        sqrt_price_after: preview.sqrt_price_x64_after,
+        price_decreasing: zero_for_one,
+        price_tolerance_bps,
+    })
+}
+
+// Private functions
+
+fn token_amount_delta_assertion(value: i128, operator: IntegerOperator) -> DeltaAssertion {
+    DeltaAssertion::Data {
+        offset: TOKEN_ACCOUNT_AMOUNT_OFFSET,
+        assertion: DataValueDeltaAssertion::U64 { value, operator },
+    }
+}
+
+fn token_mint_assertion(token_account: Pubkey, mint: Pubkey) -> Instruction {
+    AssertTokenAccountBuilder::new()
+        .target_account(token_account)
+        .assertion(TokenAccountAssertion::Mint { value: mint, operator: EquatableOperator::Equal })
+        .log_level(LogLevel::PlaintextMessage)
+        .instruction()
+}
+
+fn pool_price_assertion(pool: GuardedPool, bounds: &SlippageGuardBounds) -> Result<Instruction> {
+    // The bound is rounded away from the quoted sqrt price, so that rounding
+    // never makes it stricter than the tolerance
+    let sqrt_price_bound = sqrt_price_after_impact(
+        bounds.sqrt_price_after,
+        bounds.price_tolerance_bps,
+        bounds.price_decreasing,
+        !bounds.price_decreasing,
+    )
+    .ok_or(LighthouseClientError::InvalidSlippageTolerance(bounds.price_tolerance_bps))?;
+    // Only the side of the bound that is adverse to the swapper is enforced
+    let operator = if bounds.price_decreasing {
+        IntegerOperator::GreaterThanOrEqual
+    } else {
+        IntegerOperator::LessThanOrEqual
+    };
+
+    let instruction = match pool {
+        GuardedPool::Whirlpool(whirlpool) => AssertWhirlpoolBuilder::new()
+            .target_account(whirlpool)
+            .assertion(WhirlpoolAssertion::SqrtPrice { value: sqrt_price_bound, operator })
+            .log_level(LogLevel::PlaintextMessage)
+            .instruction(),
+        GuardedPool::ClmmPoolState(pool_state) => AssertClmmPoolStateBuilder::new()
+            .target_account(pool_state)
+            .assertion(ClmmPoolStateAssertion::SqrtPriceX64 { value: sqrt_price_bound, operator })
+            .log_level(LogLevel::PlaintextMessage)
+            .instruction(),
+    };
+    Ok(instruction)
+}
@@ -1,11 +1,216 @@
-pub use lighthouse::types::*;
+pub use lighthouse::{types::*, AccountDataAssertion};
 pub use lighthouse_common::types::*;
 use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
 
 #[derive(Debug, Clone)]
 pub struct AccountDeltaInstructions {
     pub memory_id: u8,
+    #[deprecated(note = "use `accounts`, which lists every snapshotted account")]
     pub account: Pubkey,
+    /// The snapshotted accounts, in the order of their snapshots in memory.
+    pub accounts: Vec<Pubkey>,
     pub before_ix: Vec<Instruction>,
     pub after_ix: Vec<Instruction>,
 }
+
+/// An assertion on the change of an account over a set of instructions. The
+/// memory offsets of the account snapshots are laid out by
+/// `AccountDeltaInstructionsBuilder`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeltaAssertion {
+    /// The change of the account data value at `offset`.
+    Data { offset: u64, assertion: DataValueDeltaAssertion },
+    /// The change of an account info field.
+    AccountInfo(AccountInfoDeltaAssertion),
+}
+
+/// The pool account a swap is routed through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuardedPool {
+    Whirlpool(Pubkey),
+    ClmmPoolState(Pubkey),
+}
+
+#[derive(Debug, Clone)]
+pub struct SlippageGuardParams {
+    pub payer: Pubkey,
+    pub pool: GuardedPool,
+    pub token_account_in: Pubkey,
+    pub mint_in: Pubkey,
+    pub token_account_out: Pubkey,
+    pub mint_out: Pubkey,
+    /// The memory id of the token account balance snapshots.
+    pub memory_id: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SlippageGuardBounds {
+    /// The maximum decrease of the input token account balance.
+    pub max_amount_in: u64,
+    /// The minimum increase of the output token account balance.
+    pub min_amount_out: u64,
+    /// The quoted sqrt price of the pool after the swap.
+    pub sqrt_price_after: u128,
+    /// Whether the swap moves the pool price down, i.e. swaps token A (or
+    /// token 0) for token B (or token 1).
+    pub price_decreasing: bool,
+    /// How far the pool price may move past the quoted price, in basis points.
+    pub price_tolerance_bps: u16,
+}
+
+#[derive(Debug, Clone)]
+pub struct SlippageGuardInstructions {
+    /// Instructions to run before the swap instructions.
+    pub before_ix: Vec<Instruction>,
+    /// Instructions to run after the swap instructions.
+    pub after_ix: Vec<Instruction>,
+}
+
+/// Labels of the assertion results of instructions, in the order of their
+/// results, e.g. from the `labels` of the registry builders.
+#[derive(Debug, Clone, Default)]
+pub struct AssertionLabels(pub Vec<(Instruction, Vec<String>)>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssertionReportEntry {
+    /// The index of the assertion instruction in the transaction.
+    pub instruction_index: usize,
+    pub label: String,
+    pub result: AssertionResult,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssertionReport {
+    pub entries: Vec<AssertionReportEntry>,
+}
+
+/// An assertion to plan into a transaction. The assertions of the same kind on
+/// the same account are merged into a single `*Multi` instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlannedAssertion {
+    AccountInfo {
+        target_account: Pubkey,
+        assertion: AccountInfoAssertion,
+    },
+    TokenAccount {
+        target_account: Pubkey,
+        assertion: TokenAccountAssertion,
+    },
+    MintAccount {
+        target_account: Pubkey,
+        assertion: MintAccountAssertion,
+    },
+    StakeAccount {
+        target_account: Pubkey,
+        assertion: StakeAccountAssertion,
+    },
+    UpgradeableLoaderAccount {
+        target_account: Pubkey,
+        assertion: UpgradeableLoaderStateAssertion,
+    },
+    AccountData {
+        target_account: Pubkey,
+        assertion: AccountDataAssertion,
+    },
+    /// An assertion instruction planned as is, e.g. from a registry builder,
+    /// with an estimate of the compute units it consumes.
+    Instruction {
+        instruction: Instruction,
+        compute_units: u32,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct AssertionPlan {
+    /// The assertion instructions to append to the base instructions.
+    pub instructions: Vec<Instruction>,
+    /// The assertions left out of the transaction, by descending priority.
+    pub dropped: Vec<PlannedAssertion>,
+    /// The estimated serialized size of the transaction, signatures included.
+    pub transaction_size: usize,
+    /// The estimated compute units consumed by the transaction.
+    pub compute_units: u32,
+    /// The compute unit limit of the transaction.
+    pub compute_unit_limit: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct SafetyCheckInstructions {
+    /// Instructions to run before the checked instructions, snapshotting the
+    /// accounts of the change checks. Empty without change checks.
+    pub before_ix: Vec<Instruction>,
+    /// Instructions to run after the checked instructions.
+    pub after_ix: Vec<Instruction>,
+}
+
+/// A set of assertions, e.g. an assertion template stored in a config file.
+/// Serialized as JSON, the account addresses are base58 strings and the
+/// assertions are the serialized Lighthouse assertion types:
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "log_level": "PlaintextMessage",
+///   "assertions": [
+///     {
+///       "TokenAccount": {
+///         "target_account": "<address>",
+///         "assertion": { "Amount": { "value": 1000, "operator": "GreaterThanOrEqual" } }
+///       }
+///     },
+///     {
+///       "AccountData": {
+///         "target_account": "<address>",
+///         "offset": 8,
+///         "assertion": { "U8": { "value": 1, "operator": "Equal" } }
+///       }
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AssertionSet {
+    /// The version of the schema, `ASSERTION_SET_VERSION`.
+    pub version: u8,
+    /// The log level of the assertion instructions, `Silent` if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<LogLevel>,
+    pub assertions: Vec<AssertionSetEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum AssertionSetEntry {
+    AccountInfo {
+        #[serde(with = "serde_with::As::<serde_with::DisplayFromStr>")]
+        target_account: Pubkey,
+        assertion: AccountInfoAssertion,
+    },
+    AccountData {
+        #[serde(with = "serde_with::As::<serde_with::DisplayFromStr>")]
+        target_account: Pubkey,
+        offset: u64,
+        assertion: DataValueAssertion,
+    },
+    TokenAccount {
+        #[serde(with = "serde_with::As::<serde_with::DisplayFromStr>")]
+        target_account: Pubkey,
+        assertion: TokenAccountAssertion,
+    },
+    MintAccount {
+        #[serde(with = "serde_with::As::<serde_with::DisplayFromStr>")]
+        target_account: Pubkey,
+        assertion: MintAccountAssertion,
+    },
+    StakeAccount {
+        #[serde(with = "serde_with::As::<serde_with::DisplayFromStr>")]
+        target_account: Pubkey,
+        assertion: StakeAccountAssertion,
+    },
+    UpgradeableLoaderAccount {
+        #[serde(with = "serde_with::As::<serde_with::DisplayFromStr>")]
+        target_account: Pubkey,
+        assertion: UpgradeableLoaderStateAssertion,
+    },
+}
@@ -6,8 +6,10 @@ use std::{
 
 use borsh::{io::Read, BorshDeserialize, BorshSerialize};
 
+/// A `u64` borsh serialized as LEB128. With serde, it is serialized as the
+/// `u64` itself.
 #[derive(Clone, Debug, Eq, PartialEq)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct CompactU64(pub u64);
 
 impl BorshSerialize for CompactU64 {
@@ -111,4 +113,14 @@ mod tests {
 
         assert_eq!(leb128_vec, CompactU64(u64::MAX));
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_json_round_trip() {
+        let json = serde_json::to_string(&CompactU64(u64::MAX)).unwrap();
+
+        assert_eq!(json, "18446744073709551615");
+        assert_eq!(serde_json::from_str::<CompactU64>(&json).unwrap(), CompactU64(u64::MAX));
+        assert!(serde_json::from_str::<CompactU64>("-1").is_err());
+    }
 }
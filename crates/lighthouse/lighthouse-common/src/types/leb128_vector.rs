@@ -6,8 +6,10 @@ use std::{
 
 use borsh::{io::Read, BorshDeserialize, BorshSerialize};
 
+/// A `Vec` borsh serialized with a LEB128 length prefix. With serde, it is
+/// serialized as the `Vec` itself.
 #[derive(Clone, Eq, PartialEq)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct LEB128Vec<T: BorshSerialize + BorshDeserialize>(Vec<T>);
 
 /// Deferences the inner `Vec` type.
@@ -231,4 +233,15 @@ mod tests {
             ])
         );
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_json_round_trip() {
+        let leb128_vec: LEB128Vec<u16> = LEB128Vec(vec![1, 1000]);
+        let json = serde_json::to_string(&leb128_vec).unwrap();
+
+        assert_eq!(json, "[1,1000]");
+        assert_eq!(serde_json::from_str::<LEB128Vec<u16>>(&json).unwrap(), leb128_vec);
+        assert_eq!(serde_json::from_str::<LEB128Vec<u16>>("[]").unwrap(), LEB128Vec(vec![]));
+    }
 }
@@ -3,25 +3,94 @@ use lighthouse::{
     instructions::{
         AssertAccountDataBuilder, AssertAccountDeltaBuilder, MemoryCloseBuilder, MemoryWriteBuilder,
     },
+    registry::{
+        AssertClockBuilder, AssertProgramUpgradeBuilder, AssertStakeDelegationBuilder,
+        AssertVoteAccountBuilder, AssertWhirlpoolBuilder, ClockAssertion, ProgramUpgradeAssertion,
+        StakeDelegationAssertion, VoteAccountAssertion, VoteAccountLayout, VoteStateVersion,
+        WhirlpoolAssertion,
+    },
     types::{
-        AccountDeltaAssertion, DataValueAssertion, DataValueDeltaAssertion, IntegerOperator,
-        LogLevel, WriteType,
+        AccountDeltaAssertion, AccountInfoAssertion, AccountInfoDeltaAssertion, DataValueAssertion,
+        DataValueDeltaAssertion, EquatableOperator, IntegerOperator, LogLevel,
+        MintAccountAssertion, TokenAccountAssertion, WriteType,
+    },
+    AccountDataAssertion, ID,
+};
+use lighthouse_client::{
+    instructions_builder::AccountDeltaInstructionsBuilder,
+    planner::AssertionPlanBuilder,
+    report::parse_failed_transaction_assertion_report,
+    safety_checks::SafetyChecksBuilder,
+    slippage_guard::{
+        get_orca_slippage_guard_bounds, get_raydium_slippage_guard_bounds,
+        prepare_slippage_guard_instructions,
     },
-    ID,
+    types::{AssertionLabels, DeltaAssertion, GuardedPool, PlannedAssertion, SlippageGuardParams},
 };
 use lighthouse_common::types::CompactU64;
-use litesvm::LiteSVM;
+use litesvm::{types::FailedTransactionMetadata, LiteSVM};
+use orca_whirlpools::{
+    generated::accounts::Whirlpool, math::tick::get_array_start_index, ID as WHIRLPOOLS_PROGRAM_ID,
+};
+use orca_whirlpools_client::{
+    quote::swap_quote_by_input_token,
+    types::{ExactInSwapQuote, TickArrayFacade},
+    utils::tick_array::get_tick_array_pubkeys,
+};
+use orca_whirlpools_test::{
+    fixture::{setup_user, WhirlpoolConfigFixtureBuilder, WhirlpoolFixtureBuilder},
+    tester::WhirlpoolsTester,
+    types::{
+        CreateFeeTierParams, CreateWhirlPoolTesterParams, IncreaseLiquidityParams,
+        OpenPositionParams, SwapParams, User as WhirlpoolUser,
+    },
+};
 use program_test_utils::{
     account::get_solana_account_by_pack,
     sign_and_send_transaction,
-    token::{create_mint, get_or_create_ata, mint_to},
+    svm::update_clock,
+    token::{create_mint, create_mint_with_extensions, get_or_create_ata, mint_to, MintExtension},
+};
+use raydium_clmm::ID as CLMM_PROGRAM_ID;
+use raydium_clmm_client::math::token::get_transfer_fee;
+use raydium_clmm_test::{
+    builder::RaydiumClmmTestBuilder,
+    operations::RaydiumClmmTest,
+    types::{CreateMintsParams, OpenPositionV2Params, SwapV2Params},
 };
 use solana_client_core::types::MaybeAccount;
+#[allow(deprecated)]
+use solana_program::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    stake::{
+        self,
+        state::{Authorized, Delegation, Meta, Stake, StakeStateV2},
+    },
+    vote::{
+        self,
+        state::{LandedVote, Lockout, VoteInit, VoteState, VoteState1_14_11, VoteStateVersions},
+    },
+};
 use solana_sdk::{
+    account::Account as SolanaAccount,
+    clock::Clock,
     pubkey::Pubkey,
     signature::{Keypair, Signer},
+    system_instruction,
+    transaction::TransactionError,
 };
 use spl_token::{state::Account, ID as SPL_TOKEN_ID};
+use spl_token_2022::{extension::StateWithExtensions, state::Account as TokenAccount2022};
+
+// Constants for test values
+const MAX_AMOUNT: u64 = 1_000_000_000_000_000_000;
+const POSITION_LIQUIDITY: u128 = 10_000_000_000;
+const TRANSFER_FEE_BPS: u16 = 100;
+const SLIPPAGE_TOLERANCE_BPS: u16 = 100;
+const PRICE_TOLERANCE_BPS: u16 = 10;
+/// Moves the price of the guarded pools by about 20 basis points, past
+/// [`PRICE_TOLERANCE_BPS`] but within [`SLIPPAGE_TOLERANCE_BPS`].
+const FRONT_RUN_AMOUNT: u64 = 10_000_000;
 
 #[cfg(test)]
 mod tests {
@@ -95,6 +164,619 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_account_delta_instructions_builder() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { mut svm, admin, user0, user1, .. } = create_fixture()?;
+        let amount = 1_000_000;
+        let lamports = 1_000;
+
+        let token_amount_delta = |value: i128| DeltaAssertion::Data {
+            offset: 64,
+            assertion: DataValueDeltaAssertion::U64 { value, operator: IntegerOperator::Equal },
+        };
+        let delta = AccountDeltaInstructionsBuilder::new(admin.pubkey(), user0.token_account)
+            .used_memory_ids(&[0])
+            .add_assertion(token_amount_delta(-i128::from(amount)))
+            .add_account_assertion(user1.token_account, token_amount_delta(i128::from(amount)))
+            .add_account_assertion(
+                user1.keypair.pubkey(),
+                DeltaAssertion::AccountInfo(AccountInfoDeltaAssertion::Lamports {
+                    value: i128::from(lamports),
+                    operator: IntegerOperator::Equal,
+                }),
+            )
+            .build()?;
+
+        assert_eq!(delta.memory_id, 1);
+        assert_eq!(
+            delta.accounts,
+            vec![user0.token_account, user1.token_account, user1.keypair.pubkey()]
+        );
+
+        let transfer_ixs = vec![
+            spl_token::instruction::transfer(
+                &SPL_TOKEN_ID,
+                &user0.token_account,
+                &user1.token_account,
+                &user0.keypair.pubkey(),
+                &[],
+                amount,
+            )?,
+            system_instruction::transfer(
+                &user0.keypair.pubkey(),
+                &user1.keypair.pubkey(),
+                lamports,
+            ),
+        ];
+        let ixs = [delta.before_ix, transfer_ixs, delta.after_ix].concat();
+        let _unused =
+            sign_and_send_transaction!(&mut svm, &ixs, &admin, &[&user0.keypair]).unwrap();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_assertion_report() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { mut svm, admin, .. } = create_fixture()?;
+        let sqrt_price = 1u128 << 64;
+        let whirlpool = create_whirlpool(&mut svm, sqrt_price);
+
+        let mut builder = AssertWhirlpoolBuilder::new();
+        let _unused = builder
+            .target_account(whirlpool)
+            .log_level(LogLevel::EncodedMessage)
+            .assertion(WhirlpoolAssertion::Liquidity { value: 0, operator: IntegerOperator::Equal })
+            .assertion(WhirlpoolAssertion::SqrtPrice {
+                value: sqrt_price + 1,
+                operator: IntegerOperator::GreaterThanOrEqual,
+            });
+        let ix = builder.instruction();
+        let labels = AssertionLabels::new().label(&ix, builder.labels());
+
+        let failed = sign_and_send_transaction!(&mut svm, &[ix.clone()], &admin).unwrap_err();
+        let report = parse_failed_transaction_assertion_report(&[ix], &labels, &failed)?;
+        println!("{}", report);
+
+        assert_eq!(report.entries[0].to_string(), "Whirlpool.liquidity expected == 0, got 0");
+        assert_eq!(
+            report.failures().iter().map(|entry| entry.to_string()).collect::<Vec<_>>(),
+            vec![format!(
+                "Whirlpool.sqrt_price expected >= {}, got {}",
+                sqrt_price + 1,
+                sqrt_price
+            )]
+        );
+
+        // Only the failing result of assertion 1 is logged
+        let _unused = builder.log_level(LogLevel::FailedEncodedMessage);
+        let ix = builder.instruction();
+        let labels = AssertionLabels::new().label(&ix, builder.labels());
+
+        let failed = sign_and_send_transaction!(&mut svm, &[ix.clone()], &admin).unwrap_err();
+        let report = parse_failed_transaction_assertion_report(&[ix], &labels, &failed)?;
+
+        assert_eq!(
+            report.entries.iter().map(|entry| entry.to_string()).collect::<Vec<_>>(),
+            vec![format!(
+                "Whirlpool.liquidity or Whirlpool.sqrt_price expected >= {}, got {}",
+                sqrt_price + 1,
+                sqrt_price
+            )]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_planner_compute_units() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { mut svm, admin, mint, user0, .. } = create_fixture()?;
+        let token_account = user0.token_account;
+        let amount = 1_000_000_000_000_000_000;
+
+        // The assertions of each kind are merged into a single instruction
+        let assertions: [&dyn Fn(u64) -> PlannedAssertion; 5] = [
+            &|k| PlannedAssertion::TokenAccount {
+                target_account: token_account,
+                assertion: TokenAccountAssertion::Amount {
+                    value: amount - k,
+                    operator: IntegerOperator::GreaterThanOrEqual,
+                },
+            },
+            &|k| PlannedAssertion::AccountInfo {
+                target_account: token_account,
+                assertion: AccountInfoAssertion::Lamports {
+                    value: k,
+                    operator: IntegerOperator::GreaterThanOrEqual,
+                },
+            },
+            &|_| PlannedAssertion::AccountInfo {
+                target_account: token_account,
+                assertion: AccountInfoAssertion::Owner {
+                    value: SPL_TOKEN_ID,
+                    operator: EquatableOperator::Equal,
+                },
+            },
+            &|_| PlannedAssertion::MintAccount {
+                target_account: mint,
+                assertion: MintAccountAssertion::Decimals {
+                    value: 6,
+                    operator: IntegerOperator::Equal,
+                },
+            },
+            &|k| PlannedAssertion::AccountData {
+                target_account: token_account,
+                assertion: AccountDataAssertion {
+                    offset: CompactU64::from(64u64),
+                    assertion: DataValueAssertion::U64 {
+                        value: amount - k,
+                        operator: IntegerOperator::GreaterThanOrEqual,
+                    },
+                },
+            },
+        ];
+        for assertion in assertions {
+            for log_level in [LogLevel::Silent, LogLevel::PlaintextMessage] {
+                for count in [1, 2, 5, 16] {
+                    let plan = (0..count)
+                        .fold(
+                            AssertionPlanBuilder::new(admin.pubkey(), vec![], 0)
+                                .log_level(log_level),
+                            |builder, k| builder.add_assertion(0, assertion(k)),
+                        )
+                        .build()?;
+                    assert_eq!(plan.instructions.len(), 1);
+
+                    svm.expire_blockhash();
+                    let metadata =
+                        sign_and_send_transaction!(&mut svm, &plan.instructions, &admin).unwrap();
+                    assert!(
+                        metadata.compute_units_consumed <= u64::from(plan.compute_units),
+                        "{:?} x{count} at {log_level:?}: {} compute units consumed, {} estimated",
+                        assertion(0),
+                        metadata.compute_units_consumed,
+                        plan.compute_units,
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_safety_checks() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { mut svm, admin, .. } = create_fixture()?;
+        // Token-2022 accounts of a transfer fee mint are longer than classic token
+        // accounts
+        let (mint, _) = create_mint_with_extensions(
+            &mut svm,
+            &admin,
+            &admin.pubkey(),
+            6,
+            &[MintExtension::TransferFee { fee_bps: 100, max_fee: 1_000 }],
+        )?;
+        let user0 = create_user(&mut svm, &admin, &mint);
+        let user1 = create_user(&mut svm, &admin, &mint);
+        let balance = 1_000_000_000_000_000_000;
+        let amount = 1_000_000;
+
+        let transfer_ix = spl_token_2022::instruction::transfer_checked(
+            &spl_token_2022::ID,
+            &user0.token_account,
+            &mint,
+            &user1.token_account,
+            &user0.keypair.pubkey(),
+            &[],
+            amount,
+            6,
+        )?;
+        let checks = |builder: SafetyChecksBuilder| -> Result<Vec<_>, Box<dyn std::error::Error>> {
+            let checks = builder.build()?;
+            Ok([checks.before_ix, vec![transfer_ix.clone()], checks.after_ix].concat())
+        };
+
+        // The transfer fee is capped at `max_fee`
+        let ixs = checks(
+            SafetyChecksBuilder::new(admin.pubkey())
+                .expect_token_balance_at_least(user1.token_account, balance + amount - 1_000)
+                .expect_token_mint(user1.token_account, mint)
+                .expect_no_delegate(user0.token_account)
+                .expect_no_close_authority(user0.token_account)
+                .expect_owner_unchanged(user0.token_account)
+                .expect_sol_balance_change(user0.keypair.pubkey(), 0, 0),
+        )?;
+        let _unused =
+            sign_and_send_transaction!(&mut svm, &ixs, &admin, &[&user0.keypair]).unwrap();
+
+        let ixs = checks(
+            SafetyChecksBuilder::new(admin.pubkey())
+                .expect_token_balance_at_least(user1.token_account, balance + 2 * amount),
+        )?;
+        assert!(sign_and_send_transaction!(&mut svm, &ixs, &admin, &[&user0.keypair]).is_err());
+
+        let ixs =
+            checks(SafetyChecksBuilder::new(admin.pubkey()).expect_mint_authority_none(mint))?;
+        assert!(sign_and_send_transaction!(&mut svm, &ixs, &admin, &[&user0.keypair]).is_err());
+
+        // The transfer leaves the lamports of the token account owner unchanged
+        let ixs = checks(SafetyChecksBuilder::new(admin.pubkey()).expect_sol_balance_change(
+            user0.keypair.pubkey(),
+            1,
+            i64::MAX,
+        ))?;
+        assert!(sign_and_send_transaction!(&mut svm, &ixs, &admin, &[&user0.keypair]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_program_upgrade_assertion() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { mut svm, admin, .. } = create_fixture()?;
+        let program_id = Pubkey::new_unique();
+        let upgrade_authority = Pubkey::new_unique();
+        let slot = 42;
+        let _unused = create_program_data(&mut svm, &program_id, slot, Some(upgrade_authority));
+
+        let mut builder = AssertProgramUpgradeBuilder::new();
+        let _unused = builder
+            .program(program_id)
+            .log_level(LogLevel::PlaintextMessage)
+            .assertion(ProgramUpgradeAssertion::UpgradeAuthority {
+                value: Some(upgrade_authority),
+                operator: EquatableOperator::Equal,
+            })
+            .assertion(ProgramUpgradeAssertion::Slot {
+                value: slot,
+                operator: IntegerOperator::Equal,
+            });
+        let _unused =
+            sign_and_send_transaction!(&mut svm, &[builder.instruction()], &admin).unwrap();
+
+        // The program is upgraded after its program data was read
+        let _unused = create_program_data(&mut svm, &program_id, slot + 1, Some(upgrade_authority));
+        let _unused = builder.log_level(LogLevel::FailedPlaintextMessage);
+        assert!(sign_and_send_transaction!(&mut svm, &[builder.instruction()], &admin).is_err());
+
+        let mut builder = AssertProgramUpgradeBuilder::new();
+        let _unused =
+            builder.program(program_id).assertion(ProgramUpgradeAssertion::UpgradeAuthority {
+                value: Some(admin.pubkey()),
+                operator: EquatableOperator::Equal,
+            });
+        assert!(sign_and_send_transaction!(&mut svm, &[builder.instruction()], &admin).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stake_delegation_assertion() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { mut svm, admin, .. } = create_fixture()?;
+        let voter = Pubkey::new_unique();
+        let stake_account = create_stake_account(&mut svm, &admin.pubkey(), &voter, 1_000_000);
+
+        let mut builder = AssertStakeDelegationBuilder::new();
+        let _unused = builder
+            .target_account(stake_account)
+            .log_level(LogLevel::PlaintextMessage)
+            .assertion(StakeDelegationAssertion::VoterPubkey {
+                value: voter,
+                operator: EquatableOperator::Equal,
+            })
+            .assertion(StakeDelegationAssertion::Stake {
+                value: 1_000_000,
+                operator: IntegerOperator::GreaterThanOrEqual,
+            })
+            .assertion(StakeDelegationAssertion::AuthorizedWithdrawer {
+                value: admin.pubkey(),
+                operator: EquatableOperator::Equal,
+            });
+        let _unused =
+            sign_and_send_transaction!(&mut svm, &[builder.instruction()], &admin).unwrap();
+
+        let mut builder = AssertStakeDelegationBuilder::new();
+        let _unused = builder.target_account(stake_account).assertion(
+            StakeDelegationAssertion::VoterPubkey {
+                value: Pubkey::new_unique(),
+                operator: EquatableOperator::Equal,
+            },
+        );
+        assert!(sign_and_send_transaction!(&mut svm, &[builder.instruction()], &admin).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_clock_assertion() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { mut svm, admin, .. } = create_fixture()?;
+        let deadline = 1_700_000_000;
+        update_clock(&mut svm, 100, deadline - 1);
+
+        let mut builder = AssertClockBuilder::new();
+        let _unused = builder
+            .log_level(LogLevel::PlaintextMessage)
+            .assertion(ClockAssertion::UnixTimestampBefore { deadline });
+        let _unused =
+            sign_and_send_transaction!(&mut svm, &[builder.instruction()], &admin).unwrap();
+
+        let mut builder = AssertClockBuilder::new();
+        let _unused = builder.assertion(ClockAssertion::SlotBefore { slot: 101 });
+        let _unused =
+            sign_and_send_transaction!(&mut svm, &[builder.instruction()], &admin).unwrap();
+
+        update_clock(&mut svm, 101, deadline);
+        let mut builder = AssertClockBuilder::new();
+        let _unused = builder
+            .log_level(LogLevel::FailedPlaintextMessage)
+            .assertion(ClockAssertion::UnixTimestampBefore { deadline });
+        assert!(sign_and_send_transaction!(&mut svm, &[builder.instruction()], &admin).is_err());
+
+        let mut builder = AssertClockBuilder::new();
+        let _unused = builder.assertion(ClockAssertion::SlotAtLeast { slot: 101 });
+        let _unused =
+            sign_and_send_transaction!(&mut svm, &[builder.instruction()], &admin).unwrap();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_vote_account_assertion() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { mut svm, admin, .. } = create_fixture()?;
+        let vote_fixture = VoteFixture::new();
+
+        let versions: [(VoteStateVersion, VoteAccountData); 3] = [
+            (VoteStateVersion::Current, current_vote_account_data),
+            (VoteStateVersion::V1_14_11, v1_14_11_vote_account_data),
+            (VoteStateVersion::V0_23_5, v0_23_5_vote_account_data),
+        ];
+        for (version, create_data) in versions {
+            let vote_account =
+                create_vote_account(&mut svm, create_data(&vote_fixture, &[10, 11, 12], Some(5)));
+            let data = svm.get_account(&vote_account).unwrap().data;
+            let layout = VoteAccountLayout::from_account_data(&data).unwrap();
+            assert_eq!(layout.version, version);
+            assert_eq!(layout.votes_len, 3);
+            assert!(layout.has_root_slot);
+
+            let assertions = [
+                VoteAccountAssertion::NodePubkey {
+                    value: vote_fixture.node_pubkey,
+                    operator: EquatableOperator::Equal,
+                },
+                VoteAccountAssertion::AuthorizedWithdrawer {
+                    value: vote_fixture.authorized_withdrawer,
+                    operator: EquatableOperator::Equal,
+                },
+                VoteAccountAssertion::Commission { value: 7, operator: IntegerOperator::Equal },
+                VoteAccountAssertion::RootSlot { value: 5, operator: IntegerOperator::Equal },
+                VoteAccountAssertion::LastVoteSlot { value: 12, operator: IntegerOperator::Equal },
+                VoteAccountAssertion::AuthorizedVoter {
+                    epoch: 4,
+                    value: vote_fixture.voters[0].1,
+                    operator: EquatableOperator::Equal,
+                },
+            ];
+            let assert_vote_account = |svm: &mut LiteSVM, assertions: &[VoteAccountAssertion]| {
+                let mut builder = AssertVoteAccountBuilder::new();
+                let _unused = builder
+                    .target_account(vote_account)
+                    .log_level(LogLevel::PlaintextMessage)
+                    .layout(layout.clone());
+                for assertion in assertions {
+                    let _unused = builder.assertion(assertion.clone()).unwrap();
+                }
+                svm.expire_blockhash();
+                sign_and_send_transaction!(svm, &[builder.instruction()], &admin)
+            };
+
+            assert!(assert_vote_account(&mut svm, &assertions).is_ok());
+            for assertion in [
+                VoteAccountAssertion::Commission { value: 8, operator: IntegerOperator::Equal },
+                VoteAccountAssertion::RootSlot { value: 6, operator: IntegerOperator::Equal },
+                VoteAccountAssertion::LastVoteSlot { value: 11, operator: IntegerOperator::Equal },
+                VoteAccountAssertion::AuthorizedVoter {
+                    epoch: 4,
+                    value: vote_fixture.voters[1].1,
+                    operator: EquatableOperator::Equal,
+                },
+            ] {
+                assert!(assert_vote_account(&mut svm, &[assertion]).is_err());
+            }
+
+            // Another vote moves the fields following the votes
+            let data = create_data(&vote_fixture, &[10, 11, 12, 13], Some(5));
+            svm.set_account(vote_account, vote_account_with_data(data)).unwrap();
+            assert!(assert_vote_account(&mut svm, &assertions[..3]).is_ok());
+            assert!(assert_vote_account(&mut svm, &assertions[3..4]).is_err());
+            assert!(assert_vote_account(&mut svm, &assertions[4..5]).is_err());
+        }
+
+        let vote_account =
+            create_vote_account(&mut svm, current_vote_account_data(&vote_fixture, &[], None));
+        let data = svm.get_account(&vote_account).unwrap().data;
+        let mut builder = AssertVoteAccountBuilder::new();
+        let _unused = builder
+            .target_account(vote_account)
+            .layout(VoteAccountLayout::from_account_data(&data).unwrap());
+        assert!(builder
+            .assertion(VoteAccountAssertion::LastVoteSlot {
+                value: 0,
+                operator: IntegerOperator::Equal,
+            })
+            .is_none());
+        assert!(builder
+            .assertion(VoteAccountAssertion::RootSlot {
+                value: 0,
+                operator: IntegerOperator::Equal
+            })
+            .is_none());
+        assert!(builder
+            .assertion(VoteAccountAssertion::AuthorizedVoter {
+                epoch: 2,
+                value: vote_fixture.voters[0].1,
+                operator: EquatableOperator::Equal,
+            })
+            .is_none());
+        let _unused = builder
+            .assertion(VoteAccountAssertion::AuthorizedVoter {
+                epoch: 7,
+                value: vote_fixture.voters[1].1,
+                operator: EquatableOperator::Equal,
+            })
+            .unwrap();
+        assert_eq!(builder.labels()[0], "VoteState.version");
+        let _unused =
+            sign_and_send_transaction!(&mut svm, &[builder.instruction()], &admin).unwrap();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_orca_slippage_guard() -> Result<(), Box<dyn std::error::Error>> {
+        let OrcaFixture { mut svm, whirlpools_tester, user0, user1 } = create_orca_fixture()?;
+        let a_to_b = true;
+        let amount = 100_000;
+        let params = SlippageGuardParams {
+            payer: user0.keypair.pubkey(),
+            pool: GuardedPool::Whirlpool(whirlpools_tester.whirlpool),
+            token_account_in: user0.token_account_0,
+            mint_in: whirlpools_tester.token_pair.mint_a,
+            token_account_out: user0.token_account_1,
+            mint_out: whirlpools_tester.token_pair.mint_b,
+            memory_id: 0,
+        };
+        let swap_params = |amount, quote: &ExactInSwapQuote| SwapParams {
+            token_authority: user0.keypair.pubkey(),
+            token_owner_account_a: user0.token_account_0,
+            token_owner_account_b: user0.token_account_1,
+            amount,
+            other_amount_threshold: quote.token_min_out,
+            sqrt_price_limit: 0,
+            amount_specified_is_input: true,
+            a_to_b,
+        };
+
+        let quote = quote_orca_swap(&svm, &whirlpools_tester, amount, a_to_b)?;
+        let bounds = get_orca_slippage_guard_bounds(&quote, a_to_b, PRICE_TOLERANCE_BPS);
+        let guard = prepare_slippage_guard_instructions(params.clone(), bounds)?;
+
+        // The swap spends more than quoted
+        let swap_ix = whirlpools_tester.prepare_swap(&svm, swap_params(amount * 2, &quote))?;
+        let ixs = [guard.before_ix.clone(), vec![swap_ix], guard.after_ix.clone()].concat();
+        let failed = sign_and_send_transaction!(&mut svm, &ixs, &user0.keypair).unwrap_err();
+        assert_eq!(failed_instruction_index(&failed), Some(guard.before_ix.len() + 2));
+
+        // Another swap in the same direction moves the price past the tolerance
+        // before the quoted swap lands
+        let swap_ix = whirlpools_tester.prepare_swap(&svm, swap_params(amount, &quote))?;
+        let ixs = [guard.before_ix.clone(), vec![swap_ix], guard.after_ix.clone()].concat();
+        let mut front_run = swap_params(FRONT_RUN_AMOUNT, &quote);
+        front_run.token_authority = user1.keypair.pubkey();
+        front_run.token_owner_account_a = user1.token_account_0;
+        front_run.token_owner_account_b = user1.token_account_1;
+        front_run.other_amount_threshold = 0;
+        let front_run_ix = whirlpools_tester.prepare_swap(&svm, front_run)?;
+        let mut front_run_svm = svm.clone();
+        let _unused =
+            sign_and_send_transaction!(&mut front_run_svm, &[front_run_ix], &user1.keypair)
+                .unwrap();
+        let failed =
+            sign_and_send_transaction!(&mut front_run_svm, &ixs, &user0.keypair).unwrap_err();
+        assert_eq!(failed_instruction_index(&failed), Some(guard.before_ix.len() + 1));
+
+        // The quoted swap passes the guard
+        let balances_before =
+            get_token_amounts(&svm, [user0.token_account_0, user0.token_account_1]);
+        let _unused = sign_and_send_transaction!(&mut svm, &ixs, &user0.keypair).unwrap();
+        let balances_after =
+            get_token_amounts(&svm, [user0.token_account_0, user0.token_account_1]);
+        assert_eq!(balances_before[0] - balances_after[0], quote.token_in);
+        assert!(balances_after[1] - balances_before[1] >= quote.token_min_out);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_raydium_slippage_guard() -> Result<(), Box<dyn std::error::Error>> {
+        let RaydiumFixture { mut svm, clmm_test, user0, user1 } = create_raydium_fixture()?;
+        let zero_for_one = true;
+        let is_base_input = true;
+        let amount = 100_000;
+        let params = SlippageGuardParams {
+            payer: user0.keypair.pubkey(),
+            pool: GuardedPool::ClmmPoolState(clmm_test.pool_state),
+            token_account_in: user0.token_account0,
+            mint_in: clmm_test.token_pair.mint0,
+            token_account_out: user0.token_account1,
+            mint_out: clmm_test.token_pair.mint1,
+            memory_id: 0,
+        };
+        let swap_params = |user: &RaydiumUser, amount, other_amount_threshold| SwapV2Params {
+            amount,
+            other_amount_threshold,
+            sqrt_price_limit_x64: 0,
+            is_base_input,
+            zero_for_one,
+            user_token_account0: user.token_account0,
+            user_token_account1: user.token_account1,
+        };
+
+        // The preview takes the amount the pool receives, net of the transfer fee
+        let preview = clmm_test.preview_swap_v2(&svm, swap_params(&user0, amount, 0))?;
+        let [transfer_fee_in, transfer_fee_out] =
+            [clmm_test.token_pair.mint0, clmm_test.token_pair.mint1]
+                .map(|mint| get_transfer_fee(&svm.get_account(&mint).unwrap().data, 0).unwrap());
+        assert!(transfer_fee_in.is_some() && transfer_fee_out.is_some());
+        let bounds = get_raydium_slippage_guard_bounds(
+            &preview,
+            zero_for_one,
+            is_base_input,
+            transfer_fee_in,
+            transfer_fee_out,
+            SLIPPAGE_TOLERANCE_BPS,
+            PRICE_TOLERANCE_BPS,
+        )?;
+        assert!(bounds.max_amount_in > preview.amount_in);
+        assert!(bounds.min_amount_out < preview.amount_out);
+        let guard = prepare_slippage_guard_instructions(params.clone(), bounds)?;
+
+        // The swap spends more than the bounds allow
+        let swap_ix = clmm_test.prepare_swap_v2(
+            &svm,
+            swap_params(&user0, bounds.max_amount_in * 2, bounds.min_amount_out),
+            user0.keypair.pubkey(),
+        )?;
+        let ixs = [guard.before_ix.clone(), vec![swap_ix], guard.after_ix.clone()].concat();
+        let failed = sign_and_send_transaction!(&mut svm, &ixs, &user0.keypair).unwrap_err();
+        assert_eq!(failed_instruction_index(&failed), Some(guard.before_ix.len() + 2));
+
+        // Another swap in the same direction moves the price past the tolerance
+        // before the previewed swap lands
+        let swap_ix = clmm_test.prepare_swap_v2(
+            &svm,
+            swap_params(&user0, bounds.max_amount_in, bounds.min_amount_out),
+            user0.keypair.pubkey(),
+        )?;
+        let ixs = [guard.before_ix.clone(), vec![swap_ix], guard.after_ix.clone()].concat();
+        let mut front_run_svm = svm.clone();
+        let _unused = clmm_test.swap_v2(
+            &mut front_run_svm,
+            swap_params(&user1, FRONT_RUN_AMOUNT, 0),
+            &user1.keypair,
+        )?;
+        let failed =
+            sign_and_send_transaction!(&mut front_run_svm, &ixs, &user0.keypair).unwrap_err();
+        assert_eq!(failed_instruction_index(&failed), Some(guard.before_ix.len() + 1));
+
+        // The previewed swap passes the guard
+        let balances_before = get_token_amounts(&svm, [user0.token_account0, user0.token_account1]);
+        let _unused = sign_and_send_transaction!(&mut svm, &ixs, &user0.keypair).unwrap();
+        let balances_after = get_token_amounts(&svm, [user0.token_account0, user0.token_account1]);
+        assert_eq!(balances_before[0] - balances_after[0], bounds.max_amount_in);
+        assert!(balances_after[1] - balances_before[1] >= bounds.min_amount_out);
+
+        Ok(())
+    }
 }
 
 fn create_fixture() -> Result<Fixture, Box<dyn std::error::Error>> {
@@ -125,6 +807,203 @@ fn create_user(svm: &mut LiteSVM, admin: &Keypair, mint: &Pubkey) -> User {
     User { keypair: user, token_account }
 }
 
+fn create_whirlpool(svm: &mut LiteSVM, sqrt_price: u128) -> Pubkey {
+    let mut whirlpool = Whirlpool::from_bytes(&[0; Whirlpool::LEN]).unwrap();
+    whirlpool.sqrt_price = sqrt_price;
+
+    let address = Pubkey::new_unique();
+    svm.set_account(
+        address,
+        SolanaAccount {
+            lamports: 1_000_000_000,
+            data: borsh::to_vec(&whirlpool).unwrap(),
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .unwrap();
+
+    address
+}
+
+fn create_program_data(
+    svm: &mut LiteSVM,
+    program_id: &Pubkey,
+    slot: u64,
+    upgrade_authority_address: Option<Pubkey>,
+) -> Pubkey {
+    let address = bpf_loader_upgradeable::get_program_data_address(program_id);
+    let state = UpgradeableLoaderState::ProgramData { slot, upgrade_authority_address };
+    svm.set_account(
+        address,
+        SolanaAccount {
+            lamports: 1_000_000_000,
+            data: bincode::serialize(&state).unwrap(),
+            owner: bpf_loader_upgradeable::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .unwrap();
+
+    address
+}
+
+fn create_stake_account(
+    svm: &mut LiteSVM,
+    authority: &Pubkey,
+    voter_pubkey: &Pubkey,
+    stake: u64,
+) -> Pubkey {
+    let state = StakeStateV2::Stake(
+        Meta { authorized: Authorized::auto(authority), ..Meta::default() },
+        Stake {
+            delegation: Delegation { voter_pubkey: *voter_pubkey, stake, ..Delegation::default() },
+            credits_observed: 0,
+        },
+        Default::default(),
+    );
+    let mut data = bincode::serialize(&state).unwrap();
+    data.resize(StakeStateV2::size_of(), 0);
+
+    let address = Pubkey::new_unique();
+    svm.set_account(
+        address,
+        SolanaAccount {
+            lamports: 1_000_000_000,
+            data,
+            owner: stake::program::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .unwrap();
+
+    address
+}
+
+type VoteAccountData = fn(&VoteFixture, &[u64], Option<u64>) -> Vec<u8>;
+
+struct VoteFixture {
+    node_pubkey: Pubkey,
+    authorized_withdrawer: Pubkey,
+    voters: [(u64, Pubkey); 2],
+}
+
+impl VoteFixture {
+    fn new() -> Self {
+        Self {
+            node_pubkey: Pubkey::new_unique(),
+            authorized_withdrawer: Pubkey::new_unique(),
+            voters: [(3, Pubkey::new_unique()), (5, Pubkey::new_unique())],
+        }
+    }
+}
+
+fn vote_state(fixture: &VoteFixture, slots: &[u64], root_slot: Option<u64>) -> VoteState {
+    let vote_init = VoteInit {
+        node_pubkey: fixture.node_pubkey,
+        authorized_voter: fixture.voters[0].1,
+        authorized_withdrawer: fixture.authorized_withdrawer,
+        commission: 7,
+    };
+    let (epoch, next_epoch) = (fixture.voters[0].0, fixture.voters[1].0);
+    let mut vote_state = VoteState::new(&vote_init, &Clock { epoch, ..Clock::default() });
+    vote_state
+        .set_new_authorized_voter(&fixture.voters[1].1, epoch, next_epoch, |_| Ok(()))
+        .unwrap();
+    vote_state.votes = slots
+        .iter()
+        .map(|slot| LandedVote {
+            latency: 1,
+            lockout: Lockout::new_with_confirmation_count(*slot, 2),
+        })
+        .collect();
+    vote_state.root_slot = root_slot;
+    vote_state
+}
+
+fn current_vote_account_data(
+    fixture: &VoteFixture,
+    slots: &[u64],
+    root_slot: Option<u64>,
+) -> Vec<u8> {
+    let vote_state = vote_state(fixture, slots, root_slot);
+    let mut data = bincode::serialize(&VoteStateVersions::new_current(vote_state)).unwrap();
+    data.resize(VoteState::size_of(), 0);
+    data
+}
+
+fn v1_14_11_vote_account_data(
+    fixture: &VoteFixture,
+    slots: &[u64],
+    root_slot: Option<u64>,
+) -> Vec<u8> {
+    let vote_state = VoteState1_14_11::from(vote_state(fixture, slots, root_slot));
+    let mut data = bincode::serialize(&VoteStateVersions::V1_14_11(Box::new(vote_state))).unwrap();
+    data.resize(VoteState1_14_11::size_of(), 0);
+    data
+}
+
+/// `VoteState0_23_5` cannot be constructed outside of the vote interface, so
+/// its bytes are serialized by hand and checked by deserializing them.
+fn v0_23_5_vote_account_data(
+    fixture: &VoteFixture,
+    slots: &[u64],
+    root_slot: Option<u64>,
+) -> Vec<u8> {
+    let (authorized_voter_epoch, authorized_voter) = fixture.voters[0];
+    let mut data = vec![];
+    data.extend(0u32.to_le_bytes());
+    data.extend(fixture.node_pubkey.to_bytes());
+    data.extend(authorized_voter.to_bytes());
+    data.extend(authorized_voter_epoch.to_le_bytes());
+    // 32 prior voters and the index of the circular buffer
+    data.extend([0; 32 * 56 + 8]);
+    data.extend(fixture.authorized_withdrawer.to_bytes());
+    data.push(7);
+    data.extend((slots.len() as u64).to_le_bytes());
+    for slot in slots {
+        data.extend(slot.to_le_bytes());
+        data.extend(2u32.to_le_bytes());
+    }
+    match root_slot {
+        Some(root_slot) => {
+            data.push(1);
+            data.extend(root_slot.to_le_bytes());
+        }
+        None => data.push(0),
+    }
+    // Empty epoch credits and the last timestamp
+    data.extend([0; 8 + 16]);
+
+    let vote_state = bincode::deserialize::<VoteStateVersions>(&data).unwrap().convert_to_current();
+    assert_eq!(vote_state.node_pubkey, fixture.node_pubkey);
+    assert_eq!(vote_state.authorized_withdrawer, fixture.authorized_withdrawer);
+    assert_eq!(vote_state.commission, 7);
+    assert_eq!(vote_state.last_voted_slot(), slots.last().copied());
+    assert_eq!(vote_state.root_slot, root_slot);
+    data
+}
+
+fn vote_account_with_data(data: Vec<u8>) -> SolanaAccount {
+    SolanaAccount {
+        lamports: 1_000_000_000,
+        data,
+        owner: vote::program::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn create_vote_account(svm: &mut LiteSVM, data: Vec<u8>) -> Pubkey {
+    let address = Pubkey::new_unique();
+    svm.set_account(address, vote_account_with_data(data)).unwrap();
+
+    address
+}
+
 #[allow(dead_code)]
 struct Fixture {
     svm: LiteSVM,
@@ -143,3 +1022,200 @@ struct User {
     keypair: Keypair,
     token_account: Pubkey,
 }
+
+/// Quotes an exact input swap of the whirlpool through the three tick arrays
+/// the swap instruction of the tester passes.
+fn quote_orca_swap(
+    svm: &LiteSVM,
+    whirlpools_tester: &WhirlpoolsTester,
+    amount: u64,
+    a_to_b: bool,
+) -> Result<ExactInSwapQuote, Box<dyn std::error::Error>> {
+    let whirlpool = whirlpools_tester.get_whirlpool(svm, &whirlpools_tester.whirlpool)?;
+    let start_tick_index =
+        get_array_start_index(whirlpool.tick_current_index, whirlpool.tick_spacing);
+    let tick_arrays = get_tick_array_pubkeys(
+        whirlpools_tester.whirlpool,
+        start_tick_index,
+        whirlpool.tick_spacing,
+        a_to_b,
+        3,
+        whirlpools_tester.program_id,
+    );
+    let tick_arrays = whirlpools_tester.get_tick_arrays(svm, &tick_arrays)?;
+    let tick_arrays: [TickArrayFacade; 3] = [
+        tick_arrays[0].clone().into(),
+        tick_arrays[1].clone().into(),
+        tick_arrays[2].clone().into(),
+    ];
+
+    let quote = swap_quote_by_input_token(
+        amount,
+        a_to_b,
+        SLIPPAGE_TOLERANCE_BPS,
+        whirlpool.into(),
+        tick_arrays.into(),
+        None,
+        None,
+    )?;
+
+    Ok(quote)
+}
+
+fn failed_instruction_index(failed: &FailedTransactionMetadata) -> Option<usize> {
+    match failed.err {
+        TransactionError::InstructionError(index, _) => Some(usize::from(index)),
+        _ => None,
+    }
+}
+
+fn get_token_amounts<const N: usize>(svm: &LiteSVM, token_accounts: [Pubkey; N]) -> [u64; N] {
+    token_accounts.map(|token_account| {
+        let data = svm.get_account(&token_account).unwrap().data;
+        StateWithExtensions::<TokenAccount2022>::unpack(&data).unwrap().base.amount
+    })
+}
+
+fn create_orca_fixture() -> Result<OrcaFixture, Box<dyn std::error::Error>> {
+    let mut svm = LiteSVM::new().with_sigverify(false);
+    svm.add_program(ID, include_bytes!("fixtures/lighthouse.so"));
+    svm.add_program(
+        WHIRLPOOLS_PROGRAM_ID,
+        include_bytes!("../../../orca/whirlpools-test/tests/fixtures/orca_whirlpools.so"),
+    );
+    let admin = Keypair::new();
+    let _unused = svm.airdrop(&admin.pubkey(), 1_000_000_000_000).unwrap();
+
+    let whirlpool_config_fixture = WhirlpoolConfigFixtureBuilder::new()
+        .with_fee_tier_params(vec![CreateFeeTierParams { tick_spacing: 1, default_fee_rate: 3000 }])
+        .build(&mut svm, &admin)?;
+    let fee_tier = &whirlpool_config_fixture.fee_tier_list[0];
+    let whirlpool_fixture = WhirlpoolFixtureBuilder::new().build(
+        &mut svm,
+        &admin,
+        whirlpool_config_fixture.whirlpool_config,
+        fee_tier.fee_tier,
+        fee_tier.tick_spacing,
+    )?;
+    let whirlpools_tester = WhirlpoolsTester::new(CreateWhirlPoolTesterParams {
+        program_id: WHIRLPOOLS_PROGRAM_ID,
+        whirlpool_config: whirlpool_fixture.whirlpool_config,
+        fee_tier: whirlpool_fixture.fee_tier,
+        whirlpool: whirlpool_fixture.whirlpool,
+        token_pair: whirlpool_fixture.token_pair.clone(),
+        token_vault_a: whirlpool_fixture.token_vault_a,
+        token_vault_b: whirlpool_fixture.token_vault_b,
+        tick_spacing: whirlpool_fixture.tick_spacing,
+    });
+    let (mint_a, mint_b) =
+        (whirlpool_fixture.token_pair.mint_a, whirlpool_fixture.token_pair.mint_b);
+    let user0 = setup_user(&mut svm, &admin, &mint_a, &mint_b);
+    let user1 = setup_user(&mut svm, &admin, &mint_a, &mint_b);
+    let liquidity_provider = setup_user(&mut svm, &admin, &mint_a, &mint_b);
+    update_clock(&mut svm, 1, 1000);
+
+    // A position deep enough for the swaps to stay within its range
+    let (position_nft_mint, ..) = whirlpools_tester.open_position(
+        &mut svm,
+        &liquidity_provider.keypair,
+        OpenPositionParams {
+            owner: liquidity_provider.keypair.pubkey(),
+            tick_lower_index: -60,
+            tick_upper_index: 60,
+        },
+    )?;
+    let _unused = whirlpools_tester.increase_liquidity(
+        &mut svm,
+        &admin,
+        IncreaseLiquidityParams {
+            nft_owner: liquidity_provider.keypair.pubkey(),
+            position_nft_mint,
+            token_account_a: liquidity_provider.token_account_0,
+            token_account_b: liquidity_provider.token_account_1,
+            liquidity: POSITION_LIQUIDITY,
+            token_max_a: MAX_AMOUNT,
+            token_max_b: MAX_AMOUNT,
+        },
+    )?;
+
+    Ok(OrcaFixture { svm, whirlpools_tester, user0, user1 })
+}
+
+fn create_raydium_fixture() -> Result<RaydiumFixture, Box<dyn std::error::Error>> {
+    let mut svm = LiteSVM::new().with_sigverify(false);
+    svm.add_program(ID, include_bytes!("fixtures/lighthouse.so"));
+    svm.add_program(
+        CLMM_PROGRAM_ID,
+        include_bytes!("../../../raydium/clmm-test/tests/fixtures/raydium_clmm.so"),
+    );
+    svm.add_program(
+        solana_program::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"),
+        include_bytes!("../../../raydium/clmm-test/tests/fixtures/metaplex_metadata.so"),
+    );
+    let admin = Keypair::new();
+    let _unused = svm.airdrop(&admin.pubkey(), 1_000_000_000).unwrap();
+
+    // Both mints take a transfer fee, which the bounds account for
+    let transfer_fee = MintExtension::TransferFee { fee_bps: TRANSFER_FEE_BPS, max_fee: u64::MAX };
+    let clmm_test = RaydiumClmmTestBuilder::new()
+        .with_mints_params(CreateMintsParams {
+            extensions_a: vec![transfer_fee],
+            extensions_b: vec![transfer_fee],
+            ..Default::default()
+        })
+        .build(&mut svm, &admin)?;
+    let (mint0, mint1) = (clmm_test.token_pair.mint0, clmm_test.token_pair.mint1);
+    let create_user = |svm: &mut LiteSVM| {
+        let keypair = Keypair::new();
+        let _unused = svm.airdrop(&keypair.pubkey(), 1_000_000_000).unwrap();
+        let [token_account0, token_account1] = [mint0, mint1].map(|mint| {
+            let (token_account, _) =
+                get_or_create_ata(svm, &admin, &mint, &keypair.pubkey()).unwrap();
+            let _unused =
+                mint_to(svm, &admin, &mint, &token_account, &[&admin], MAX_AMOUNT).unwrap();
+            token_account
+        });
+        RaydiumUser { keypair, token_account0, token_account1 }
+    };
+    let user0 = create_user(&mut svm);
+    let user1 = create_user(&mut svm);
+    let liquidity_provider = create_user(&mut svm);
+    update_clock(&mut svm, 1, 1000);
+
+    // A position deep enough for the swaps to stay within its range
+    let _unused = clmm_test.open_position_v2(
+        &mut svm,
+        OpenPositionV2Params {
+            tick_lower_index: -120,
+            tick_upper_index: 120,
+            liquidity: POSITION_LIQUIDITY,
+            amount0_max: MAX_AMOUNT,
+            amount1_max: MAX_AMOUNT,
+            user_token_account0: liquidity_provider.token_account0,
+            user_token_account1: liquidity_provider.token_account1,
+        },
+        &liquidity_provider.keypair,
+    )?;
+
+    Ok(RaydiumFixture { svm, clmm_test, user0, user1 })
+}
+
+struct OrcaFixture {
+    svm: LiteSVM,
+    whirlpools_tester: WhirlpoolsTester,
+    user0: WhirlpoolUser,
+    user1: WhirlpoolUser,
+}
+
+struct RaydiumFixture {
+    svm: LiteSVM,
+    clmm_test: RaydiumClmmTest,
+    user0: RaydiumUser,
+    user1: RaydiumUser,
+}
+
+struct RaydiumUser {
+    keypair: Keypair,
+    token_account0: Pubkey,
+    token_account1: Pubkey,
+}
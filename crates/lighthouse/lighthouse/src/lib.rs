@@ -8,25 +8,27 @@ pub use generated::{
     programs::{LIGHTHOUSE_ID, LIGHTHOUSE_ID as ID},
     types,
 };
+pub use hooked::AccountDataAssertion;
 pub use lighthouse_common::{CompactU64, LEB128Vec};
 use solana_program::pubkey::{Pubkey, PubkeyError};
 
 pub mod instructions {
     pub use crate::generated::instructions::{
-        AssertAccountDataBuilder, AssertAccountDeltaBuilder, AssertAccountInfoBuilder,
-        AssertAccountInfoMultiBuilder, AssertBubblegumTreeConfigAccountBuilder,
-        AssertMerkleTreeAccountBuilder, AssertMintAccountBuilder, AssertMintAccountMultiBuilder,
-        AssertStakeAccountBuilder, AssertStakeAccountMultiBuilder, AssertSysvarClockBuilder,
-        AssertTokenAccountBuilder, AssertTokenAccountMultiBuilder,
-        AssertUpgradeableLoaderAccountBuilder, AssertUpgradeableLoaderAccountMultiBuilder,
-        MemoryCloseBuilder, MemoryWriteBuilder,
+        AssertAccountDataBuilder, AssertAccountDataMultiBuilder, AssertAccountDeltaBuilder,
+        AssertAccountInfoBuilder, AssertAccountInfoMultiBuilder,
+        AssertBubblegumTreeConfigAccountBuilder, AssertMerkleTreeAccountBuilder,
+        AssertMintAccountBuilder, AssertMintAccountMultiBuilder, AssertStakeAccountBuilder,
+        AssertStakeAccountMultiBuilder, AssertSysvarClockBuilder, AssertTokenAccountBuilder,
+        AssertTokenAccountMultiBuilder, AssertUpgradeableLoaderAccountBuilder,
+        AssertUpgradeableLoaderAccountMultiBuilder, MemoryCloseBuilder, MemoryWriteBuilder,
     };
 }
 
 #[cfg(feature = "cpi")]
 pub mod cpi {
     pub use crate::generated::instructions::{
-        AssertAccountDataCpiBuilder, AssertAccountDeltaCpiBuilder, AssertAccountInfoCpiBuilder,
+        AssertAccountDataCpiBuilder, AssertAccountDataMultiCpiBuilder,
+        AssertAccountDeltaCpiBuilder, AssertAccountInfoCpiBuilder,
         AssertAccountInfoMultiCpiBuilder, AssertBubblegumTreeConfigAccountCpiBuilder,
         AssertMerkleTreeAccountCpiBuilder, AssertMintAccountCpiBuilder,
         AssertMintAccountMultiCpiBuilder, AssertStakeAccountCpiBuilder,
@@ -86,8 +88,10 @@ pub fn find_memory_pda_bump_iterate(
 pub mod utils {
     use borsh::BorshDeserialize;
     use solana_sdk::{
+        compute_budget,
         instruction::{AccountMeta, Instruction},
-        message::{legacy, v0, CompileError, Message, VersionedMessage},
+        message::{legacy, v0, AddressLookupTableAccount, CompileError, Message, VersionedMessage},
+        pubkey::Pubkey,
         signer::{Signer, SignerError},
         transaction::{Transaction, VersionedTransaction},
     };
@@ -99,8 +103,10 @@ pub mod utils {
     pub enum ClientError {
         #[error("Transaction already signed")]
         TransactionAlreadySigned,
-        #[error("Address table lookups not supported")]
-        AddressTableLookupsNotSupported,
+        #[error("Address lookup table {0} not found")]
+        AddressLookupTableNotFound(Pubkey),
+        #[error("Address lookup table {0} has no address at index {1}")]
+        InvalidAddressLookupTableIndex(Pubkey, u8),
         #[error("Empty transaction")]
         EmptyTransaction,
         #[error("...")]
@@ -135,8 +141,8 @@ pub mod utils {
             return Err(ClientError::TransactionAlreadySigned);
         }
 
-        let mut merged_ixs = decompile_instruction_from_transaction(transaction)?;
-        merged_ixs.extend(ixs);
+        let merged_ixs =
+            merge_instructions(decompile_instruction_from_transaction(transaction)?, ixs);
 
         let transaction = Transaction::new_unsigned(Message::new(
             &merged_ixs,
@@ -146,21 +152,30 @@ pub mod utils {
         Ok(transaction)
     }
 
+    /// Appends instructions to a versioned transaction and signs it.
+    ///
+    /// A v0 message is recompiled against `address_lookup_table_accounts`,
+    /// which must hold the resolved lookup tables of the message, so the
+    /// accounts loaded from lookup tables stay loaded from them. Appended
+    /// compute budget instructions replace the ones of the same kind in the
+    /// transaction.
     pub fn append_instructions_to_versioned_transaction(
         transaction: &VersionedTransaction,
         ixs: Vec<Instruction>,
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
         signers: &[&dyn Signer],
     ) -> Result<VersionedTransaction, ClientError> {
         if !transaction.signatures.is_empty() {
             return Err(ClientError::TransactionAlreadySigned);
         }
 
-        if transaction.message.address_table_lookups().is_some() {
-            return Err(ClientError::AddressTableLookupsNotSupported);
-        }
-
-        let mut merged_ixs = decompile_instruction_from_versioned_transaction(transaction)?;
-        merged_ixs.extend(ixs);
+        let merged_ixs = merge_instructions(
+            decompile_instruction_from_versioned_transaction(
+                transaction,
+                address_lookup_table_accounts,
+            )?,
+            ixs,
+        );
 
         let payer = transaction
             .message
@@ -176,7 +191,7 @@ pub mod utils {
                 v0::Message::try_compile(
                     payer,
                     &merged_ixs,
-                    &[],
+                    address_lookup_table_accounts,
                     *transaction.message.recent_blockhash(),
                 )
                 .map_err(ClientError::CompileError)?,
@@ -191,23 +206,48 @@ pub mod utils {
         Ok(transaction)
     }
 
+    /// Decompiles the instructions of a versioned transaction. The addresses
+    /// of a v0 message loaded from lookup tables are resolved from
+    /// `address_lookup_table_accounts`.
     pub fn decompile_instruction_from_versioned_transaction(
         transaction: &VersionedTransaction,
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
     ) -> Result<Vec<Instruction>, ClientError> {
         if !transaction.signatures.is_empty() {
             return Err(ClientError::TransactionAlreadySigned);
         }
 
-        if transaction.message.address_table_lookups().is_some() {
-            return Err(ClientError::AddressTableLookupsNotSupported);
+        // Loaded addresses follow the static keys, all the writable ones first
+        let mut account_keys = transaction.message.static_account_keys().to_vec();
+        if let Some(lookups) = transaction.message.address_table_lookups() {
+            let mut writable_keys = vec![];
+            let mut readonly_keys = vec![];
+            for lookup in lookups {
+                let table = address_lookup_table_accounts
+                    .iter()
+                    .find(|table| table.key == lookup.account_key)
+                    .ok_or(ClientError::AddressLookupTableNotFound(lookup.account_key))?;
+                let resolve = |index: &u8| {
+                    table.addresses.get(*index as usize).copied().ok_or(
+                        ClientError::InvalidAddressLookupTableIndex(lookup.account_key, *index),
+                    )
+                };
+
+                for index in &lookup.writable_indexes {
+                    writable_keys.push(resolve(index)?);
+                }
+                for index in &lookup.readonly_indexes {
+                    readonly_keys.push(resolve(index)?);
+                }
+            }
+            account_keys.extend(writable_keys);
+            account_keys.extend(readonly_keys);
         }
 
         let mut modified_ixs = vec![];
         let compiled_ixs = transaction.message.instructions();
 
         for instruction in compiled_ixs {
-            let account_keys = transaction.message.static_account_keys();
-
             modified_ixs.push(Instruction {
                 program_id: account_keys[instruction.program_id_index as usize],
                 accounts: instruction
@@ -215,7 +255,7 @@ pub mod utils {
                     .iter()
                     .map(|index| AccountMeta {
                         pubkey: account_keys[*index as usize],
-                        is_signer: index < &transaction.message.header().num_required_signatures,
+                        is_signer: transaction.message.is_signer(*index as usize),
                         is_writable: transaction.message.is_maybe_writable(*index as usize, None),
                     })
                     .collect(),
@@ -243,7 +283,7 @@ pub mod utils {
                     .iter()
                     .map(|index| AccountMeta {
                         pubkey: transaction.message.account_keys[*index as usize],
-                        is_signer: index < &transaction.message.header.num_required_signatures,
+                        is_signer: transaction.message.is_signer(*index as usize),
                         is_writable: transaction.message.is_maybe_writable(*index as usize, None),
                     })
                     .collect(),
@@ -253,4 +293,132 @@ pub mod utils {
 
         Ok(modified_ixs)
     }
+
+    // Private functions
+
+    /// Appends `appended` to `ixs` with the compute budget instructions first.
+    /// The runtime rejects a transaction with two compute budget instructions
+    /// of the same kind, so the appended ones replace the existing ones.
+    fn merge_instructions(ixs: Vec<Instruction>, appended: Vec<Instruction>) -> Vec<Instruction> {
+        let (appended_budget_ixs, appended): (Vec<_>, Vec<_>) =
+            appended.into_iter().partition(|ix| compute_budget::check_id(&ix.program_id));
+        let (mut budget_ixs, ixs): (Vec<_>, Vec<_>) =
+            ixs.into_iter().partition(|ix| compute_budget::check_id(&ix.program_id));
+
+        budget_ixs.retain(|ix| {
+            !appended_budget_ixs.iter().any(|appended| appended.data.first() == ix.data.first())
+        });
+        budget_ixs.extend(appended_budget_ixs);
+
+        budget_ixs.into_iter().chain(ixs).chain(appended).collect()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use solana_sdk::{
+            compute_budget::ComputeBudgetInstruction, hash::Hash, signature::Keypair,
+        };
+
+        use super::*;
+
+        struct Fixture {
+            payer: Keypair,
+            table: AddressLookupTableAccount,
+            ixs: Vec<Instruction>,
+            transaction: VersionedTransaction,
+        }
+
+        fn create_fixture() -> Fixture {
+            let payer = Keypair::new();
+            let (pool, vault, program) =
+                (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+            let table = AddressLookupTableAccount {
+                key: Pubkey::new_unique(),
+                addresses: vec![Pubkey::new_unique(), vault, pool],
+            };
+            let ixs = vec![
+                ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+                Instruction::new_with_bytes(
+                    program,
+                    &[1],
+                    vec![
+                        AccountMeta::new(payer.pubkey(), true),
+                        AccountMeta::new(vault, false),
+                        AccountMeta::new_readonly(pool, false),
+                    ],
+                ),
+            ];
+            let message = v0::Message::try_compile(
+                &payer.pubkey(),
+                &ixs,
+                std::slice::from_ref(&table),
+                Hash::new_unique(),
+            )
+            .unwrap();
+            let transaction =
+                VersionedTransaction { signatures: vec![], message: VersionedMessage::V0(message) };
+
+            Fixture { payer, table, ixs, transaction }
+        }
+
+        #[test]
+        fn decompiles_lookup_table_addresses() {
+            let Fixture { table, ixs, transaction, .. } = create_fixture();
+
+            assert_eq!(transaction.message.address_table_lookups().unwrap().len(), 1);
+            assert_eq!(
+                decompile_instruction_from_versioned_transaction(&transaction, &[table]).unwrap(),
+                ixs
+            );
+        }
+
+        #[test]
+        fn decompile_requires_lookup_tables() {
+            let Fixture { table, transaction, .. } = create_fixture();
+
+            assert!(matches!(
+                decompile_instruction_from_versioned_transaction(&transaction, &[]),
+                Err(ClientError::AddressLookupTableNotFound(key)) if key == table.key
+            ));
+
+            let truncated = AddressLookupTableAccount {
+                key: table.key,
+                addresses: table.addresses[..1].to_vec(),
+            };
+            assert!(matches!(
+                decompile_instruction_from_versioned_transaction(&transaction, &[truncated]),
+                Err(ClientError::InvalidAddressLookupTableIndex(key, 1)) if key == table.key
+            ));
+        }
+
+        #[test]
+        fn appends_instructions_with_lookup_tables() {
+            let Fixture { payer, table, ixs, transaction } = create_fixture();
+            let assertion_ix = Instruction::new_with_bytes(
+                crate::ID,
+                &[2],
+                vec![AccountMeta::new_readonly(table.addresses[2], false)],
+            );
+            let budget_ix = ComputeBudgetInstruction::set_compute_unit_limit(400_000);
+            let price_ix = ComputeBudgetInstruction::set_compute_unit_price(1);
+
+            let appended = append_instructions_to_versioned_transaction(
+                &transaction,
+                vec![assertion_ix.clone(), budget_ix.clone(), price_ix.clone()],
+                std::slice::from_ref(&table),
+                &[&payer],
+            )
+            .unwrap();
+
+            assert!(appended.verify_with_results().iter().all(|verified| *verified));
+            assert_eq!(appended.message.address_table_lookups().unwrap().len(), 1);
+            assert_eq!(appended.message.recent_blockhash(), transaction.message.recent_blockhash());
+
+            let unsigned = VersionedTransaction { signatures: vec![], message: appended.message };
+            assert_eq!(
+                decompile_instruction_from_versioned_transaction(&unsigned, &[table]).unwrap(),
+                vec![budget_ix, price_ix, ixs[1].clone(), assertion_ix]
+            );
+        }
+    }
 }
@@ -0,0 +1,133 @@
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+use super::account_data_assertion;
+use crate::{
+    generated::{
+        instructions::AssertAccountDataMultiBuilder,
+        types::{DataValueAssertion, EquatableOperator, IntegerOperator, LogLevel},
+    },
+    hooked::AccountDataAssertion,
+};
+
+/// Byte offsets of the fields of a Raydium CLMM `PoolState` account,
+/// discriminator included.
+pub const CLMM_POOL_STATE_AMM_CONFIG_OFFSET: u64 = 9;
+pub const CLMM_POOL_STATE_LIQUIDITY_OFFSET: u64 = 237;
+pub const CLMM_POOL_STATE_SQRT_PRICE_X64_OFFSET: u64 = 253;
+pub const CLMM_POOL_STATE_TICK_CURRENT_OFFSET: u64 = 269;
+
+/// The trade fee rate of a Raydium pool lives in its amm config, so the fee
+/// tier is asserted through the `AmmConfig` address.
+#[derive(Debug, Clone)]
+pub enum ClmmPoolStateAssertion {
+    AmmConfig { value: Pubkey, operator: EquatableOperator },
+    Liquidity { value: u128, operator: IntegerOperator },
+    SqrtPriceX64 { value: u128, operator: IntegerOperator },
+    TickCurrent { value: i32, operator: IntegerOperator },
+}
+
+impl ClmmPoolStateAssertion {
+    /// The name of the asserted field, e.g. to label assertion results.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ClmmPoolStateAssertion::AmmConfig { .. } => "PoolState.amm_config",
+            ClmmPoolStateAssertion::Liquidity { .. } => "PoolState.liquidity",
+            ClmmPoolStateAssertion::SqrtPriceX64 { .. } => "PoolState.sqrt_price_x64",
+            ClmmPoolStateAssertion::TickCurrent { .. } => "PoolState.tick_current",
+        }
+    }
+}
+
+/// Asserts on the fields of a Raydium CLMM `PoolState` account. Every call to
+/// `assertion` adds to the assertions of the instruction, so a price range can
+/// be guarded with a lower and an upper bound.
+pub struct AssertClmmPoolStateBuilder(
+    AssertAccountDataMultiBuilder,
+    Vec<AccountDataAssertion>,
+    Vec<&'static str>,
+);
+
+impl AssertClmmPoolStateBuilder {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self { Self(AssertAccountDataMultiBuilder::new(), Vec::new(), Vec::new()) }
+
+    pub fn assertion(&mut self, assertion: ClmmPoolStateAssertion) -> &mut Self {
+        self.2.push(assertion.label());
+        let assertion = match assertion {
+            ClmmPoolStateAssertion::AmmConfig { value, operator } => account_data_assertion(
+                CLMM_POOL_STATE_AMM_CONFIG_OFFSET,
+                DataValueAssertion::Pubkey { value, operator },
+            ),
+            ClmmPoolStateAssertion::Liquidity { value, operator } => account_data_assertion(
+                CLMM_POOL_STATE_LIQUIDITY_OFFSET,
+                DataValueAssertion::U128 { value, operator },
+            ),
+            ClmmPoolStateAssertion::SqrtPriceX64 { value, operator } => account_data_assertion(
+                CLMM_POOL_STATE_SQRT_PRICE_X64_OFFSET,
+                DataValueAssertion::U128 { value, operator },
+            ),
+            ClmmPoolStateAssertion::TickCurrent { value, operator } => account_data_assertion(
+                CLMM_POOL_STATE_TICK_CURRENT_OFFSET,
+                DataValueAssertion::I32 { value, operator },
+            ),
+        };
+        self.1.push(assertion);
+        self.0.assertions(self.1.clone().into());
+        self
+    }
+
+    pub fn log_level(&mut self, log_level: LogLevel) -> &mut Self {
+        self.0.log_level(log_level);
+        self
+    }
+
+    pub fn target_account(&mut self, target_account: Pubkey) -> &mut Self {
+        self.0.target_account(target_account);
+        self
+    }
+
+    /// The labels of the assertions, in the order of their results.
+    pub fn labels(&self) -> Vec<String> { self.2.iter().map(|label| label.to_string()).collect() }
+
+    pub fn instruction(&self) -> Instruction { self.0.instruction() }
+}
+
+#[cfg(test)]
+mod tests {
+    use raydium_clmm::generated::accounts::PoolState;
+
+    use super::*;
+
+    fn read<const N: usize>(data: &[u8], offset: u64) -> [u8; N] {
+        data[offset as usize..offset as usize + N].try_into().unwrap()
+    }
+
+    #[test]
+    fn offsets_match_pool_state_layout() {
+        let mut pool_state = PoolState::from_bytes(&[0; PoolState::LEN]).unwrap();
+        pool_state.amm_config = Pubkey::new_unique();
+        pool_state.liquidity = 0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10;
+        pool_state.sqrt_price_x64 = 0x1112_1314_1516_1718_191a_1b1c_1d1e_1f20;
+        pool_state.tick_current = -0x2122_2324;
+
+        let data = borsh::to_vec(&pool_state).unwrap();
+
+        assert_eq!(data.len(), PoolState::LEN);
+        assert_eq!(
+            Pubkey::new_from_array(read(&data, CLMM_POOL_STATE_AMM_CONFIG_OFFSET)),
+            pool_state.amm_config
+        );
+        assert_eq!(
+            u128::from_le_bytes(read(&data, CLMM_POOL_STATE_LIQUIDITY_OFFSET)),
+            pool_state.liquidity
+        );
+        assert_eq!(
+            u128::from_le_bytes(read(&data, CLMM_POOL_STATE_SQRT_PRICE_X64_OFFSET)),
+            pool_state.sqrt_price_x64
+        );
+        assert_eq!(
+            i32::from_le_bytes(read(&data, CLMM_POOL_STATE_TICK_CURRENT_OFFSET)),
+            pool_state.tick_current
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use solana_program::instruction::Instruction;
+
+use crate::generated::{
+    instructions::AssertSysvarClockBuilder,
+    types::{IntegerOperator, LogLevel, SysvarClockAssertion},
+};
+
+/// Bounds on the time a transaction lands at, e.g. a deadline after which an
+/// order must not fill.
+#[derive(Debug, Clone)]
+pub enum ClockAssertion {
+    /// The unix timestamp of the cluster is strictly before `deadline`.
+    UnixTimestampBefore { deadline: i64 },
+    /// The unix timestamp of the cluster is at or after `start`.
+    UnixTimestampAtLeast { start: i64 },
+    /// The slot is strictly before `slot`.
+    SlotBefore { slot: u64 },
+    /// The slot is at or after `slot`.
+    SlotAtLeast { slot: u64 },
+}
+
+impl ClockAssertion {
+    /// The name of the asserted field, e.g. to label assertion results.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ClockAssertion::UnixTimestampBefore { .. }
+            | ClockAssertion::UnixTimestampAtLeast { .. } => "Clock.unix_timestamp",
+            ClockAssertion::SlotBefore { .. } | ClockAssertion::SlotAtLeast { .. } => "Clock.slot",
+        }
+    }
+}
+
+/// Asserts on the clock sysvar. The instruction holds a single assertion, so
+/// every call to `assertion` replaces the previous one.
+pub struct AssertClockBuilder(AssertSysvarClockBuilder, Option<&'static str>);
+
+impl AssertClockBuilder {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self { Self(AssertSysvarClockBuilder::new(), None) }
+
+    pub fn assertion(&mut self, assertion: ClockAssertion) -> &mut Self {
+        self.1 = Some(assertion.label());
+        let assertion = match assertion {
+            ClockAssertion::UnixTimestampBefore { deadline } => {
+                SysvarClockAssertion::UnixTimestamp {
+                    value: deadline,
+                    operator: IntegerOperator::LessThan,
+                }
+            }
+            ClockAssertion::UnixTimestampAtLeast { start } => SysvarClockAssertion::UnixTimestamp {
+                value: start,
+                operator: IntegerOperator::GreaterThanOrEqual,
+            },
+            ClockAssertion::SlotBefore { slot } => {
+                SysvarClockAssertion::Slot { value: slot, operator: IntegerOperator::LessThan }
+            }
+            ClockAssertion::SlotAtLeast { slot } => SysvarClockAssertion::Slot {
+                value: slot,
+                operator: IntegerOperator::GreaterThanOrEqual,
+            },
+        };
+        self.0.assertion(assertion);
+        self
+    }
+
+    pub fn log_level(&mut self, log_level: LogLevel) -> &mut Self {
+        self.0.log_level(log_level);
+        self
+    }
+
+    /// The labels of the assertions, in the order of their results.
+    pub fn labels(&self) -> Vec<String> { self.1.iter().map(|label| label.to_string()).collect() }
+
+    pub fn instruction(&self) -> Instruction { self.0.instruction() }
+}
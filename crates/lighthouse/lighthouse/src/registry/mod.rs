@@ -1,3 +1,23 @@
+pub mod clmm_pool_state_assertion;
+pub mod clock_assertion;
+pub mod orca_position_assertion;
+pub mod personal_position_assertion;
+pub mod program_upgrade_assertion;
+pub mod stake_delegation_assertion;
 pub mod vote_account_assertion;
+pub mod whirlpool_assertion;
 
+pub use clmm_pool_state_assertion::*;
+pub use clock_assertion::*;
+pub use orca_position_assertion::*;
+pub use personal_position_assertion::*;
+pub use program_upgrade_assertion::*;
+pub use stake_delegation_assertion::*;
 pub use vote_account_assertion::*;
+pub use whirlpool_assertion::*;
+
+use crate::{generated::types::DataValueAssertion, hooked::AccountDataAssertion, CompactU64};
+
+fn account_data_assertion(offset: u64, assertion: DataValueAssertion) -> AccountDataAssertion {
+    AccountDataAssertion { offset: CompactU64(offset), assertion }
+}
@@ -0,0 +1,166 @@
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+use super::account_data_assertion;
+use crate::{
+    generated::{
+        instructions::AssertAccountDataMultiBuilder,
+        types::{DataValueAssertion, EquatableOperator, IntegerOperator, LogLevel},
+    },
+    hooked::AccountDataAssertion,
+};
+
+/// Byte offsets of the fields of an Orca `Position` account, discriminator
+/// included.
+pub const ORCA_POSITION_WHIRLPOOL_OFFSET: u64 = 8;
+pub const ORCA_POSITION_POSITION_MINT_OFFSET: u64 = 40;
+pub const ORCA_POSITION_LIQUIDITY_OFFSET: u64 = 72;
+pub const ORCA_POSITION_TICK_LOWER_INDEX_OFFSET: u64 = 88;
+pub const ORCA_POSITION_TICK_UPPER_INDEX_OFFSET: u64 = 92;
+pub const ORCA_POSITION_FEE_OWED_A_OFFSET: u64 = 112;
+pub const ORCA_POSITION_FEE_OWED_B_OFFSET: u64 = 136;
+
+#[derive(Debug, Clone)]
+pub enum OrcaPositionAssertion {
+    Whirlpool { value: Pubkey, operator: EquatableOperator },
+    PositionMint { value: Pubkey, operator: EquatableOperator },
+    Liquidity { value: u128, operator: IntegerOperator },
+    TickLowerIndex { value: i32, operator: IntegerOperator },
+    TickUpperIndex { value: i32, operator: IntegerOperator },
+    FeeOwedA { value: u64, operator: IntegerOperator },
+    FeeOwedB { value: u64, operator: IntegerOperator },
+}
+
+impl OrcaPositionAssertion {
+    /// The name of the asserted field, e.g. to label assertion results.
+    pub fn label(&self) -> &'static str {
+        match self {
+            OrcaPositionAssertion::Whirlpool { .. } => "Position.whirlpool",
+            OrcaPositionAssertion::PositionMint { .. } => "Position.position_mint",
+            OrcaPositionAssertion::Liquidity { .. } => "Position.liquidity",
+            OrcaPositionAssertion::TickLowerIndex { .. } => "Position.tick_lower_index",
+            OrcaPositionAssertion::TickUpperIndex { .. } => "Position.tick_upper_index",
+            OrcaPositionAssertion::FeeOwedA { .. } => "Position.fee_owed_a",
+            OrcaPositionAssertion::FeeOwedB { .. } => "Position.fee_owed_b",
+        }
+    }
+}
+
+/// Asserts on the fields of an Orca `Position` account. Every call to
+/// `assertion` adds to the assertions of the instruction.
+pub struct AssertOrcaPositionBuilder(
+    AssertAccountDataMultiBuilder,
+    Vec<AccountDataAssertion>,
+    Vec<&'static str>,
+);
+
+impl AssertOrcaPositionBuilder {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self { Self(AssertAccountDataMultiBuilder::new(), Vec::new(), Vec::new()) }
+
+    pub fn assertion(&mut self, assertion: OrcaPositionAssertion) -> &mut Self {
+        self.2.push(assertion.label());
+        let assertion = match assertion {
+            OrcaPositionAssertion::Whirlpool { value, operator } => account_data_assertion(
+                ORCA_POSITION_WHIRLPOOL_OFFSET,
+                DataValueAssertion::Pubkey { value, operator },
+            ),
+            OrcaPositionAssertion::PositionMint { value, operator } => account_data_assertion(
+                ORCA_POSITION_POSITION_MINT_OFFSET,
+                DataValueAssertion::Pubkey { value, operator },
+            ),
+            OrcaPositionAssertion::Liquidity { value, operator } => account_data_assertion(
+                ORCA_POSITION_LIQUIDITY_OFFSET,
+                DataValueAssertion::U128 { value, operator },
+            ),
+            OrcaPositionAssertion::TickLowerIndex { value, operator } => account_data_assertion(
+                ORCA_POSITION_TICK_LOWER_INDEX_OFFSET,
+                DataValueAssertion::I32 { value, operator },
+            ),
+            OrcaPositionAssertion::TickUpperIndex { value, operator } => account_data_assertion(
+                ORCA_POSITION_TICK_UPPER_INDEX_OFFSET,
+                DataValueAssertion::I32 { value, operator },
+            ),
+            OrcaPositionAssertion::FeeOwedA { value, operator } => account_data_assertion(
+                ORCA_POSITION_FEE_OWED_A_OFFSET,
+                DataValueAssertion::U64 { value, operator },
+            ),
+            OrcaPositionAssertion::FeeOwedB { value, operator } => account_data_assertion(
+                ORCA_POSITION_FEE_OWED_B_OFFSET,
+                DataValueAssertion::U64 { value, operator },
+            ),
+        };
+        self.1.push(assertion);
+        self.0.assertions(self.1.clone().into());
+        self
+    }
+
+    pub fn log_level(&mut self, log_level: LogLevel) -> &mut Self {
+        self.0.log_level(log_level);
+        self
+    }
+
+    pub fn target_account(&mut self, target_account: Pubkey) -> &mut Self {
+        self.0.target_account(target_account);
+        self
+    }
+
+    /// The labels of the assertions, in the order of their results.
+    pub fn labels(&self) -> Vec<String> { self.2.iter().map(|label| label.to_string()).collect() }
+
+    pub fn instruction(&self) -> Instruction { self.0.instruction() }
+}
+
+#[cfg(test)]
+mod tests {
+    use orca_whirlpools::generated::accounts::Position;
+
+    use super::*;
+
+    fn read<const N: usize>(data: &[u8], offset: u64) -> [u8; N] {
+        data[offset as usize..offset as usize + N].try_into().unwrap()
+    }
+
+    #[test]
+    fn offsets_match_position_layout() {
+        let mut position = Position::from_bytes(&[0; Position::LEN]).unwrap();
+        position.whirlpool = Pubkey::new_unique();
+        position.position_mint = Pubkey::new_unique();
+        position.liquidity = 0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10;
+        position.tick_lower_index = -0x1112_1314;
+        position.tick_upper_index = 0x1516_1718;
+        position.fee_owed_a = 0x191a_1b1c_1d1e_1f20;
+        position.fee_owed_b = 0x2122_2324_2526_2728;
+
+        let data = borsh::to_vec(&position).unwrap();
+
+        assert_eq!(data.len(), Position::LEN);
+        assert_eq!(
+            Pubkey::new_from_array(read(&data, ORCA_POSITION_WHIRLPOOL_OFFSET)),
+            position.whirlpool
+        );
+        assert_eq!(
+            Pubkey::new_from_array(read(&data, ORCA_POSITION_POSITION_MINT_OFFSET)),
+            position.position_mint
+        );
+        assert_eq!(
+            u128::from_le_bytes(read(&data, ORCA_POSITION_LIQUIDITY_OFFSET)),
+            position.liquidity
+        );
+        assert_eq!(
+            i32::from_le_bytes(read(&data, ORCA_POSITION_TICK_LOWER_INDEX_OFFSET)),
+            position.tick_lower_index
+        );
+        assert_eq!(
+            i32::from_le_bytes(read(&data, ORCA_POSITION_TICK_UPPER_INDEX_OFFSET)),
+            position.tick_upper_index
+        );
+        assert_eq!(
+            u64::from_le_bytes(read(&data, ORCA_POSITION_FEE_OWED_A_OFFSET)),
+            position.fee_owed_a
+        );
+        assert_eq!(
+            u64::from_le_bytes(read(&data, ORCA_POSITION_FEE_OWED_B_OFFSET)),
+            position.fee_owed_b
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+use super::account_data_assertion;
+use crate::{
+    generated::{
+        instructions::AssertAccountDataMultiBuilder,
+        types::{DataValueAssertion, EquatableOperator, IntegerOperator, LogLevel},
+    },
+    hooked::AccountDataAssertion,
+};
+
+/// Byte offsets of the fields of a Raydium CLMM `PersonalPositionState`
+/// account, discriminator included.
+pub const PERSONAL_POSITION_NFT_MINT_OFFSET: u64 = 9;
+pub const PERSONAL_POSITION_POOL_ID_OFFSET: u64 = 41;
+pub const PERSONAL_POSITION_TICK_LOWER_INDEX_OFFSET: u64 = 73;
+pub const PERSONAL_POSITION_TICK_UPPER_INDEX_OFFSET: u64 = 77;
+pub const PERSONAL_POSITION_LIQUIDITY_OFFSET: u64 = 81;
+pub const PERSONAL_POSITION_TOKEN_FEES_OWED_0_OFFSET: u64 = 129;
+pub const PERSONAL_POSITION_TOKEN_FEES_OWED_1_OFFSET: u64 = 137;
+
+#[derive(Debug, Clone)]
+pub enum PersonalPositionAssertion {
+    NftMint { value: Pubkey, operator: EquatableOperator },
+    PoolId { value: Pubkey, operator: EquatableOperator },
+    TickLowerIndex { value: i32, operator: IntegerOperator },
+    TickUpperIndex { value: i32, operator: IntegerOperator },
+    Liquidity { value: u128, operator: IntegerOperator },
+    TokenFeesOwed0 { value: u64, operator: IntegerOperator },
+    TokenFeesOwed1 { value: u64, operator: IntegerOperator },
+}
+
+impl PersonalPositionAssertion {
+    /// The name of the asserted field, e.g. to label assertion results.
+    pub fn label(&self) -> &'static str {
+        match self {
+            PersonalPositionAssertion::NftMint { .. } => "PersonalPositionState.nft_mint",
+            PersonalPositionAssertion::PoolId { .. } => "PersonalPositionState.pool_id",
+            PersonalPositionAssertion::TickLowerIndex { .. } => {
+                "PersonalPositionState.tick_lower_index"
+            }
+            PersonalPositionAssertion::TickUpperIndex { .. } => {
+                "PersonalPositionState.tick_upper_index"
+            }
+            PersonalPositionAssertion::Liquidity { .. } => "PersonalPositionState.liquidity",
+            PersonalPositionAssertion::TokenFeesOwed0 { .. } => {
+                "PersonalPositionState.token_fees_owed0"
+            }
+            PersonalPositionAssertion::TokenFeesOwed1 { .. } => {
+                "PersonalPositionState.token_fees_owed1"
+            }
+        }
+    }
+}
+
+/// Asserts on the fields of a Raydium CLMM `PersonalPositionState` account.
+/// Every call to `assertion` adds to the assertions of the instruction.
+pub struct AssertPersonalPositionBuilder(
+    AssertAccountDataMultiBuilder,
+    Vec<AccountDataAssertion>,
+    Vec<&'static str>,
+);
+
+impl AssertPersonalPositionBuilder {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self { Self(AssertAccountDataMultiBuilder::new(), Vec::new(), Vec::new()) }
+
+    pub fn assertion(&mut self, assertion: PersonalPositionAssertion) -> &mut Self {
+        self.2.push(assertion.label());
+        let assertion = match assertion {
+            PersonalPositionAssertion::NftMint { value, operator } => account_data_assertion(
+                PERSONAL_POSITION_NFT_MINT_OFFSET,
+                DataValueAssertion::Pubkey { value, operator },
+            ),
+            PersonalPositionAssertion::PoolId { value, operator } => account_data_assertion(
+                PERSONAL_POSITION_POOL_ID_OFFSET,
+                DataValueAssertion::Pubkey { value, operator },
+            ),
+            PersonalPositionAssertion::TickLowerIndex { value, operator } => {
+                account_data_assertion(
+                    PERSONAL_POSITION_TICK_LOWER_INDEX_OFFSET,
+                    DataValueAssertion::I32 { value, operator },
+                )
+            }
+            PersonalPositionAssertion::TickUpperIndex { value, operator } => {
+                account_data_assertion(
+                    PERSONAL_POSITION_TICK_UPPER_INDEX_OFFSET,
+                    DataValueAssertion::I32 { value, operator },
+                )
+            }
+            PersonalPositionAssertion::Liquidity { value, operator } => account_data_assertion(
+                PERSONAL_POSITION_LIQUIDITY_OFFSET,
+                DataValueAssertion::U128 { value, operator },
+            ),
+            PersonalPositionAssertion::TokenFeesOwed0 { value, operator } => {
+                account_data_assertion(
+                    PERSONAL_POSITION_TOKEN_FEES_OWED_0_OFFSET,
+                    DataValueAssertion::U64 { value, operator },
+                )
+            }
+            PersonalPositionAssertion::TokenFeesOwed1 { value, operator } => {
+                account_data_assertion(
+                    PERSONAL_POSITION_TOKEN_FEES_OWED_1_OFFSET,
+                    DataValueAssertion::U64 { value, operator },
+                )
+            }
+        };
+        self.1.push(assertion);
+        self.0.assertions(self.1.clone().into());
+        self
+    }
+
+    pub fn log_level(&mut self, log_level: LogLevel) -> &mut Self {
+        self.0.log_level(log_level);
+        self
+    }
+
+    pub fn target_account(&mut self, target_account: Pubkey) -> &mut Self {
+        self.0.target_account(target_account);
+        self
+    }
+
+    /// The labels of the assertions, in the order of their results.
+    pub fn labels(&self) -> Vec<String> { self.2.iter().map(|label| label.to_string()).collect() }
+
+    pub fn instruction(&self) -> Instruction { self.0.instruction() }
+}
+
+#[cfg(test)]
+mod tests {
+    use raydium_clmm::generated::accounts::PersonalPositionState;
+
+    use super::*;
+
+    fn read<const N: usize>(data: &[u8], offset: u64) -> [u8; N] {
+        data[offset as usize..offset as usize + N].try_into().unwrap()
+    }
+
+    #[test]
+    fn offsets_match_personal_position_layout() {
+        let mut position =
+            PersonalPositionState::from_bytes(&[0; PersonalPositionState::LEN]).unwrap();
+        position.nft_mint = Pubkey::new_unique();
+        position.pool_id = Pubkey::new_unique();
+        position.tick_lower_index = -0x0102_0304;
+        position.tick_upper_index = 0x0506_0708;
+        position.liquidity = 0x090a_0b0c_0d0e_0f10_1112_1314_1516_1718;
+        position.token_fees_owed0 = 0x191a_1b1c_1d1e_1f20;
+        position.token_fees_owed1 = 0x2122_2324_2526_2728;
+
+        let data = borsh::to_vec(&position).unwrap();
+
+        assert_eq!(data.len(), PersonalPositionState::LEN);
+        assert_eq!(
+            Pubkey::new_from_array(read(&data, PERSONAL_POSITION_NFT_MINT_OFFSET)),
+            position.nft_mint
+        );
+        assert_eq!(
+            Pubkey::new_from_array(read(&data, PERSONAL_POSITION_POOL_ID_OFFSET)),
+            position.pool_id
+        );
+        assert_eq!(
+            i32::from_le_bytes(read(&data, PERSONAL_POSITION_TICK_LOWER_INDEX_OFFSET)),
+            position.tick_lower_index
+        );
+        assert_eq!(
+            i32::from_le_bytes(read(&data, PERSONAL_POSITION_TICK_UPPER_INDEX_OFFSET)),
+            position.tick_upper_index
+        );
+        assert_eq!(
+            u128::from_le_bytes(read(&data, PERSONAL_POSITION_LIQUIDITY_OFFSET)),
+            position.liquidity
+        );
+        assert_eq!(
+            u64::from_le_bytes(read(&data, PERSONAL_POSITION_TOKEN_FEES_OWED_0_OFFSET)),
+            position.token_fees_owed0
+        );
+        assert_eq!(
+            u64::from_le_bytes(read(&data, PERSONAL_POSITION_TOKEN_FEES_OWED_1_OFFSET)),
+            position.token_fees_owed1
+        );
+    }
+}
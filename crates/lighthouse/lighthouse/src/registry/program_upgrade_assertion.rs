@@ -0,0 +1,83 @@
+use solana_program::{bpf_loader_upgradeable, instruction::Instruction, pubkey::Pubkey};
+
+use crate::generated::{
+    instructions::AssertUpgradeableLoaderAccountMultiBuilder,
+    types::{
+        EquatableOperator, IntegerOperator, LogLevel, UpgradeableLoaderStateAssertion,
+        UpgradeableProgramDataAssertion,
+    },
+};
+
+/// The deployment of an upgradeable program, asserted on its program data
+/// account. Every upgrade sets the slot of the program data, so asserting the
+/// slot read before sending a transaction guards against the program being
+/// upgraded in the meantime.
+#[derive(Debug, Clone)]
+pub enum ProgramUpgradeAssertion {
+    UpgradeAuthority { value: Option<Pubkey>, operator: EquatableOperator },
+    Slot { value: u64, operator: IntegerOperator },
+}
+
+impl ProgramUpgradeAssertion {
+    /// The name of the asserted field, e.g. to label assertion results.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProgramUpgradeAssertion::UpgradeAuthority { .. } => {
+                "ProgramData.upgrade_authority_address"
+            }
+            ProgramUpgradeAssertion::Slot { .. } => "ProgramData.slot",
+        }
+    }
+}
+
+/// Asserts on the program data account of an upgradeable program. Every call
+/// to `assertion` adds to the assertions of the instruction.
+pub struct AssertProgramUpgradeBuilder(
+    AssertUpgradeableLoaderAccountMultiBuilder,
+    Vec<UpgradeableLoaderStateAssertion>,
+    Vec<&'static str>,
+);
+
+impl AssertProgramUpgradeBuilder {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self(AssertUpgradeableLoaderAccountMultiBuilder::new(), Vec::new(), Vec::new())
+    }
+
+    pub fn assertion(&mut self, assertion: ProgramUpgradeAssertion) -> &mut Self {
+        self.2.push(assertion.label());
+        let assertion = match assertion {
+            ProgramUpgradeAssertion::UpgradeAuthority { value, operator } => {
+                UpgradeableProgramDataAssertion::UpgradeAuthority { value, operator }
+            }
+            ProgramUpgradeAssertion::Slot { value, operator } => {
+                UpgradeableProgramDataAssertion::Slot { value, operator }
+            }
+        };
+        self.1.push(UpgradeableLoaderStateAssertion::ProgramData(assertion));
+        self.0.assertions(self.1.clone().into());
+        self
+    }
+
+    pub fn log_level(&mut self, log_level: LogLevel) -> &mut Self {
+        self.0.log_level(log_level);
+        self
+    }
+
+    /// Targets the program data account of `program_id`.
+    pub fn program(&mut self, program_id: Pubkey) -> &mut Self {
+        let (program_data, _) =
+            Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID);
+        self.target_account(program_data)
+    }
+
+    pub fn target_account(&mut self, target_account: Pubkey) -> &mut Self {
+        self.0.target_account(target_account);
+        self
+    }
+
+    /// The labels of the assertions, in the order of their results.
+    pub fn labels(&self) -> Vec<String> { self.2.iter().map(|label| label.to_string()).collect() }
+
+    pub fn instruction(&self) -> Instruction { self.0.instruction() }
+}
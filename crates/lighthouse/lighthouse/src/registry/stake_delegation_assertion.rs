@@ -0,0 +1,110 @@
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::generated::{
+    instructions::AssertStakeAccountMultiBuilder,
+    types::{
+        EquatableOperator, IntegerOperator, LogLevel, MetaAssertion, StakeAccountAssertion,
+        StakeAssertion,
+    },
+};
+
+/// The delegation and authorities of a stake account. The delegation fields
+/// only exist on delegated stake accounts, asserting on them fails for any
+/// other stake account.
+#[derive(Debug, Clone)]
+pub enum StakeDelegationAssertion {
+    VoterPubkey { value: Pubkey, operator: EquatableOperator },
+    Stake { value: u64, operator: IntegerOperator },
+    ActivationEpoch { value: u64, operator: IntegerOperator },
+    DeactivationEpoch { value: u64, operator: IntegerOperator },
+    AuthorizedStaker { value: Pubkey, operator: EquatableOperator },
+    AuthorizedWithdrawer { value: Pubkey, operator: EquatableOperator },
+}
+
+impl StakeDelegationAssertion {
+    /// The name of the asserted field, e.g. to label assertion results.
+    pub fn label(&self) -> &'static str {
+        match self {
+            StakeDelegationAssertion::VoterPubkey { .. } => "Stake.delegation.voter_pubkey",
+            StakeDelegationAssertion::Stake { .. } => "Stake.delegation.stake",
+            StakeDelegationAssertion::ActivationEpoch { .. } => "Stake.delegation.activation_epoch",
+            StakeDelegationAssertion::DeactivationEpoch { .. } => {
+                "Stake.delegation.deactivation_epoch"
+            }
+            StakeDelegationAssertion::AuthorizedStaker { .. } => "Meta.authorized.staker",
+            StakeDelegationAssertion::AuthorizedWithdrawer { .. } => "Meta.authorized.withdrawer",
+        }
+    }
+}
+
+/// Asserts on the delegation of a stake account. Every call to `assertion`
+/// adds to the assertions of the instruction.
+pub struct AssertStakeDelegationBuilder(
+    AssertStakeAccountMultiBuilder,
+    Vec<StakeAccountAssertion>,
+    Vec<&'static str>,
+);
+
+impl AssertStakeDelegationBuilder {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self { Self(AssertStakeAccountMultiBuilder::new(), Vec::new(), Vec::new()) }
+
+    pub fn assertion(&mut self, assertion: StakeDelegationAssertion) -> &mut Self {
+        self.2.push(assertion.label());
+        let assertion =
+            match assertion {
+                StakeDelegationAssertion::VoterPubkey { value, operator } => {
+                    StakeAccountAssertion::StakeAssertion(StakeAssertion::DelegationVoterPubkey {
+                        value,
+                        operator,
+                    })
+                }
+                StakeDelegationAssertion::Stake { value, operator } => {
+                    StakeAccountAssertion::StakeAssertion(StakeAssertion::DelegationStake {
+                        value,
+                        operator,
+                    })
+                }
+                StakeDelegationAssertion::ActivationEpoch { value, operator } => {
+                    StakeAccountAssertion::StakeAssertion(
+                        StakeAssertion::DelegationActivationEpoch { value, operator },
+                    )
+                }
+                StakeDelegationAssertion::DeactivationEpoch { value, operator } => {
+                    StakeAccountAssertion::StakeAssertion(
+                        StakeAssertion::DelegationDeactivationEpoch { value, operator },
+                    )
+                }
+                StakeDelegationAssertion::AuthorizedStaker { value, operator } => {
+                    StakeAccountAssertion::MetaAssertion(MetaAssertion::AuthorizedStaker {
+                        value,
+                        operator,
+                    })
+                }
+                StakeDelegationAssertion::AuthorizedWithdrawer { value, operator } => {
+                    StakeAccountAssertion::MetaAssertion(MetaAssertion::AuthorizedWithdrawer {
+                        value,
+                        operator,
+                    })
+                }
+            };
+        self.1.push(assertion);
+        self.0.assertions(self.1.clone().into());
+        self
+    }
+
+    pub fn log_level(&mut self, log_level: LogLevel) -> &mut Self {
+        self.0.log_level(log_level);
+        self
+    }
+
+    pub fn target_account(&mut self, target_account: Pubkey) -> &mut Self {
+        self.0.target_account(target_account);
+        self
+    }
+
+    /// The labels of the assertions, in the order of their results.
+    pub fn labels(&self) -> Vec<String> { self.2.iter().map(|label| label.to_string()).collect() }
+
+    pub fn instruction(&self) -> Instruction { self.0.instruction() }
+}
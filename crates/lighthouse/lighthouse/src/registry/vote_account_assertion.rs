@@ -1,57 +1,380 @@
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+use super::account_data_assertion;
+use crate::{
+    generated::{
+        instructions::AssertAccountDataMultiBuilder,
+        types::{DataValueAssertion, EquatableOperator, IntegerOperator, LogLevel},
+    },
+    hooked::AccountDataAssertion,
+};
+
+/// Byte offsets of the fixed fields of a vote account, `VoteStateVersions`
+/// tag included.
+pub const VOTE_STATE_VERSION_OFFSET: u64 = 0;
+pub const VOTE_STATE_NODE_PUBKEY_OFFSET: u64 = 4;
+pub const VOTE_STATE_AUTHORIZED_WITHDRAWER_OFFSET: u64 = 36;
+pub const VOTE_STATE_COMMISSION_OFFSET: u64 = 68;
+pub const VOTE_STATE_VOTES_OFFSET: u64 = 69;
+pub const VOTE_STATE_0_23_5_AUTHORIZED_VOTER_OFFSET: u64 = 36;
+pub const VOTE_STATE_0_23_5_AUTHORIZED_VOTER_EPOCH_OFFSET: u64 = 68;
+pub const VOTE_STATE_0_23_5_AUTHORIZED_WITHDRAWER_OFFSET: u64 = 1876;
+pub const VOTE_STATE_0_23_5_COMMISSION_OFFSET: u64 = 1908;
+pub const VOTE_STATE_0_23_5_VOTES_OFFSET: u64 = 1909;
+
+const LOCKOUT_LEN: u64 = 12;
+const LANDED_VOTE_LEN: u64 = 13;
+const AUTHORIZED_VOTER_LEN: u64 = 40;
+
+/// The `VoteStateVersions` variants, by tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoteStateVersion {
+    V0_23_5,
+    V1_14_11,
+    Current,
+}
+
+impl VoteStateVersion {
+    fn tag(self) -> u32 {
+        match self {
+            VoteStateVersion::V0_23_5 => 0,
+            VoteStateVersion::V1_14_11 => 1,
+            VoteStateVersion::Current => 2,
+        }
+    }
+}
+
+/// The layout of a vote account, read from its data. The fields following the
+/// votes move with the number of votes and whether there is a root slot, so
+/// the assertions on them also assert the layout is unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoteAccountLayout {
+    pub version: VoteStateVersion,
+    pub votes_len: u64,
+    pub has_root_slot: bool,
+    /// The epochs of the authorized voters, in ascending order. Version
+    /// `V0_23_5` has a single authorized voter at a fixed offset.
+    pub authorized_voter_epochs: Vec<u64>,
+}
+
+impl VoteAccountLayout {
+    /// Reads the layout of vote account data, `None` if the data is not a
+    /// vote account.
+    pub fn from_account_data(data: &[u8]) -> Option<Self> {
+        let version = match read_u32(data, VOTE_STATE_VERSION_OFFSET)? {
+            0 => VoteStateVersion::V0_23_5,
+            1 => VoteStateVersion::V1_14_11,
+            2 => VoteStateVersion::Current,
+            _ => return None,
+        };
+        let mut layout =
+            Self { version, votes_len: 0, has_root_slot: false, authorized_voter_epochs: vec![] };
+
+        layout.votes_len = read_u64(data, layout.votes_offset())?;
+        layout.has_root_slot = match data.get(usize::try_from(layout.root_slot_offset()).ok()?)? {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+
+        if version != VoteStateVersion::V0_23_5 {
+            let offset = layout.authorized_voters_offset();
+            let len = read_u64(data, offset)?;
+            layout.authorized_voter_epochs = (0..len)
+                .map(|index| read_u64(data, offset + 8 + index * AUTHORIZED_VOTER_LEN))
+                .collect::<Option<_>>()?;
+        }
+
+        Some(layout)
+    }
+
+    fn authorized_withdrawer_offset(&self) -> u64 {
+        match self.version {
+            VoteStateVersion::V0_23_5 => VOTE_STATE_0_23_5_AUTHORIZED_WITHDRAWER_OFFSET,
+            _ => VOTE_STATE_AUTHORIZED_WITHDRAWER_OFFSET,
+        }
+    }
+
+    fn commission_offset(&self) -> u64 {
+        match self.version {
+            VoteStateVersion::V0_23_5 => VOTE_STATE_0_23_5_COMMISSION_OFFSET,
+            _ => VOTE_STATE_COMMISSION_OFFSET,
+        }
+    }
+
+    fn votes_offset(&self) -> u64 {
+        match self.version {
+            VoteStateVersion::V0_23_5 => VOTE_STATE_0_23_5_VOTES_OFFSET,
+            _ => VOTE_STATE_VOTES_OFFSET,
+        }
+    }
+
+    /// The offset of the vote at `index`. Current votes are a latency byte
+    /// followed by a lockout, older votes are a lockout.
+    fn vote_offset(&self, index: u64) -> u64 {
+        match self.version {
+            VoteStateVersion::Current => self.votes_offset() + 8 + index * LANDED_VOTE_LEN,
+            _ => self.votes_offset() + 8 + index * LOCKOUT_LEN,
+        }
+    }
+
+    fn vote_slot_offset(&self, index: u64) -> u64 {
+        match self.version {
+            VoteStateVersion::Current => self.vote_offset(index) + 1,
+            _ => self.vote_offset(index),
+        }
+    }
+
+    fn root_slot_offset(&self) -> u64 { self.vote_offset(self.votes_len) }
+
+    fn authorized_voters_offset(&self) -> u64 {
+        self.root_slot_offset() + if self.has_root_slot { 9 } else { 1 }
+    }
+}
+
+impl Default for VoteAccountLayout {
+    /// The layout of a current vote account, enough to assert on its fixed
+    /// fields.
+    fn default() -> Self {
+        Self {
+            version: VoteStateVersion::Current,
+            votes_len: 0,
+            has_root_slot: false,
+            authorized_voter_epochs: vec![],
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum VoteAccountAssertion {
     AuthorizedWithdrawer {
-        value: solana_program::pubkey::Pubkey,
-        operator: crate::generated::types::EquatableOperator,
+        value: Pubkey,
+        operator: EquatableOperator,
+    },
+    NodePubkey {
+        value: Pubkey,
+        operator: EquatableOperator,
+    },
+    Commission {
+        value: u8,
+        operator: IntegerOperator,
+    },
+    /// The authorized voter for `epoch`, i.e. the one of the latest epoch up
+    /// to `epoch`.
+    AuthorizedVoter {
+        epoch: u64,
+        value: Pubkey,
+        operator: EquatableOperator,
+    },
+    RootSlot {
+        value: u64,
+        operator: IntegerOperator,
+    },
+    LastVoteSlot {
+        value: u64,
+        operator: IntegerOperator,
     },
 }
 
-pub struct AssertVoteAccountBuilder(crate::generated::instructions::AssertAccountDataMultiBuilder);
+impl VoteAccountAssertion {
+    /// The name of the asserted field, e.g. to label assertion results.
+    pub fn label(&self) -> &'static str {
+        match self {
+            VoteAccountAssertion::AuthorizedWithdrawer { .. } => "VoteState.authorized_withdrawer",
+            VoteAccountAssertion::NodePubkey { .. } => "VoteState.node_pubkey",
+            VoteAccountAssertion::Commission { .. } => "VoteState.commission",
+            VoteAccountAssertion::AuthorizedVoter { .. } => "VoteState.authorized_voter",
+            VoteAccountAssertion::RootSlot { .. } => "VoteState.root_slot",
+            VoteAccountAssertion::LastVoteSlot { .. } => "VoteState.last_vote_slot",
+        }
+    }
+}
+
+/// Asserts on the fields of a vote account. Every call to `assertion` adds to
+/// the assertions of the instruction, along with the assertions on the version
+/// and layout of the account they rely on.
+///
+/// Without a `layout`, the account is asserted to be a current vote account
+/// and only its fixed fields can be asserted on. The layout must be set before
+/// adding assertions.
+pub struct AssertVoteAccountBuilder(
+    AssertAccountDataMultiBuilder,
+    Vec<AccountDataAssertion>,
+    Vec<&'static str>,
+    VoteAccountLayout,
+);
 
 impl AssertVoteAccountBuilder {
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        Self(crate::generated::instructions::AssertAccountDataMultiBuilder::new())
+        Self(AssertAccountDataMultiBuilder::new(), Vec::new(), Vec::new(), Default::default())
+    }
+
+    pub fn layout(&mut self, layout: VoteAccountLayout) -> &mut Self {
+        self.3 = layout;
+        self
     }
 
-    #[allow(clippy::redundant_closure_call)]
-    #[allow(clippy::redundant_field_names)]
-    pub fn assertion(&mut self, assertion: VoteAccountAssertion) -> &mut Self {
+    /// Adds the assertion, `None` if the layout has no vote for
+    /// `LastVoteSlot`, no root slot for `RootSlot`, or no authorized voter up
+    /// to the epoch of `AuthorizedVoter`. The builder is then left unchanged.
+    pub fn assertion(&mut self, assertion: VoteAccountAssertion) -> Option<&mut Self> {
+        let layout = self.3.clone();
+        let label = assertion.label();
+        // The index of the asserted vote or authorized voter
+        let index = match &assertion {
+            VoteAccountAssertion::AuthorizedVoter { epoch, .. }
+                if layout.version != VoteStateVersion::V0_23_5 =>
+            {
+                layout
+                    .authorized_voter_epochs
+                    .iter()
+                    .rposition(|voter_epoch| voter_epoch <= epoch)? as u64
+            }
+            VoteAccountAssertion::RootSlot { .. } if !layout.has_root_slot => return None,
+            VoteAccountAssertion::LastVoteSlot { .. } => layout.votes_len.checked_sub(1)?,
+            _ => 0,
+        };
+
+        self.push(
+            "VoteState.version",
+            VOTE_STATE_VERSION_OFFSET,
+            DataValueAssertion::U32 {
+                value: layout.version.tag(),
+                operator: IntegerOperator::Equal,
+            },
+        );
+
         match assertion {
-            VoteAccountAssertion::AuthorizedWithdrawer { value, operator } => {
-                self.0.assertions(
-                    vec![
-                        crate::hooked::AccountDataAssertion {
-                            offset: crate::CompactU64(0),
-                            assertion: crate::generated::types::DataValueAssertion::U8 {
-                                value: 2,
-                                operator: crate::types::IntegerOperator::Equal,
-                            },
-                        },
-                        crate::hooked::AccountDataAssertion {
-                            offset: crate::CompactU64(36),
-                            assertion: crate::generated::types::DataValueAssertion::Pubkey {
-                                value,
-                                operator,
-                            },
-                        },
-                    ]
-                    .into(),
+            VoteAccountAssertion::AuthorizedWithdrawer { value, operator } => self.push(
+                label,
+                layout.authorized_withdrawer_offset(),
+                DataValueAssertion::Pubkey { value, operator },
+            ),
+            VoteAccountAssertion::NodePubkey { value, operator } => self.push(
+                label,
+                VOTE_STATE_NODE_PUBKEY_OFFSET,
+                DataValueAssertion::Pubkey { value, operator },
+            ),
+            VoteAccountAssertion::Commission { value, operator } => self.push(
+                label,
+                layout.commission_offset(),
+                DataValueAssertion::U8 { value, operator },
+            ),
+            VoteAccountAssertion::AuthorizedVoter { epoch, value, operator }
+                if layout.version == VoteStateVersion::V0_23_5 =>
+            {
+                self.push(
+                    "VoteState.authorized_voter_epoch",
+                    VOTE_STATE_0_23_5_AUTHORIZED_VOTER_EPOCH_OFFSET,
+                    DataValueAssertion::U64 {
+                        value: epoch,
+                        operator: IntegerOperator::LessThanOrEqual,
+                    },
+                );
+                self.push(
+                    label,
+                    VOTE_STATE_0_23_5_AUTHORIZED_VOTER_OFFSET,
+                    DataValueAssertion::Pubkey { value, operator },
+                )
+            }
+            VoteAccountAssertion::AuthorizedVoter { value, operator, .. } => {
+                let offset = layout.authorized_voters_offset() + 8 + index * AUTHORIZED_VOTER_LEN;
+
+                self.push_layout_assertions(&layout, true);
+                self.push(
+                    "VoteState.authorized_voters.len",
+                    layout.authorized_voters_offset(),
+                    DataValueAssertion::U64 {
+                        value: layout.authorized_voter_epochs.len() as u64,
+                        operator: IntegerOperator::Equal,
+                    },
+                );
+                self.push(
+                    "VoteState.authorized_voters.epoch",
+                    offset,
+                    DataValueAssertion::U64 {
+                        value: layout.authorized_voter_epochs[index as usize],
+                        operator: IntegerOperator::Equal,
+                    },
                 );
+                self.push(label, offset + 8, DataValueAssertion::Pubkey { value, operator })
+            }
+            VoteAccountAssertion::RootSlot { value, operator } => {
+                self.push_layout_assertions(&layout, true);
+                self.push(
+                    label,
+                    layout.root_slot_offset() + 1,
+                    DataValueAssertion::U64 { value, operator },
+                )
+            }
+            VoteAccountAssertion::LastVoteSlot { value, operator } => {
+                self.push_layout_assertions(&layout, false);
+                self.push(
+                    label,
+                    layout.vote_slot_offset(index),
+                    DataValueAssertion::U64 { value, operator },
+                )
             }
         }
-        self
+
+        self.0.assertions(self.1.clone().into());
+        Some(self)
     }
 
-    pub fn log_level(&mut self, log_level: crate::generated::types::LogLevel) -> &mut Self {
+    pub fn log_level(&mut self, log_level: LogLevel) -> &mut Self {
         self.0.log_level(log_level);
         self
     }
 
-    pub fn target_account(&mut self, target_account: solana_program::pubkey::Pubkey) -> &mut Self {
+    pub fn target_account(&mut self, target_account: Pubkey) -> &mut Self {
         self.0.target_account(target_account);
         self
     }
 
-    pub fn instruction(&self) -> solana_program::instruction::Instruction { self.0.instruction() }
+    /// The labels of the assertions, in the order of their results.
+    pub fn labels(&self) -> Vec<String> { self.2.iter().map(|label| label.to_string()).collect() }
+
+    pub fn instruction(&self) -> Instruction { self.0.instruction() }
+
+    /// Adds an assertion, unless the same assertion was already added.
+    fn push(&mut self, label: &'static str, offset: u64, assertion: DataValueAssertion) {
+        let assertion = account_data_assertion(offset, assertion);
+        if !self.1.contains(&assertion) {
+            self.1.push(assertion);
+            self.2.push(label);
+        }
+    }
+
+    /// Adds the assertions on the layout of the votes, and of the root slot
+    /// for the fields following it.
+    fn push_layout_assertions(&mut self, layout: &VoteAccountLayout, with_root_slot: bool) {
+        self.push(
+            "VoteState.votes.len",
+            layout.votes_offset(),
+            DataValueAssertion::U64 { value: layout.votes_len, operator: IntegerOperator::Equal },
+        );
+        if with_root_slot {
+            self.push(
+                "VoteState.root_slot.is_some",
+                layout.root_slot_offset(),
+                DataValueAssertion::U8 {
+                    value: u8::from(layout.has_root_slot),
+                    operator: IntegerOperator::Equal,
+                },
+            );
+        }
+    }
+}
+
+// Private functions
+
+fn read_u32(data: &[u8], offset: u64) -> Option<u32> {
+    let offset = usize::try_from(offset).ok()?;
+    Some(u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], offset: u64) -> Option<u64> {
+    let offset = usize::try_from(offset).ok()?;
+    Some(u64::from_le_bytes(data.get(offset..offset + 8)?.try_into().ok()?))
 }
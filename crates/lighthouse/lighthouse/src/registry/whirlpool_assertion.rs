@@ -0,0 +1,168 @@
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+use super::account_data_assertion;
+use crate::{
+    generated::{
+        instructions::AssertAccountDataMultiBuilder,
+        types::{DataValueAssertion, IntegerOperator, LogLevel},
+    },
+    hooked::AccountDataAssertion,
+};
+
+/// Byte offsets of the fields of an Orca `Whirlpool` account, discriminator
+/// included.
+pub const WHIRLPOOL_FEE_RATE_OFFSET: u64 = 45;
+pub const WHIRLPOOL_LIQUIDITY_OFFSET: u64 = 49;
+pub const WHIRLPOOL_SQRT_PRICE_OFFSET: u64 = 65;
+pub const WHIRLPOOL_TICK_CURRENT_INDEX_OFFSET: u64 = 81;
+
+#[derive(Debug, Clone)]
+pub enum WhirlpoolAssertion {
+    SqrtPrice { value: u128, operator: IntegerOperator },
+    TickCurrentIndex { value: i32, operator: IntegerOperator },
+    Liquidity { value: u128, operator: IntegerOperator },
+    FeeRate { value: u16, operator: IntegerOperator },
+}
+
+impl WhirlpoolAssertion {
+    /// The name of the asserted field, e.g. to label assertion results.
+    pub fn label(&self) -> &'static str {
+        match self {
+            WhirlpoolAssertion::SqrtPrice { .. } => "Whirlpool.sqrt_price",
+            WhirlpoolAssertion::TickCurrentIndex { .. } => "Whirlpool.tick_current_index",
+            WhirlpoolAssertion::Liquidity { .. } => "Whirlpool.liquidity",
+            WhirlpoolAssertion::FeeRate { .. } => "Whirlpool.fee_rate",
+        }
+    }
+}
+
+/// Asserts on the fields of an Orca `Whirlpool` account. Every call to
+/// `assertion` adds to the assertions of the instruction, so a price range can
+/// be guarded with a lower and an upper bound.
+pub struct AssertWhirlpoolBuilder(
+    AssertAccountDataMultiBuilder,
+    Vec<AccountDataAssertion>,
+    Vec<&'static str>,
+);
+
+impl AssertWhirlpoolBuilder {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self { Self(AssertAccountDataMultiBuilder::new(), Vec::new(), Vec::new()) }
+
+    pub fn assertion(&mut self, assertion: WhirlpoolAssertion) -> &mut Self {
+        self.2.push(assertion.label());
+        let assertion = match assertion {
+            WhirlpoolAssertion::SqrtPrice { value, operator } => account_data_assertion(
+                WHIRLPOOL_SQRT_PRICE_OFFSET,
+                DataValueAssertion::U128 { value, operator },
+            ),
+            WhirlpoolAssertion::TickCurrentIndex { value, operator } => account_data_assertion(
+                WHIRLPOOL_TICK_CURRENT_INDEX_OFFSET,
+                DataValueAssertion::I32 { value, operator },
+            ),
+            WhirlpoolAssertion::Liquidity { value, operator } => account_data_assertion(
+                WHIRLPOOL_LIQUIDITY_OFFSET,
+                DataValueAssertion::U128 { value, operator },
+            ),
+            WhirlpoolAssertion::FeeRate { value, operator } => account_data_assertion(
+                WHIRLPOOL_FEE_RATE_OFFSET,
+                DataValueAssertion::U16 { value, operator },
+            ),
+        };
+        self.1.push(assertion);
+        self.0.assertions(self.1.clone().into());
+        self
+    }
+
+    pub fn log_level(&mut self, log_level: LogLevel) -> &mut Self {
+        self.0.log_level(log_level);
+        self
+    }
+
+    pub fn target_account(&mut self, target_account: Pubkey) -> &mut Self {
+        self.0.target_account(target_account);
+        self
+    }
+
+    /// The labels of the assertions, in the order of their results.
+    pub fn labels(&self) -> Vec<String> { self.2.iter().map(|label| label.to_string()).collect() }
+
+    pub fn instruction(&self) -> Instruction { self.0.instruction() }
+}
+
+#[cfg(test)]
+mod tests {
+    use orca_whirlpools::generated::accounts::Whirlpool;
+
+    use super::*;
+
+    fn read<const N: usize>(data: &[u8], offset: u64) -> [u8; N] {
+        data[offset as usize..offset as usize + N].try_into().unwrap()
+    }
+
+    #[test]
+    fn offsets_match_whirlpool_layout() {
+        let mut whirlpool = Whirlpool::from_bytes(&[0; Whirlpool::LEN]).unwrap();
+        whirlpool.fee_rate = 0x0102;
+        whirlpool.liquidity = 0x0304_0506_0708_090a_0b0c_0d0e_0f10_1112;
+        whirlpool.sqrt_price = 0x1314_1516_1718_191a_1b1c_1d1e_1f20_2122;
+        whirlpool.tick_current_index = -0x2324_2526;
+
+        let data = borsh::to_vec(&whirlpool).unwrap();
+
+        assert_eq!(data.len(), Whirlpool::LEN);
+        assert_eq!(u16::from_le_bytes(read(&data, WHIRLPOOL_FEE_RATE_OFFSET)), whirlpool.fee_rate);
+        assert_eq!(
+            u128::from_le_bytes(read(&data, WHIRLPOOL_LIQUIDITY_OFFSET)),
+            whirlpool.liquidity
+        );
+        assert_eq!(
+            u128::from_le_bytes(read(&data, WHIRLPOOL_SQRT_PRICE_OFFSET)),
+            whirlpool.sqrt_price
+        );
+        assert_eq!(
+            i32::from_le_bytes(read(&data, WHIRLPOOL_TICK_CURRENT_INDEX_OFFSET)),
+            whirlpool.tick_current_index
+        );
+    }
+
+    #[test]
+    fn assertions_accumulate() {
+        let ix = AssertWhirlpoolBuilder::new()
+            .target_account(Pubkey::new_unique())
+            .assertion(WhirlpoolAssertion::SqrtPrice {
+                value: 1,
+                operator: IntegerOperator::GreaterThanOrEqual,
+            })
+            .assertion(WhirlpoolAssertion::SqrtPrice {
+                value: 2,
+                operator: IntegerOperator::LessThanOrEqual,
+            })
+            .instruction();
+
+        let expected = AssertAccountDataMultiBuilder::new()
+            .target_account(ix.accounts[0].pubkey)
+            .assertions(
+                vec![
+                    account_data_assertion(
+                        WHIRLPOOL_SQRT_PRICE_OFFSET,
+                        DataValueAssertion::U128 {
+                            value: 1,
+                            operator: IntegerOperator::GreaterThanOrEqual,
+                        },
+                    ),
+                    account_data_assertion(
+                        WHIRLPOOL_SQRT_PRICE_OFFSET,
+                        DataValueAssertion::U128 {
+                            value: 2,
+                            operator: IntegerOperator::LessThanOrEqual,
+                        },
+                    ),
+                ]
+                .into(),
+            )
+            .instruction();
+
+        assert_eq!(ix, expected);
+    }
+}
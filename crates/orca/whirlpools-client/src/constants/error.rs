@@ -31,3 +31,5 @@ pub const INVALID_SLIPPAGE_TOLERANCE: CoreError = "Invalid slippage tolerance";
 pub const TICK_INDEX_NOT_IN_ARRAY: CoreError = "Tick index not in array";
 
 pub const INVALID_TICK_ARRAY_SEQUENCE: CoreError = "Invalid tick array sequence";
+
+pub const INCONSISTENT_POOL_SNAPSHOTS: CoreError = "Inconsistent pool snapshots";
@@ -2,3 +2,5 @@
 
 /// The number of reward tokens in a pool.
 pub const NUM_REWARDS: usize = 3;
+
+pub use solana_client_core::constants::SECONDS_PER_YEAR;
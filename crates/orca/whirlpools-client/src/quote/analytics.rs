@@ -0,0 +1,153 @@
+use ethnum::U256;
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+
+use super::try_get_token_estimates_from_liquidity;
+use crate::{
+    constants::{
+        CoreError, AMOUNT_EXCEEDS_MAX_U64, ARITHMETIC_OVERFLOW, FEE_RATE_DENOMINATOR,
+        INCONSISTENT_POOL_SNAPSHOTS, SECONDS_PER_YEAR,
+    },
+    math::order_tick_indexes,
+    types::{PoolFeeReport, WhirlpoolFacade},
+};
+
+/// Summarize the activity of a whirlpool between two snapshots of its state.
+///
+/// A whirlpool does not track its volume nor the fees paid to the liquidity
+/// providers. Those are estimated from the fee growth using the liquidity of
+/// the earlier snapshot, so they are only exact if no tick was crossed in
+/// between. The protocol fees owed are reset when they are collected, in which
+/// case the snapshots are reported as inconsistent.
+///
+/// # Parameters
+/// - `whirlpool_before` - The earlier whirlpool state
+/// - `timestamp_before` - The unix timestamp of the earlier whirlpool state
+/// - `whirlpool_after` - The later whirlpool state
+/// - `timestamp_after` - The unix timestamp of the later whirlpool state
+///
+/// # Returns
+/// - A PoolFeeReport struct containing the estimated swap volumes and the fees
+///   split between the liquidity providers and the protocol
+pub fn pool_fee_report(
+    whirlpool_before: WhirlpoolFacade,
+    timestamp_before: u64,
+    whirlpool_after: WhirlpoolFacade,
+    timestamp_after: u64,
+) -> Result<PoolFeeReport, CoreError> {
+    if timestamp_after <= timestamp_before {
+        return Err(INCONSISTENT_POOL_SNAPSHOTS);
+    }
+
+    let protocol_fee_a = whirlpool_after
+        .protocol_fee_owed_a
+        .checked_sub(whirlpool_before.protocol_fee_owed_a)
+        .ok_or(INCONSISTENT_POOL_SNAPSHOTS)?;
+    let protocol_fee_b = whirlpool_after
+        .protocol_fee_owed_b
+        .checked_sub(whirlpool_before.protocol_fee_owed_b)
+        .ok_or(INCONSISTENT_POOL_SNAPSHOTS)?;
+
+    let fee_growth_a =
+        whirlpool_after.fee_growth_global_a.wrapping_sub(whirlpool_before.fee_growth_global_a);
+    let fee_growth_b =
+        whirlpool_after.fee_growth_global_b.wrapping_sub(whirlpool_before.fee_growth_global_b);
+
+    let lp_fee_a = try_get_fees_from_growth(fee_growth_a, whirlpool_before.liquidity)?;
+    let lp_fee_b = try_get_fees_from_growth(fee_growth_b, whirlpool_before.liquidity)?;
+
+    let volume_in_a = try_get_volume_from_fee(lp_fee_a, protocol_fee_a, whirlpool_before.fee_rate)?;
+    let volume_in_b = try_get_volume_from_fee(lp_fee_b, protocol_fee_b, whirlpool_before.fee_rate)?;
+
+    Ok(PoolFeeReport {
+        elapsed_seconds: timestamp_after - timestamp_before,
+        volume_in_a,
+        volume_in_b,
+        lp_fee_a,
+        lp_fee_b,
+        protocol_fee_a,
+        protocol_fee_b,
+        fee_growth_a,
+        fee_growth_b,
+    })
+}
+
+/// Calculate the annualized fee yield of a position over the period of a
+/// report, assuming it stayed in range the whole time. Both the fees and the
+/// position are valued in token B at the price of the whirlpool.
+///
+/// # Parameters
+/// - `report` - The pool fee report of the period
+/// - `whirlpool` - The whirlpool state to value the position at
+/// - `liquidity` - The liquidity of the position
+/// - `tick_index_1` - The first tick index of the position
+/// - `tick_index_2` - The second tick index of the position
+///
+/// # Returns
+/// - The fee APR, e.g. 0.12 for 12%
+pub fn fee_apr(
+    report: PoolFeeReport,
+    whirlpool: WhirlpoolFacade,
+    liquidity: u128,
+    tick_index_1: i32,
+    tick_index_2: i32,
+) -> Result<Decimal, CoreError> {
+    if report.elapsed_seconds == 0 {
+        return Err(INCONSISTENT_POOL_SNAPSHOTS);
+    }
+
+    let tick_range = order_tick_indexes(tick_index_1, tick_index_2);
+    let (token_a, token_b) = try_get_token_estimates_from_liquidity(
+        liquidity,
+        whirlpool.sqrt_price,
+        tick_range.tick_lower_index,
+        tick_range.tick_upper_index,
+        false,
+    )?;
+    let fee_a = try_get_fees_from_growth(report.fee_growth_a, liquidity)?;
+    let fee_b = try_get_fees_from_growth(report.fee_growth_b, liquidity)?;
+
+    let sqrt_price = Decimal::from_u128(whirlpool.sqrt_price).ok_or(ARITHMETIC_OVERFLOW)?
+        / Decimal::from(1u128 << 64);
+    let price = sqrt_price.checked_mul(sqrt_price).ok_or(ARITHMETIC_OVERFLOW)?;
+    let position_value = get_value(token_a, token_b, price)?;
+    if position_value.is_zero() {
+        return Err(ARITHMETIC_OVERFLOW);
+    }
+    let fee_value = get_value(fee_a, fee_b, price)?;
+
+    fee_value
+        .checked_div(position_value)
+        .and_then(|yield_| {
+            yield_.checked_mul(
+                Decimal::from(SECONDS_PER_YEAR) / Decimal::from(report.elapsed_seconds),
+            )
+        })
+        .ok_or(ARITHMETIC_OVERFLOW)
+}
+
+// Private functions
+
+fn try_get_fees_from_growth(fee_growth: u128, liquidity: u128) -> Result<u64, CoreError> {
+    let fees: U256 =
+        <U256>::from(fee_growth).checked_mul(liquidity.into()).ok_or(ARITHMETIC_OVERFLOW)? >> 64;
+    fees.try_into().map_err(|_| AMOUNT_EXCEEDS_MAX_U64)
+}
+
+fn get_value(amount_a: u64, amount_b: u64, price: Decimal) -> Result<Decimal, CoreError> {
+    Decimal::from(amount_a)
+        .checked_mul(price)
+        .and_then(|value_a| value_a.checked_add(Decimal::from(amount_b)))
+        .ok_or(ARITHMETIC_OVERFLOW)
+}
+
+fn try_get_volume_from_fee(
+    lp_fee: u64,
+    protocol_fee: u64,
+    fee_rate: u16,
+) -> Result<u128, CoreError> {
+    if fee_rate == 0 {
+        return Ok(0);
+    }
+    let total_fee = <u128>::from(lp_fee) + <u128>::from(protocol_fee);
+    Ok(total_fee * <u128>::from(FEE_RATE_DENOMINATOR) / <u128>::from(fee_rate))
+}
@@ -0,0 +1,430 @@
+use solana_client_core::math::sqrt_price_after_impact;
+
+use super::compute_swap;
+use crate::{
+    constants::{
+        CoreError, AMOUNT_EXCEEDS_MAX_U64, ARITHMETIC_OVERFLOW, MAX_SQRT_PRICE, MIN_SQRT_PRICE,
+        SQRT_PRICE_OUT_OF_BOUNDS,
+    },
+    math::{
+        sqrt_price_to_tick_index, tick_index_to_sqrt_price, try_get_amount_delta_a,
+        try_get_amount_delta_b, TickArraySequence,
+    },
+    types::{LiquidityDepth, LiquidityRange, PriceImpactQuote, TickArrays, WhirlpoolFacade, U128},
+};
+
+/// Computes the active liquidity for every tick range covered by the tick
+/// arrays.
+///
+/// # Parameters
+/// - `whirlpool`: The whirlpool state
+/// - `tick_arrays`: The tick arrays to walk
+///
+/// # Returns
+/// - A list of `LiquidityRange` ordered by tick index. Adjacent ranges are
+///   separated by an initialized tick.
+pub fn liquidity_distribution(
+    whirlpool: WhirlpoolFacade,
+    tick_arrays: TickArrays,
+) -> Result<Vec<LiquidityRange>, CoreError> {
+    let tick_sequence = TickArraySequence::new(tick_arrays.into(), whirlpool.tick_spacing)?;
+    let start_index = tick_sequence.start_index();
+    let end_index = tick_sequence.end_index();
+    let current_tick_index = whirlpool.tick_current_index.clamp(start_index, end_index);
+
+    let initialized_ticks = initialized_ticks(&tick_sequence);
+    let split =
+        initialized_ticks.partition_point(|(tick_index, _)| *tick_index <= current_tick_index);
+    let (ticks_below, ticks_above) = initialized_ticks.split_at(split);
+
+    let mut ranges_below = Vec::with_capacity(ticks_below.len() + 1);
+    let mut liquidity = whirlpool.liquidity;
+    let mut upper_index = ticks_above.first().map_or(end_index, |(tick_index, _)| *tick_index);
+    for (tick_index, liquidity_net) in ticks_below.iter().rev() {
+        push_range(&mut ranges_below, *tick_index, upper_index, liquidity);
+        liquidity = try_cross_tick(liquidity, *liquidity_net, true)?;
+        upper_index = *tick_index;
+    }
+    push_range(&mut ranges_below, start_index, upper_index, liquidity);
+    ranges_below.reverse();
+
+    let mut ranges = ranges_below;
+    let mut liquidity = whirlpool.liquidity;
+    for (i, (tick_index, liquidity_net)) in ticks_above.iter().enumerate() {
+        liquidity = try_cross_tick(liquidity, *liquidity_net, false)?;
+        let upper_index = ticks_above.get(i + 1).map_or(end_index, |(tick_index, _)| *tick_index);
+        push_range(&mut ranges, *tick_index, upper_index, liquidity);
+    }
+
+    Ok(ranges)
+}
+
+/// Computes the token amounts held by the active liquidity between the
+/// current price and a target price. No swap fees are taken into account.
+///
+/// # Parameters
+/// - `target_sqrt_price`: The sqrt price to walk to
+/// - `whirlpool`: The whirlpool state
+/// - `tick_arrays`: The tick arrays covering the walked price range
+///
+/// # Returns
+/// - A `LiquidityDepth` with the amounts of token A and token B between the two
+///   prices
+pub fn liquidity_depth(
+    target_sqrt_price: U128,
+    whirlpool: WhirlpoolFacade,
+    tick_arrays: TickArrays,
+) -> Result<LiquidityDepth, CoreError> {
+    if !(MIN_SQRT_PRICE..=MAX_SQRT_PRICE).contains(&target_sqrt_price) {
+        return Err(SQRT_PRICE_OUT_OF_BOUNDS);
+    }
+
+    let tick_sequence = TickArraySequence::new(tick_arrays.into(), whirlpool.tick_spacing)?;
+    let a_to_b = target_sqrt_price < whirlpool.sqrt_price;
+
+    let mut current_sqrt_price = whirlpool.sqrt_price;
+    let mut current_tick_index = whirlpool.tick_current_index;
+    let mut current_liquidity = whirlpool.liquidity;
+    let mut token_a = 0u64;
+    let mut token_b = 0u64;
+
+    while current_sqrt_price != target_sqrt_price {
+        let (next_tick, next_tick_index) = if a_to_b {
+            tick_sequence.prev_initialized_tick(current_tick_index)?
+        } else {
+            tick_sequence.next_initialized_tick(current_tick_index)?
+        };
+        let next_tick_sqrt_price = tick_index_to_sqrt_price(next_tick_index);
+        let next_sqrt_price = if a_to_b {
+            next_tick_sqrt_price.max(target_sqrt_price)
+        } else {
+            next_tick_sqrt_price.min(target_sqrt_price)
+        };
+
+        let delta_a =
+            try_get_amount_delta_a(current_sqrt_price, next_sqrt_price, current_liquidity, false)?;
+        let delta_b =
+            try_get_amount_delta_b(current_sqrt_price, next_sqrt_price, current_liquidity, false)?;
+        token_a = token_a.checked_add(delta_a).ok_or(AMOUNT_EXCEEDS_MAX_U64)?;
+        token_b = token_b.checked_add(delta_b).ok_or(AMOUNT_EXCEEDS_MAX_U64)?;
+
+        if next_sqrt_price == next_tick_sqrt_price {
+            let liquidity_net = next_tick.map_or(0, |tick| tick.liquidity_net);
+            current_liquidity = try_cross_tick(current_liquidity, liquidity_net, a_to_b)?;
+            current_tick_index = if a_to_b { next_tick_index - 1 } else { next_tick_index };
+        } else {
+            current_tick_index = sqrt_price_to_tick_index(next_sqrt_price);
+        }
+
+        current_sqrt_price = next_sqrt_price;
+    }
+
+    Ok(LiquidityDepth {
+        sqrt_price_start: whirlpool.sqrt_price,
+        sqrt_price_end: target_sqrt_price,
+        token_a,
+        token_b,
+    })
+}
+
+/// Computes the swap needed to move the price of a whirlpool by a number of
+/// basis points.
+///
+/// # Parameters
+/// - `price_impact_bps`: The price movement in basis points
+/// - `a_to_b`: If `true`, the price moves down (token A in). Otherwise, the
+///   price moves up (token B in).
+/// - `whirlpool`: The whirlpool state
+/// - `tick_arrays`: The tick arrays covering the price movement
+///
+/// # Returns
+/// - A `PriceImpactQuote` with the input amount (including trade fees) and the
+///   output amount of the swap
+pub fn price_impact_quote(
+    price_impact_bps: u16,
+    a_to_b: bool,
+    whirlpool: WhirlpoolFacade,
+    tick_arrays: TickArrays,
+) -> Result<PriceImpactQuote, CoreError> {
+    let target_sqrt_price =
+        try_get_sqrt_price_after_impact(whirlpool.sqrt_price, price_impact_bps, a_to_b)?;
+
+    if target_sqrt_price == whirlpool.sqrt_price {
+        return Ok(PriceImpactQuote { a_to_b, target_sqrt_price, ..Default::default() });
+    }
+
+    let tick_sequence = TickArraySequence::new(tick_arrays.into(), whirlpool.tick_spacing)?;
+    let swap_result =
+        compute_swap(u64::MAX, target_sqrt_price, whirlpool, tick_sequence, a_to_b, true, 0)?;
+
+    let (token_in, token_out) = if a_to_b {
+        (swap_result.token_a, swap_result.token_b)
+    } else {
+        (swap_result.token_b, swap_result.token_a)
+    };
+
+    Ok(PriceImpactQuote {
+        a_to_b,
+        target_sqrt_price,
+        token_in,
+        token_out,
+        trade_fee: swap_result.trade_fee,
+    })
+}
+
+// Private functions
+
+fn initialized_ticks<const SIZE: usize>(
+    tick_sequence: &TickArraySequence<SIZE>,
+) -> Vec<(i32, i128)> {
+    let start_index = tick_sequence.start_index();
+    let end_index = tick_sequence.end_index();
+    let tick_spacing = i32::from(tick_sequence.tick_spacing);
+
+    let mut ticks = Vec::new();
+    for tick_array in tick_sequence.tick_arrays.iter().flatten() {
+        for (i, tick) in tick_array.ticks.iter().enumerate() {
+            let tick_index = tick_array.start_tick_index + i as i32 * tick_spacing;
+            if tick.initialized && (start_index..=end_index).contains(&tick_index) {
+                ticks.push((tick_index, tick.liquidity_net));
+            }
+        }
+    }
+    ticks
+}
+
+fn push_range(
+    ranges: &mut Vec<LiquidityRange>,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    liquidity: u128,
+) {
+    if tick_lower_index < tick_upper_index {
+        ranges.push(LiquidityRange { tick_lower_index, tick_upper_index, liquidity });
+    }
+}
+
+fn try_cross_tick(liquidity: u128, liquidity_net: i128, a_to_b: bool) -> Result<u128, CoreError> {
+    let liquidity_net_unsigned = liquidity_net.unsigned_abs();
+    if (liquidity_net < 0) == a_to_b {
+        liquidity.checked_add(liquidity_net_unsigned)
+    } else {
+        liquidity.checked_sub(liquidity_net_unsigned)
+    }
+    .ok_or(ARITHMETIC_OVERFLOW)
+}
+
+fn try_get_sqrt_price_after_impact(
+    sqrt_price: u128,
+    price_impact_bps: u16,
+    a_to_b: bool,
+) -> Result<u128, CoreError> {
+    let target_sqrt_price = sqrt_price_after_impact(sqrt_price, price_impact_bps, a_to_b, false)
+        .ok_or(ARITHMETIC_OVERFLOW)?;
+
+    Ok(target_sqrt_price.clamp(MIN_SQRT_PRICE, MAX_SQRT_PRICE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::TICK_ARRAY_SIZE,
+        types::{TickArrayFacade, TickFacade},
+    };
+
+    const LIQUIDITY_1: u128 = 1_000_000_000;
+    const LIQUIDITY_2: u128 = 2_000_000_000;
+
+    /// Three tick arrays of tick spacing 1 starting at -88, 0 and 88, with a
+    /// position of `LIQUIDITY_1` in [-10, 10) and one of `LIQUIDITY_2` in
+    /// [0, 20).
+    fn tick_arrays() -> TickArrays {
+        let array_size = TICK_ARRAY_SIZE as i32;
+        let mut tick_arrays = [-array_size, 0, array_size].map(|start_tick_index| {
+            TickArrayFacade { start_tick_index, ticks: [TickFacade::default(); TICK_ARRAY_SIZE] }
+        });
+        for (tick_lower_index, tick_upper_index, liquidity) in
+            [(-10, 10, LIQUIDITY_1), (0, 20, LIQUIDITY_2)]
+        {
+            for (tick_index, liquidity_net) in
+                [(tick_lower_index, liquidity as i128), (tick_upper_index, -(liquidity as i128))]
+            {
+                let tick_array =
+                    &mut tick_arrays[((tick_index + array_size) / array_size) as usize];
+                let tick =
+                    &mut tick_array.ticks[(tick_index - tick_array.start_tick_index) as usize];
+                tick.initialized = true;
+                tick.liquidity_net += liquidity_net;
+                tick.liquidity_gross += liquidity;
+            }
+        }
+        tick_arrays.into()
+    }
+
+    fn whirlpool(tick_index: i32, liquidity: u128, fee_rate: u16) -> WhirlpoolFacade {
+        WhirlpoolFacade {
+            tick_spacing: 1,
+            fee_rate,
+            liquidity,
+            sqrt_price: tick_index_to_sqrt_price(tick_index),
+            tick_current_index: tick_index,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_liquidity_distribution() {
+        let range = |tick_lower_index, tick_upper_index, liquidity| LiquidityRange {
+            tick_lower_index,
+            tick_upper_index,
+            liquidity,
+        };
+        let expected = vec![
+            range(-88, -10, 0),
+            range(-10, 0, LIQUIDITY_1),
+            range(0, 10, LIQUIDITY_1 + LIQUIDITY_2),
+            range(10, 20, LIQUIDITY_2),
+            range(20, 175, 0),
+        ];
+
+        let whirlpool = whirlpool(5, LIQUIDITY_1 + LIQUIDITY_2, 0);
+        assert_eq!(liquidity_distribution(whirlpool, tick_arrays()).unwrap(), expected);
+
+        // The same from a price sitting on an initialized tick
+        let whirlpool = WhirlpoolFacade { liquidity: LIQUIDITY_2, ..whirlpool_at(10) };
+        assert_eq!(liquidity_distribution(whirlpool, tick_arrays()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_liquidity_depth() {
+        let whirlpool = whirlpool(5, LIQUIDITY_1 + LIQUIDITY_2, 0);
+
+        let depth = liquidity_depth(whirlpool.sqrt_price, whirlpool, tick_arrays()).unwrap();
+        assert_eq!((depth.token_a, depth.token_b), (0, 0));
+
+        // Within a range, the amounts held by its liquidity
+        let target_sqrt_price = tick_index_to_sqrt_price(8);
+        let depth = liquidity_depth(target_sqrt_price, whirlpool, tick_arrays()).unwrap();
+        assert_eq!(depth.sqrt_price_start, whirlpool.sqrt_price);
+        assert_eq!(depth.sqrt_price_end, target_sqrt_price);
+        assert_eq!(
+            (depth.token_a, depth.token_b),
+            (
+                try_get_amount_delta_a(
+                    whirlpool.sqrt_price,
+                    target_sqrt_price,
+                    whirlpool.liquidity,
+                    false
+                )
+                .unwrap(),
+                try_get_amount_delta_b(
+                    whirlpool.sqrt_price,
+                    target_sqrt_price,
+                    whirlpool.liquidity,
+                    false
+                )
+                .unwrap(),
+            )
+        );
+
+        // Across an initialized tick, the sum of the depths on both sides
+        for (tick_index, target_tick_index) in [(5, 15), (5, -5)] {
+            let target_sqrt_price = tick_index_to_sqrt_price(target_tick_index);
+            let crossed_tick_index = if target_tick_index > tick_index { 10 } else { 0 };
+            let depth = liquidity_depth(target_sqrt_price, whirlpool, tick_arrays()).unwrap();
+            let depth_to_tick = liquidity_depth(
+                tick_index_to_sqrt_price(crossed_tick_index),
+                whirlpool,
+                tick_arrays(),
+            )
+            .unwrap();
+            // Past the crossed tick, which is left below the current tick
+            // index when crossed downwards
+            let whirlpool_at_tick = if target_tick_index > tick_index {
+                WhirlpoolFacade { liquidity: LIQUIDITY_2, ..whirlpool_at(crossed_tick_index) }
+            } else {
+                WhirlpoolFacade {
+                    liquidity: LIQUIDITY_1,
+                    tick_current_index: crossed_tick_index - 1,
+                    ..whirlpool_at(crossed_tick_index)
+                }
+            };
+            let depth_from_tick =
+                liquidity_depth(target_sqrt_price, whirlpool_at_tick, tick_arrays()).unwrap();
+            assert_eq!(depth.token_a, depth_to_tick.token_a + depth_from_tick.token_a);
+            assert_eq!(depth.token_b, depth_to_tick.token_b + depth_from_tick.token_b);
+        }
+
+        assert_eq!(
+            liquidity_depth(MAX_SQRT_PRICE + 1, whirlpool, tick_arrays()),
+            Err(SQRT_PRICE_OUT_OF_BOUNDS)
+        );
+    }
+
+    #[test]
+    fn test_price_impact_quote() {
+        let whirlpool = whirlpool(5, LIQUIDITY_1 + LIQUIDITY_2, 0);
+
+        let quote = price_impact_quote(0, true, whirlpool, tick_arrays()).unwrap();
+        assert_eq!(
+            quote,
+            PriceImpactQuote {
+                a_to_b: true,
+                target_sqrt_price: whirlpool.sqrt_price,
+                ..Default::default()
+            }
+        );
+
+        // Without fees, the swap moves the amounts held between the two prices,
+        // the input rounded up once per crossed range
+        for a_to_b in [true, false] {
+            let quote = price_impact_quote(20, a_to_b, whirlpool, tick_arrays()).unwrap();
+            assert_eq!(
+                quote.target_sqrt_price,
+                sqrt_price_after_impact(whirlpool.sqrt_price, 20, a_to_b, false).unwrap()
+            );
+            let depth = liquidity_depth(quote.target_sqrt_price, whirlpool, tick_arrays()).unwrap();
+            let (depth_in, depth_out) = if a_to_b {
+                (depth.token_a, depth.token_b)
+            } else {
+                (depth.token_b, depth.token_a)
+            };
+            assert_eq!(quote.token_out, depth_out);
+            assert!(quote.token_in >= depth_in && quote.token_in <= depth_in + 2);
+            assert_eq!(quote.trade_fee, 0);
+        }
+
+        // The trade fee is paid on top of the input
+        let whirlpool = WhirlpoolFacade { fee_rate: 3000, ..whirlpool };
+        let quote = price_impact_quote(20, true, whirlpool, tick_arrays()).unwrap();
+        let quote_without_fee = price_impact_quote(
+            20,
+            true,
+            WhirlpoolFacade { fee_rate: 0, ..whirlpool },
+            tick_arrays(),
+        )
+        .unwrap();
+        assert!(quote.trade_fee > 0);
+        assert_eq!(quote.token_in, quote_without_fee.token_in + quote.trade_fee);
+        assert_eq!(quote.token_out, quote_without_fee.token_out);
+    }
+
+    #[test]
+    fn test_sqrt_price_after_impact_bounds() {
+        assert_eq!(
+            try_get_sqrt_price_after_impact(MIN_SQRT_PRICE, 5_000, true),
+            Ok(MIN_SQRT_PRICE)
+        );
+        assert_eq!(
+            try_get_sqrt_price_after_impact(MAX_SQRT_PRICE, 5_000, false),
+            Ok(MAX_SQRT_PRICE)
+        );
+        assert_eq!(
+            try_get_sqrt_price_after_impact(MAX_SQRT_PRICE, 10_001, true),
+            Err(ARITHMETIC_OVERFLOW)
+        );
+    }
+
+    fn whirlpool_at(tick_index: i32) -> WhirlpoolFacade { whirlpool(tick_index, 0, 0) }
+}
@@ -434,7 +434,7 @@ fn try_get_token_b_from_liquidity(
     }
 }
 
-fn try_get_token_estimates_from_liquidity(
+pub(crate) fn try_get_token_estimates_from_liquidity(
     liquidity_delta: u128,
     current_sqrt_price: u128,
     tick_lower_index: i32,
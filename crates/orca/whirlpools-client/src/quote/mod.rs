@@ -1,8 +1,12 @@
+mod analytics;
+mod depth;
 mod fees;
 mod liquidity;
 mod rewards;
 mod swap;
 
+pub use analytics::*;
+pub use depth::*;
 pub use fees::*;
 pub use liquidity::*;
 pub use rewards::*;
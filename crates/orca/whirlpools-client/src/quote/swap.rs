@@ -78,6 +78,7 @@ pub fn swap_quote_by_input_token(
         token_est_out,
         token_min_out,
         trade_fee: swap_result.trade_fee,
+        sqrt_price_after: swap_result.sqrt_price_after,
     })
 }
 
@@ -146,6 +147,7 @@ pub fn swap_quote_by_output_token(
         token_est_in,
         token_max_in,
         trade_fee: swap_result.trade_fee,
+        sqrt_price_after: swap_result.sqrt_price_after,
     })
 }
 
@@ -153,6 +155,7 @@ pub struct SwapResult {
     pub token_a: u64,
     pub token_b: u64,
     pub trade_fee: u64,
+    pub sqrt_price_after: u128,
 }
 
 /// Computes the amounts of tokens A and B based on the current Whirlpool state
@@ -279,7 +282,7 @@ pub fn compute_swap<const SIZE: usize>(
     let token_a = if a_to_b == specified_input { swapped_amount } else { amount_calculated };
     let token_b = if a_to_b == specified_input { amount_calculated } else { swapped_amount };
 
-    Ok(SwapResult { token_a, token_b, trade_fee })
+    Ok(SwapResult { token_a, token_b, trade_fee, sqrt_price_after: current_sqrt_price })
 }
 
 // Private functions
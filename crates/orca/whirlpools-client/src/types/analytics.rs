@@ -0,0 +1,16 @@
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct PoolFeeReport {
+    pub elapsed_seconds: u64,
+    /// The estimated swap volume paid in token A, fees included.
+    pub volume_in_a: u128,
+    /// The estimated swap volume paid in token B, fees included.
+    pub volume_in_b: u128,
+    /// The estimated fees accrued to the liquidity providers.
+    pub lp_fee_a: u64,
+    pub lp_fee_b: u64,
+    pub protocol_fee_a: u64,
+    pub protocol_fee_b: u64,
+    /// The fees earned per unit of in-range liquidity, as Q64.64.
+    pub fee_growth_a: u128,
+    pub fee_growth_b: u128,
+}
@@ -0,0 +1,23 @@
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct LiquidityRange {
+    pub tick_lower_index: i32,
+    pub tick_upper_index: i32,
+    pub liquidity: u128,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct LiquidityDepth {
+    pub sqrt_price_start: u128,
+    pub sqrt_price_end: u128,
+    pub token_a: u64,
+    pub token_b: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct PriceImpactQuote {
+    pub a_to_b: bool,
+    pub target_sqrt_price: u128,
+    pub token_in: u64,
+    pub token_out: u64,
+    pub trade_fee: u64,
+}
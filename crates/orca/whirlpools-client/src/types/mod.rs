@@ -1,3 +1,5 @@
+mod analytics;
+mod depth;
 mod fees;
 mod liquidity;
 mod params;
@@ -11,6 +13,8 @@ mod tick_array;
 mod token;
 mod u128;
 
+pub use analytics::*;
+pub use depth::*;
 pub use fees::*;
 pub use liquidity::*;
 pub use params::*;
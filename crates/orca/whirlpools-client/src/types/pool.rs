@@ -12,6 +12,8 @@ pub struct WhirlpoolFacade {
     pub tick_current_index: i32,
     pub fee_growth_global_a: u128,
     pub fee_growth_global_b: u128,
+    pub protocol_fee_owed_a: u64,
+    pub protocol_fee_owed_b: u64,
     pub reward_last_updated_timestamp: u64,
     pub reward_infos: [WhirlpoolRewardInfoFacade; NUM_REWARDS],
 }
@@ -33,6 +35,8 @@ impl From<orca_whirlpools::generated::accounts::Whirlpool> for WhirlpoolFacade {
             tick_current_index: whirlpool.tick_current_index,
             fee_growth_global_a: whirlpool.fee_growth_global_a,
             fee_growth_global_b: whirlpool.fee_growth_global_b,
+            protocol_fee_owed_a: whirlpool.protocol_fee_owed_a,
+            protocol_fee_owed_b: whirlpool.protocol_fee_owed_b,
             reward_last_updated_timestamp: whirlpool.reward_last_updated_timestamp,
             reward_infos: whirlpool.reward_infos.map(|reward| reward.into()),
         }
@@ -5,6 +5,8 @@ pub struct ExactInSwapQuote {
     pub token_est_out: u64,
     pub token_min_out: u64,
     pub trade_fee: u64,
+    /// The sqrt price of the whirlpool after the swap.
+    pub sqrt_price_after: u128,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
@@ -14,4 +16,6 @@ pub struct ExactOutSwapQuote {
     pub token_est_in: u64,
     pub token_max_in: u64,
     pub trade_fee: u64,
+    /// The sqrt price of the whirlpool after the swap.
+    pub sqrt_price_after: u128,
 }
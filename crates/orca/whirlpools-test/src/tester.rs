@@ -9,6 +9,7 @@ use program_test_utils::{
 };
 use solana_client_core::MaybeAccount;
 use solana_sdk::{
+    instruction::Instruction,
     pubkey::Pubkey,
     signature::{Keypair, Signer},
 };
@@ -241,6 +242,18 @@ impl WhirlpoolsTester {
         payer: &Keypair,
         params: SwapParams,
     ) -> Result<TransactionMetadata> {
+        let ix = self.prepare_swap(svm, params)?;
+
+        let metadata = sign_and_send_transaction!(svm, &[ix], payer).inspect_err(|e| {
+            println!("Error swapping: {:?}", e);
+        })?;
+
+        Ok(metadata)
+    }
+
+    /// Builds the swap instruction through the three tick arrays following
+    /// the current tick in the swap direction.
+    pub fn prepare_swap(&self, svm: &LiteSVM, params: SwapParams) -> Result<Instruction> {
         let whirlpool_account = self.get_whirlpool(svm, &self.whirlpool)?;
 
         let start_tick_index = orca_whirlpools::math::tick::get_array_start_index(
@@ -281,11 +294,7 @@ impl WhirlpoolsTester {
             self.program_id,
         )?;
 
-        let metadata = sign_and_send_transaction!(svm, &[ix], payer).inspect_err(|e| {
-            println!("Error swapping: {:?}", e);
-        })?;
-
-        Ok(metadata)
+        Ok(ix)
     }
 
     pub fn preview_swap(
@@ -1,19 +1,28 @@
 use litesvm::LiteSVM;
-use orca_whirlpools::ID;
+use orca_whirlpools::{utils::derive, ID};
+use orca_whirlpools_client::{
+    constants::{FEE_RATE_DENOMINATOR, INCONSISTENT_POOL_SNAPSHOTS, SECONDS_PER_YEAR},
+    quote::{decrease_liquidity_quote, fee_apr, pool_fee_report},
+    types::WhirlpoolFacade,
+};
 use orca_whirlpools_test::{
     fixture::{setup_user, WhirlpoolConfigFixtureBuilder, WhirlpoolFixtureBuilder},
     tester::WhirlpoolsTester,
     types::{
-        CreateFeeTierParams, CreateWhirlPoolTesterParams, IncreaseLiquidityParams,
-        OpenPositionParams, SwapParams, User, WhirlpoolConfigFixture, WhirlpoolFixture,
+        CreateConfigParams, CreateFeeTierParams, CreateWhirlPoolTesterParams,
+        IncreaseLiquidityParams, OpenPositionParams, SwapParams, User, WhirlpoolConfigFixture,
+        WhirlpoolFixture,
     },
 };
 use program_test_utils::svm::update_clock;
+use rust_decimal::{prelude::FromPrimitive, Decimal};
 use solana_sdk::signature::{Keypair, Signer};
 
 // Constants for test values
 const MAX_AMOUNT: u64 = 1_000_000_000_000_000_000;
 const INCREASE_LIQUIDITY: u128 = 10_000;
+const FEE_RATE: u16 = 3000;
+const PROTOCOL_FEE_RATE: u16 = 300;
 
 #[cfg(test)]
 mod tests {
@@ -122,9 +131,131 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_pool_fee_report() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { whirlpools_tester, mut svm, user0, user1, .. } =
+            create_fixture_with_fee_rates(FEE_RATE, PROTOCOL_FEE_RATE)?;
+        update_clock(&mut svm, 1, 1000);
+        let whirlpool = whirlpools_tester.whirlpool;
+
+        // A position deep enough for the swap to stay within its range, so no
+        // tick is crossed and the report is exact
+        let (tick_lower_index, tick_upper_index) = (-60, 60);
+        let liquidity = 10_000_000_000;
+        let (position_nft_mint, ..) = whirlpools_tester.open_position(
+            &mut svm,
+            &user0.keypair,
+            OpenPositionParams {
+                owner: user0.keypair.pubkey(),
+                tick_lower_index,
+                tick_upper_index,
+            },
+        )?;
+        let increase_liquidity = |svm: &mut LiteSVM, liquidity| {
+            whirlpools_tester.increase_liquidity(
+                svm,
+                &user0.keypair,
+                IncreaseLiquidityParams {
+                    nft_owner: user0.keypair.pubkey(),
+                    position_nft_mint,
+                    token_account_a: user0.token_account_0,
+                    token_account_b: user0.token_account_1,
+                    liquidity,
+                    token_max_a: MAX_AMOUNT,
+                    token_max_b: MAX_AMOUNT,
+                },
+            )
+        };
+        let _unused = increase_liquidity(&mut svm, liquidity)?;
+        let whirlpool_before =
+            WhirlpoolFacade::from(whirlpools_tester.get_whirlpool(&svm, &whirlpool)?);
+
+        let amount = 100_000;
+        let _unused = whirlpools_tester.swap(
+            &mut svm,
+            &user1.keypair,
+            SwapParams {
+                token_authority: user1.keypair.pubkey(),
+                token_owner_account_a: user1.token_account_0,
+                token_owner_account_b: user1.token_account_1,
+                amount,
+                other_amount_threshold: 0,
+                sqrt_price_limit: 0,
+                amount_specified_is_input: true,
+                a_to_b: false,
+            },
+        )?;
+        update_clock(&mut svm, 2, 1000 + 86_400);
+        let whirlpool_after =
+            WhirlpoolFacade::from(whirlpools_tester.get_whirlpool(&svm, &whirlpool)?);
+        assert_eq!(whirlpool_after.tick_current_index, whirlpool_before.tick_current_index);
+
+        let report = pool_fee_report(whirlpool_before, 1000, whirlpool_after, 1000 + 86_400)?;
+        assert_eq!(report.elapsed_seconds, 86_400);
+        assert_eq!(report.volume_in_a, 0);
+        assert_eq!(report.lp_fee_a + report.protocol_fee_a, 0);
+        assert_eq!(
+            report.protocol_fee_b,
+            whirlpool_after.protocol_fee_owed_b - whirlpool_before.protocol_fee_owed_b
+        );
+        assert!(report.protocol_fee_b > 0);
+        // The fees are floored when estimated from the fee growth, which can
+        // miss the volume paid for a single unit of fee
+        assert!(report.volume_in_b <= amount as u128);
+        assert!(
+            amount as u128 - report.volume_in_b
+                <= FEE_RATE_DENOMINATOR.div_ceil(u32::from(FEE_RATE)) as u128
+        );
+
+        // The APR is the yield of the fees the program credits the position
+        // with when its liquidity is next modified
+        let _unused = increase_liquidity(&mut svm, INCREASE_LIQUIDITY)?;
+        let position = whirlpools_tester
+            .get_position(&svm, &derive::derive_position_pubkey(position_nft_mint, Some(ID)).0)?;
+        assert!(position.fee_owed_b > 0);
+        assert_eq!(position.fee_owed_a, 0);
+
+        let position_amounts = decrease_liquidity_quote(
+            liquidity,
+            0,
+            whirlpool_after.sqrt_price,
+            tick_lower_index,
+            tick_upper_index,
+            None,
+            None,
+        )?;
+        let sqrt_price =
+            Decimal::from_u128(whirlpool_after.sqrt_price).unwrap() / Decimal::from(1u128 << 64);
+        let price = sqrt_price * sqrt_price;
+        let fee_value =
+            Decimal::from(position.fee_owed_a) * price + Decimal::from(position.fee_owed_b);
+        let position_value = Decimal::from(position_amounts.token_est_a) * price
+            + Decimal::from(position_amounts.token_est_b);
+        let apr = fee_apr(report, whirlpool_after, liquidity, tick_lower_index, tick_upper_index)?;
+        assert_eq!(
+            apr,
+            fee_value / position_value * (Decimal::from(SECONDS_PER_YEAR) / Decimal::from(86_400))
+        );
+
+        // Snapshots in the wrong order are rejected
+        let result = pool_fee_report(whirlpool_after, 1000, whirlpool_before, 2000);
+        assert_eq!(result, Err(INCONSISTENT_POOL_SNAPSHOTS));
+        let result = pool_fee_report(whirlpool_before, 2000, whirlpool_after, 1000);
+        assert_eq!(result, Err(INCONSISTENT_POOL_SNAPSHOTS));
+
+        Ok(())
+    }
 }
 
 fn create_fixture() -> Result<Fixture, Box<dyn std::error::Error>> {
+    create_fixture_with_fee_rates(0, 0)
+}
+
+fn create_fixture_with_fee_rates(
+    fee_rate: u16,
+    protocol_fee_rate: u16,
+) -> Result<Fixture, Box<dyn std::error::Error>> {
     let program_id = ID;
     let mut svm = LiteSVM::new().with_sigverify(false);
     svm.add_program(program_id, include_bytes!("fixtures/orca_whirlpools.so"));
@@ -132,9 +263,13 @@ fn create_fixture() -> Result<Fixture, Box<dyn std::error::Error>> {
     let admin = Keypair::new();
     let _unused = svm.airdrop(&admin.pubkey(), 1_000_000_000_000).unwrap();
 
-    let fee_tier_params = vec![CreateFeeTierParams { tick_spacing: 1, default_fee_rate: 0 }];
+    let fee_tier_params = vec![CreateFeeTierParams { tick_spacing: 1, default_fee_rate: fee_rate }];
 
     let whirlpool_config_fixture = WhirlpoolConfigFixtureBuilder::new()
+        .with_config_params(CreateConfigParams {
+            default_protocol_fee_rate: protocol_fee_rate,
+            ..Default::default()
+        })
         .with_fee_tier_params(fee_tier_params)
         .build(&mut svm, &admin)?;
 
@@ -0,0 +1,162 @@
+use raydium_clmm::{
+    generated::accounts::PoolState,
+    libraries::{big_num::U256, fixed_point_64},
+    math::full_math::MulDiv,
+};
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+
+use crate::{
+    constants::SECONDS_PER_YEAR,
+    error::{ClmmClientError, Result},
+    math::price::normalize_price_sqrt,
+    quote::get_amounts_from_liquidity,
+    types::PoolFeeReport,
+};
+
+/// Summarize the activity of a pool between two snapshots of its state
+///
+/// The protocol and fund fee counters are reset when they are collected, so
+/// snapshots taken around a collection are reported as inconsistent.
+///
+/// # Parameters
+/// - `pool_state_before` - The earlier pool state
+/// - `timestamp_before` - The unix timestamp of the earlier pool state
+/// - `pool_state_after` - The later pool state
+/// - `timestamp_after` - The unix timestamp of the later pool state
+///
+/// # Returns
+/// - A PoolFeeReport struct containing the swap volumes and the fees split
+///   between the liquidity providers, the protocol and the fund
+pub fn pool_fee_report(
+    pool_state_before: &PoolState,
+    timestamp_before: u64,
+    pool_state_after: &PoolState,
+    timestamp_after: u64,
+) -> Result<PoolFeeReport> {
+    if timestamp_after <= timestamp_before {
+        return Err(ClmmClientError::InconsistentPoolSnapshots);
+    }
+
+    Ok(PoolFeeReport {
+        elapsed_seconds: timestamp_after - timestamp_before,
+        volume_in_0: get_delta(
+            pool_state_before.swap_in_amount_token0,
+            pool_state_after.swap_in_amount_token0,
+        )?,
+        volume_in_1: get_delta(
+            pool_state_before.swap_in_amount_token1,
+            pool_state_after.swap_in_amount_token1,
+        )?,
+        volume_out_0: get_delta(
+            pool_state_before.swap_out_amount_token0,
+            pool_state_after.swap_out_amount_token0,
+        )?,
+        volume_out_1: get_delta(
+            pool_state_before.swap_out_amount_token1,
+            pool_state_after.swap_out_amount_token1,
+        )?,
+        lp_fee_0: get_delta(
+            pool_state_before.total_fees_token0,
+            pool_state_after.total_fees_token0,
+        )?,
+        lp_fee_1: get_delta(
+            pool_state_before.total_fees_token1,
+            pool_state_after.total_fees_token1,
+        )?,
+        protocol_fee_0: get_delta(
+            pool_state_before.protocol_fees_token0,
+            pool_state_after.protocol_fees_token0,
+        )?,
+        protocol_fee_1: get_delta(
+            pool_state_before.protocol_fees_token1,
+            pool_state_after.protocol_fees_token1,
+        )?,
+        fund_fee_0: get_delta(
+            pool_state_before.fund_fees_token0,
+            pool_state_after.fund_fees_token0,
+        )?,
+        fund_fee_1: get_delta(
+            pool_state_before.fund_fees_token1,
+            pool_state_after.fund_fees_token1,
+        )?,
+        fee_growth_0_x64: pool_state_after
+            .fee_growth_global0_x64
+            .wrapping_sub(pool_state_before.fee_growth_global0_x64),
+        fee_growth_1_x64: pool_state_after
+            .fee_growth_global1_x64
+            .wrapping_sub(pool_state_before.fee_growth_global1_x64),
+    })
+}
+
+/// Calculate the annualized fee yield of a position over the period of a
+/// report, assuming it stayed in range the whole time. Both the fees and the
+/// position are valued in token 1 at the price of `pool_state`.
+///
+/// # Parameters
+/// - `report` - The pool fee report of the period
+/// - `pool_state` - The pool state to value the position at
+/// - `liquidity` - The liquidity of the position
+/// - `tick_lower_index` - The lower tick index of the position
+/// - `tick_upper_index` - The upper tick index of the position
+///
+/// # Returns
+/// - The fee APR, e.g. 0.12 for 12%
+pub fn fee_apr(
+    report: &PoolFeeReport,
+    pool_state: &PoolState,
+    liquidity: u128,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+) -> Result<Decimal> {
+    if report.elapsed_seconds == 0 {
+        return Err(ClmmClientError::InconsistentPoolSnapshots);
+    }
+
+    let (amount_0, amount_1) = get_amounts_from_liquidity(
+        liquidity,
+        pool_state,
+        tick_lower_index,
+        tick_upper_index,
+        false,
+    )?;
+    let fee_0 = get_fees_from_growth(report.fee_growth_0_x64, liquidity)?;
+    let fee_1 = get_fees_from_growth(report.fee_growth_1_x64, liquidity)?;
+
+    let price_sqrt = normalize_price_sqrt(pool_state.sqrt_price_x64);
+    let price = price_sqrt * price_sqrt;
+    let position_value = Decimal::from(amount_0) * price + Decimal::from(amount_1);
+    if position_value.is_zero() {
+        return Err(ClmmClientError::InvalidParameter);
+    }
+    let fee_value = fee_0 * price + fee_1;
+
+    fee_value
+        .checked_div(position_value)
+        .and_then(|yield_| {
+            yield_.checked_mul(
+                Decimal::from(SECONDS_PER_YEAR) / Decimal::from(report.elapsed_seconds),
+            )
+        })
+        .ok_or(ClmmClientError::ArithmeticOverflow)
+}
+
+// Private functions
+
+fn get_delta<T: TryFrom<u128> + Into<u128>>(before: T, after: T) -> Result<T> {
+    after
+        .into()
+        .checked_sub(before.into())
+        .ok_or(ClmmClientError::InconsistentPoolSnapshots)?
+        .try_into()
+        .map_err(|_| ClmmClientError::ArithmeticOverflow)
+}
+
+fn get_fees_from_growth(fee_growth_x64: u128, liquidity: u128) -> Result<Decimal> {
+    let fees = U256::from(fee_growth_x64)
+        .mul_div_floor(U256::from(liquidity), U256::from(fixed_point_64::Q64))
+        .ok_or(ClmmClientError::ArithmeticOverflow)?;
+    if fees > U256::from(u128::MAX) {
+        return Err(ClmmClientError::ArithmeticOverflow);
+    }
+    Decimal::from_u128(fees.as_u128()).ok_or(ClmmClientError::ArithmeticOverflow)
+}
@@ -6,3 +6,21 @@ pub const ADMIN_KEY: Pubkey = pubkey!("GThUX1Atko4tqhN2NaiTazWSeFWMuiUvfFnyJyUgh
 pub const METADATA_PROGRAM_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
 
 pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+pub use solana_client_core::constants::SECONDS_PER_YEAR;
+
+/// The shortest period a reward can be initialized or extended for.
+pub const MIN_REWARD_PERIOD: u64 = 7 * 24 * 3600;
+
+/// The longest period a reward can be initialized or extended for.
+pub const MAX_REWARD_PERIOD: u64 = 90 * 24 * 3600;
+
+/// The emission rate of an ongoing reward can only be increased within this
+/// period before its end.
+pub const INCREASE_EMISSIONS_PERIOD: u64 = 72 * 3600;
+
+/// The maximum number of accounts fetched in a single `getMultipleAccounts`
+/// request.
+pub const MAX_FETCH_ACCOUNTS: usize = 100;
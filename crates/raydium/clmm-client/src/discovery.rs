@@ -0,0 +1,190 @@
+use std::{fmt::Display, ops::Range};
+
+use raydium_clmm::{
+    generated::accounts::{AmmConfig, PoolState},
+    utils::derive,
+};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use crate::{
+    constants::MAX_FETCH_ACCOUNTS,
+    error::{ClmmClientError, Result},
+    types::{AmmConfigInfo, PoolInfo},
+};
+
+/// Derive the addresses of the amm configs in a range of indices
+///
+/// # Parameters
+/// - `indices` - The range of amm config indices
+/// - `program_id` - The program id
+///
+/// # Returns
+/// - The amm config addresses, in the order of the indices
+pub fn get_amm_config_addresses(indices: Range<u16>, program_id: Pubkey) -> Vec<Pubkey> {
+    indices.map(|index| derive::derive_amm_config_pubkey(index, Some(program_id)).0).collect()
+}
+
+/// Derive the addresses of the pools of a mint pair in each amm config. Both
+/// mint orderings are derived, so the mints can be given in any order.
+///
+/// # Parameters
+/// - `amm_configs` - The amm config addresses
+/// - `mint_a` - The first mint of the pair
+/// - `mint_b` - The second mint of the pair
+/// - `program_id` - The program id
+///
+/// # Returns
+/// - Pairs of amm config and pool addresses
+pub fn get_pool_addresses(
+    amm_configs: &[Pubkey],
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    program_id: Pubkey,
+) -> Vec<(Pubkey, Pubkey)> {
+    amm_configs
+        .iter()
+        .flat_map(|amm_config| {
+            [(mint_a, mint_b), (mint_b, mint_a)].map(|(mint0, mint1)| {
+                let pool_state =
+                    derive::derive_pool_state_pubkey(*amm_config, mint0, mint1, Some(program_id)).0;
+                (*amm_config, pool_state)
+            })
+        })
+        .collect()
+}
+
+/// Fetch the existing amm configs in a range of indices
+///
+/// # Parameters
+/// - `indices` - The range of amm config indices to look up
+/// - `program_id` - The program id
+/// - `fetch_accounts` - Fetches a batch of at most `MAX_FETCH_ACCOUNTS`
+///   accounts, e.g. with `RpcClient::get_multiple_accounts`
+///
+/// # Returns
+/// - The existing amm configs, in the order of their indices
+pub fn fetch_amm_configs<F, E>(
+    indices: Range<u16>,
+    program_id: Pubkey,
+    fetch_accounts: F,
+) -> Result<Vec<AmmConfigInfo>>
+where
+    F: FnMut(&[Pubkey]) -> std::result::Result<Vec<Option<Account>>, E>,
+    E: Display,
+{
+    let addresses = get_amm_config_addresses(indices, program_id);
+    let amm_configs = fetch_and_decode(&addresses, program_id, fetch_accounts, |data| {
+        AmmConfig::from_bytes(data).ok()
+    })?;
+
+    Ok(amm_configs
+        .into_iter()
+        .map(|(address, amm_config)| AmmConfigInfo { address, amm_config })
+        .collect())
+}
+
+/// Fetch the pools of a mint pair across amm configs
+///
+/// # Parameters
+/// - `amm_configs` - The amm configs to look for pools in
+/// - `mint_a` - The first mint of the pair
+/// - `mint_b` - The second mint of the pair
+/// - `program_id` - The program id
+/// - `fetch_accounts` - Fetches a batch of at most `MAX_FETCH_ACCOUNTS`
+///   accounts, e.g. with `RpcClient::get_multiple_accounts`
+///
+/// # Returns
+/// - The existing pools, sorted by decreasing liquidity
+pub fn fetch_pools_by_mints<F, E>(
+    amm_configs: &[AmmConfigInfo],
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    program_id: Pubkey,
+    fetch_accounts: F,
+) -> Result<Vec<PoolInfo>>
+where
+    F: FnMut(&[Pubkey]) -> std::result::Result<Vec<Option<Account>>, E>,
+    E: Display,
+{
+    let amm_config_addresses =
+        amm_configs.iter().map(|amm_config| amm_config.address).collect::<Vec<_>>();
+    let pool_addresses = get_pool_addresses(&amm_config_addresses, mint_a, mint_b, program_id);
+    let addresses = pool_addresses.iter().map(|(_, pool_state)| *pool_state).collect::<Vec<_>>();
+    let pool_states = fetch_and_decode(&addresses, program_id, fetch_accounts, |data| {
+        PoolState::from_bytes(data).ok()
+    })?;
+
+    let mut pools = pool_states
+        .into_iter()
+        .filter_map(|(address, pool_state)| {
+            let amm_config = amm_configs
+                .iter()
+                .find(|amm_config| amm_config.address == pool_state.amm_config)?;
+            Some(PoolInfo {
+                address,
+                amm_config: amm_config.address,
+                tick_spacing: amm_config.amm_config.tick_spacing,
+                trade_fee_rate: amm_config.amm_config.trade_fee_rate,
+                pool_state,
+            })
+        })
+        .collect::<Vec<_>>();
+    pools.sort_by(|a, b| b.pool_state.liquidity.cmp(&a.pool_state.liquidity));
+
+    Ok(pools)
+}
+
+/// Find the amm config of a fee tier
+///
+/// # Parameters
+/// - `amm_configs` - The amm configs to choose from
+/// - `tick_spacing` - The tick spacing of the fee tier
+/// - `trade_fee_rate` - The trade fee rate of the fee tier, in hundredths of a
+///   bip
+///
+/// # Returns
+/// - The amm config with the lowest index matching the fee tier, if any
+pub fn find_amm_config(
+    amm_configs: &[AmmConfigInfo],
+    tick_spacing: u16,
+    trade_fee_rate: u32,
+) -> Option<&AmmConfigInfo> {
+    amm_configs
+        .iter()
+        .filter(|amm_config| {
+            amm_config.amm_config.tick_spacing == tick_spacing
+                && amm_config.amm_config.trade_fee_rate == trade_fee_rate
+        })
+        .min_by_key(|amm_config| amm_config.amm_config.index)
+}
+
+// Private functions
+
+fn fetch_and_decode<T, F, E>(
+    addresses: &[Pubkey],
+    program_id: Pubkey,
+    mut fetch_accounts: F,
+    decode: impl Fn(&[u8]) -> Option<T>,
+) -> Result<Vec<(Pubkey, T)>>
+where
+    F: FnMut(&[Pubkey]) -> std::result::Result<Vec<Option<Account>>, E>,
+    E: Display,
+{
+    let mut decoded = Vec::new();
+    for chunk in addresses.chunks(MAX_FETCH_ACCOUNTS) {
+        let accounts = fetch_accounts(chunk)
+            .map_err(|e| ClmmClientError::FetchAccountsFailed(e.to_string()))?;
+        if accounts.len() != chunk.len() {
+            return Err(ClmmClientError::FetchAccountsFailed(format!(
+                "expected {} accounts, got {}",
+                chunk.len(),
+                accounts.len()
+            )));
+        }
+        decoded.extend(chunk.iter().zip(accounts).filter_map(|(address, account)| {
+            let account = account.filter(|account| account.owner == program_id)?;
+            Some((*address, decode(&account.data)?))
+        }));
+    }
+    Ok(decoded)
+}
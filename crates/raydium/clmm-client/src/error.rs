@@ -14,6 +14,40 @@ pub enum ClmmClientError {
     NoTickArrayAvailable,
     #[error("Swap error: {0}")]
     SwapError(#[from] raydium_clmm::math::swap_v2::SwapError),
+    #[error("Liquidity error: {0}")]
+    LiquidityError(#[from] raydium_clmm::math::liquidity::LiquidityError),
+    #[error("Tick error: {0}")]
+    TickError(#[from] raydium_clmm::math::tick::TickError),
+    #[error("Pool state error: {0}")]
+    PoolStateError(#[from] raydium_clmm::state::PoolStateError),
+    #[error("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[error("Invalid transfer fee")]
+    InvalidTransferFee,
+    #[error("Invalid slippage tolerance")]
+    InvalidSlippageTolerance,
+    #[error("Invalid mint account")]
+    InvalidMintAccount,
+    #[error("Pool operation disabled: {0:?}")]
+    PoolOperationDisabled(raydium_clmm::state::PoolStatusBitIndex),
+    #[error("Pool not open until {open_time}")]
+    PoolNotOpen { open_time: u64 },
+    #[error("Inconsistent pool snapshots")]
+    InconsistentPoolSnapshots,
+    #[error("Invalid reward index")]
+    InvalidRewardIndex,
+    #[error("All reward slots are in use")]
+    RewardSlotsFull,
+    #[error("Reward mint not allowed")]
+    RewardMintNotAllowed,
+    #[error("Reward authority not approved")]
+    RewardAuthorityNotApproved,
+    #[error("Invalid reward schedule")]
+    InvalidRewardSchedule,
+    #[error("Mint not supported by the program: {0}")]
+    UnsupportedMint(solana_sdk::pubkey::Pubkey),
+    #[error("Failed to fetch accounts: {0}")]
+    FetchAccountsFailed(String),
 }
 
 pub type Result<T> = std::result::Result<T, ClmmClientError>;
@@ -1,19 +1,31 @@
-use raydium_clmm::{generated, math::tick::get_array_start_index, utils::derive};
+use raydium_clmm::{
+    generated::{
+        self,
+        accounts::{OperationState, PoolState},
+    },
+    math::{tick::get_array_start_index, tickarray_bitmap::is_overflow_default_tickarray_bitmap},
+    utils::derive,
+};
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
     signature::{Keypair, Signer},
 };
 use spl_associated_token_account::{
-    get_associated_token_address, ID as SPL_ASSOCIATED_TOKEN_ACCOUNT_ID,
+    get_associated_token_address, get_associated_token_address_with_program_id,
+    ID as SPL_ASSOCIATED_TOKEN_ACCOUNT_ID,
 };
 use spl_token::ID as SPL_TOKEN_ID;
 use spl_token_2022::ID as SPL_TOKEN_2022_ID;
 
 use crate::{
     constants::{MEMO_PROGRAM_ID, METADATA_PROGRAM_ID},
-    error::Result,
+    error::{ClmmClientError, Result},
+    quote::{initialize_reward_quote, set_reward_params_quote},
     types::*,
+    validation::{
+        validate_decrease_liquidity, validate_increase_liquidity, validate_pool_mint, validate_swap,
+    },
 };
 
 pub fn prepare_amm_config_instruction(
@@ -76,12 +88,28 @@ pub fn prepare_create_pool_instruction(
     Ok((ix, pool_state))
 }
 
+/// Same as [`prepare_create_pool_instruction`], refusing to build the
+/// instruction when one of the mints cannot be used as a pool token.
+pub fn prepare_create_pool_instruction_checked(
+    params: CreatePoolParams,
+    mint0_data: &[u8],
+    mint1_data: &[u8],
+    whitelist_mints: &[Pubkey],
+    program_id: Pubkey,
+) -> Result<(Instruction, Pubkey)> {
+    validate_pool_mint(params.mint0, mint0_data, whitelist_mints)?;
+    validate_pool_mint(params.mint1, mint1_data, whitelist_mints)?;
+    prepare_create_pool_instruction(params, program_id)
+}
+
 pub fn prepare_open_position_v2_instruction(
     params: OpenPositionV2Params,
     program_id: Pubkey,
 ) -> Result<(Instruction, Keypair, Pubkey)> {
-    let tick_array_lower_start_index = get_array_start_index(params.tick_lower_index, 1);
-    let tick_array_upper_start_index = get_array_start_index(params.tick_upper_index, 1);
+    let tick_array_lower_start_index =
+        get_array_start_index(params.tick_lower_index, params.tick_spacing);
+    let tick_array_upper_start_index =
+        get_array_start_index(params.tick_upper_index, params.tick_spacing);
 
     let position_nft_mint = Keypair::new();
     let personal_position =
@@ -110,9 +138,13 @@ pub fn prepare_open_position_v2_instruction(
     let position_nft_account =
         get_associated_token_address(&params.payer, &position_nft_mint.pubkey());
 
-    let tickarray_bitmap_extension =
-        derive::derive_tick_array_bitmap_pubkey(params.pool_state, Some(program_id)).0;
-    let remaining_accounts = vec![AccountMeta::new(tickarray_bitmap_extension, false)];
+    let remaining_accounts = get_open_position_remaining_accounts(
+        params.pool_state,
+        params.tick_spacing,
+        params.tick_lower_index,
+        params.tick_upper_index,
+        program_id,
+    );
 
     let mut ix = generated::instructions::OpenPositionV2Builder::new()
         .payer(params.payer)
@@ -152,13 +184,40 @@ pub fn prepare_open_position_v2_instruction(
     Ok((ix, position_nft_mint, protocol_position))
 }
 
+/// Same as [`prepare_open_position_v2_instruction`], refusing to build the
+/// instruction when the pool has opening positions disabled.
+pub fn prepare_open_position_v2_instruction_checked(
+    params: OpenPositionV2Params,
+    pool_state: &PoolState,
+    program_id: Pubkey,
+) -> Result<(Instruction, Keypair, Pubkey)> {
+    validate_increase_liquidity(pool_state)?;
+    prepare_open_position_v2_instruction(params, program_id)
+}
+
+#[deprecated(note = "use `prepare_increase_liquidity_v2_instruction_for_position`, which \
+                     supports Token-2022 position NFTs")]
 pub fn prepare_increase_liquidity_v2_instruction(
     params: IncreaseLiquidityV2Params,
     program_id: Pubkey,
+) -> Result<Instruction> {
+    let position_accounts =
+        PositionAccounts { nft_token_program: SPL_TOKEN_ID, reward_recipients: Vec::new() };
+    prepare_increase_liquidity_v2_instruction_for_position(params, &position_accounts, program_id)
+}
+
+pub fn prepare_increase_liquidity_v2_instruction_for_position(
+    params: IncreaseLiquidityV2Params,
+    position_accounts: &PositionAccounts,
+    program_id: Pubkey,
 ) -> Result<Instruction> {
     let personal_position =
         derive::derive_personal_position_pubkey(params.position_nft_mint, Some(program_id)).0;
-    let nft_account = get_associated_token_address(&params.nft_owner, &params.position_nft_mint);
+    let nft_account = get_associated_token_address_with_program_id(
+        &params.nft_owner,
+        &params.position_nft_mint,
+        &position_accounts.nft_token_program,
+    );
 
     let mut ix = generated::instructions::IncreaseLiquidityV2Builder::new()
         .nft_owner(params.nft_owner)
@@ -186,6 +245,18 @@ pub fn prepare_increase_liquidity_v2_instruction(
     Ok(ix)
 }
 
+/// Same as [`prepare_increase_liquidity_v2_instruction_for_position`], refusing
+/// to build the instruction when the pool has increasing liquidity disabled.
+pub fn prepare_increase_liquidity_v2_instruction_checked(
+    params: IncreaseLiquidityV2Params,
+    position_accounts: &PositionAccounts,
+    pool_state: &PoolState,
+    program_id: Pubkey,
+) -> Result<Instruction> {
+    validate_increase_liquidity(pool_state)?;
+    prepare_increase_liquidity_v2_instruction_for_position(params, position_accounts, program_id)
+}
+
 pub fn prepare_swap_v2_instruction(
     params: SwapV2Params,
     program_id: Pubkey,
@@ -230,13 +301,58 @@ pub fn prepare_swap_v2_instruction(
     Ok(ix)
 }
 
+/// Same as [`prepare_swap_v2_instruction`], refusing to build the instruction
+/// when the pool has swaps disabled or is not open at `current_timestamp`.
+pub fn prepare_swap_v2_instruction_checked(
+    params: SwapV2Params,
+    pool_state: &PoolState,
+    current_timestamp: u64,
+    program_id: Pubkey,
+) -> Result<Instruction> {
+    validate_swap(pool_state, current_timestamp)?;
+    prepare_swap_v2_instruction(params, program_id)
+}
+
+#[deprecated(note = "use `prepare_decrease_liquidity_v2_instruction_for_position`, which \
+                     supports Token-2022 position NFTs and pools with rewards")]
 pub fn prepare_decrease_liquidity_v2_instruction(
     params: DecreaseLiquidityV2Params,
     program_id: Pubkey,
+) -> Result<Instruction> {
+    let position_accounts =
+        PositionAccounts { nft_token_program: SPL_TOKEN_ID, reward_recipients: Vec::new() };
+    prepare_decrease_liquidity_v2_instruction_for_position(params, &position_accounts, program_id)
+}
+
+pub fn prepare_decrease_liquidity_v2_instruction_for_position(
+    params: DecreaseLiquidityV2Params,
+    position_accounts: &PositionAccounts,
+    program_id: Pubkey,
 ) -> Result<Instruction> {
     let personal_position =
         derive::derive_personal_position_pubkey(params.position_nft_mint, Some(program_id)).0;
-    let nft_account = get_associated_token_address(&params.nft_owner, &params.position_nft_mint);
+    let nft_account = get_associated_token_address_with_program_id(
+        &params.nft_owner,
+        &params.position_nft_mint,
+        &position_accounts.nft_token_program,
+    );
+    let remaining_accounts: Vec<AccountMeta> = position_accounts
+        .reward_recipients
+        .iter()
+        .flat_map(|reward_recipient| {
+            let reward_token_vault = derive::derive_pool_reward_vault_pubkey(
+                params.pool_state,
+                reward_recipient.reward_mint,
+                Some(program_id),
+            )
+            .0;
+            [
+                AccountMeta::new(reward_token_vault, false),
+                AccountMeta::new(reward_recipient.recipient_token_account, false),
+                AccountMeta::new_readonly(reward_recipient.reward_mint, false),
+            ]
+        })
+        .collect();
 
     let mut ix = generated::instructions::DecreaseLiquidityV2Builder::new()
         .nft_owner(params.nft_owner)
@@ -251,14 +367,472 @@ pub fn prepare_decrease_liquidity_v2_instruction(
         .recipient_token_account0(params.recipient_token_account0)
         .recipient_token_account1(params.recipient_token_account1)
         .token_program2022(SPL_TOKEN_2022_ID)
+        .memo_program(MEMO_PROGRAM_ID)
         .vault0_mint(params.mint0)
         .vault1_mint(params.mint1)
         .liquidity(params.liquidity)
         .amount0_min(params.amount0_min)
         .amount1_min(params.amount1_min)
+        .add_remaining_accounts(&remaining_accounts)
+        .instruction();
+
+    ix.program_id = program_id;
+
+    Ok(ix)
+}
+
+/// Same as [`prepare_decrease_liquidity_v2_instruction_for_position`], refusing
+/// to build the instruction when the pool has decreasing liquidity and
+/// collecting fees and rewards all disabled.
+pub fn prepare_decrease_liquidity_v2_instruction_checked(
+    params: DecreaseLiquidityV2Params,
+    position_accounts: &PositionAccounts,
+    pool_state: &PoolState,
+    program_id: Pubkey,
+) -> Result<Instruction> {
+    validate_decrease_liquidity(pool_state)?;
+    prepare_decrease_liquidity_v2_instruction_for_position(params, position_accounts, program_id)
+}
+
+pub fn prepare_close_position_instruction(
+    params: ClosePositionParams,
+    program_id: Pubkey,
+) -> Result<Instruction> {
+    let personal_position =
+        derive::derive_personal_position_pubkey(params.position_nft_mint, Some(program_id)).0;
+    let position_nft_account = get_associated_token_address_with_program_id(
+        &params.nft_owner,
+        &params.position_nft_mint,
+        &params.nft_token_program,
+    );
+
+    let mut ix = generated::instructions::ClosePositionBuilder::new()
+        .nft_owner(params.nft_owner)
+        .position_nft_mint(params.position_nft_mint)
+        .position_nft_account(position_nft_account)
+        .personal_position(personal_position)
+        .token_program(params.nft_token_program)
+        .instruction();
+
+    ix.program_id = program_id;
+
+    Ok(ix)
+}
+
+pub fn prepare_swap_router_base_in_instruction(
+    params: SwapRouterBaseInParams,
+    program_id: Pubkey,
+) -> Result<Instruction> {
+    if params.hops.is_empty() {
+        return Err(ClmmClientError::InvalidParameter);
+    }
+
+    let mut remaining_accounts = Vec::new();
+    let mut input_mint = params.input_mint;
+    for hop in params.hops {
+        let input_vault =
+            derive::derive_pool_vault_pubkey(hop.pool_state, input_mint, Some(program_id)).0;
+        let output_vault =
+            derive::derive_pool_vault_pubkey(hop.pool_state, hop.output_mint, Some(program_id)).0;
+        let observation_state =
+            derive::derive_observation_pubkey(hop.pool_state, Some(program_id)).0;
+        let tick_array_bitmap_extension =
+            derive::derive_tick_array_bitmap_pubkey(hop.pool_state, Some(program_id)).0;
+
+        remaining_accounts.extend([
+            AccountMeta::new_readonly(hop.amm_config, false),
+            AccountMeta::new(hop.pool_state, false),
+            AccountMeta::new(hop.output_token_account, false),
+            AccountMeta::new(input_vault, false),
+            AccountMeta::new(output_vault, false),
+            AccountMeta::new_readonly(hop.output_mint, false),
+            AccountMeta::new(observation_state, false),
+            AccountMeta::new(tick_array_bitmap_extension, false),
+        ]);
+        remaining_accounts.extend(
+            hop.tick_array_accounts
+                .into_iter()
+                .map(|tick_array| AccountMeta::new(tick_array, false)),
+        );
+
+        input_mint = hop.output_mint;
+    }
+
+    let mut ix = generated::instructions::SwapRouterBaseInBuilder::new()
+        .payer(params.payer)
+        .input_token_account(params.input_token_account)
+        .input_token_mint(params.input_mint)
+        .token_program(params.input_token_program)
+        .token_program2022(SPL_TOKEN_2022_ID)
+        .memo_program(MEMO_PROGRAM_ID)
+        .amount_in(params.amount_in)
+        .amount_out_minimum(params.amount_out_minimum)
+        .add_remaining_accounts(&remaining_accounts)
+        .instruction();
+
+    ix.program_id = program_id;
+
+    Ok(ix)
+}
+
+pub fn prepare_collect_remaining_rewards_instruction(
+    params: CollectRemainingRewardsParams,
+    program_id: Pubkey,
+) -> Result<Instruction> {
+    let reward_token_vault = derive::derive_pool_reward_vault_pubkey(
+        params.pool_state,
+        params.reward_mint,
+        Some(program_id),
+    )
+    .0;
+
+    let mut ix = generated::instructions::CollectRemainingRewardsBuilder::new()
+        .reward_funder(params.reward_funder)
+        .funder_token_account(params.funder_token_account)
+        .pool_state(params.pool_state)
+        .reward_token_vault(reward_token_vault)
+        .reward_vault_mint(params.reward_mint)
+        .token_program(SPL_TOKEN_ID)
+        .token_program2022(SPL_TOKEN_2022_ID)
+        .memo_program(MEMO_PROGRAM_ID)
+        .reward_index(params.reward_index)
+        .instruction();
+
+    // The IDL marks the vault read-only, but the program transfers the
+    // leftovers out of it
+    if let Some(meta) = ix.accounts.iter_mut().find(|meta| meta.pubkey == reward_token_vault) {
+        meta.is_writable = true;
+    }
+    ix.program_id = program_id;
+
+    Ok(ix)
+}
+
+pub fn prepare_initialize_reward_instruction(
+    params: InitializeRewardParams,
+    program_id: Pubkey,
+) -> Result<(Instruction, Pubkey)> {
+    let operation_state = derive::derive_operation_pubkey(Some(program_id)).0;
+    let reward_token_vault = derive::derive_pool_reward_vault_pubkey(
+        params.pool_state,
+        params.reward_mint,
+        Some(program_id),
+    )
+    .0;
+
+    let mut ix = generated::instructions::InitializeRewardBuilder::new()
+        .reward_funder(params.reward_funder)
+        .funder_token_account(params.funder_token_account)
+        .amm_config(params.amm_config)
+        .pool_state(params.pool_state)
+        .operation_state(operation_state)
+        .reward_token_mint(params.reward_mint)
+        .reward_token_vault(reward_token_vault)
+        .reward_token_program(params.reward_token_program)
+        .open_time(params.open_time)
+        .end_time(params.end_time)
+        .emissions_per_second_x64(params.emissions_per_second_x64)
+        .instruction();
+
+    ix.program_id = program_id;
+
+    Ok((ix, reward_token_vault))
+}
+
+pub fn prepare_set_reward_params_instruction(
+    params: SetRewardParamsParams,
+    program_id: Pubkey,
+) -> Result<Instruction> {
+    let operation_state = derive::derive_operation_pubkey(Some(program_id)).0;
+
+    let remaining_accounts = match (params.authority_token_account, params.reward_mint) {
+        (Some(authority_token_account), Some(reward_mint)) => {
+            let reward_token_vault = derive::derive_pool_reward_vault_pubkey(
+                params.pool_state,
+                reward_mint,
+                Some(program_id),
+            )
+            .0;
+            vec![
+                AccountMeta::new(reward_token_vault, false),
+                AccountMeta::new(authority_token_account, false),
+                AccountMeta::new_readonly(reward_mint, false),
+            ]
+        }
+        (None, None) => vec![],
+        _ => return Err(ClmmClientError::InvalidParameter),
+    };
+
+    let mut ix = generated::instructions::SetRewardParamsBuilder::new()
+        .authority(params.authority)
+        .amm_config(params.amm_config)
+        .pool_state(params.pool_state)
+        .operation_state(operation_state)
+        .token_program(SPL_TOKEN_ID)
+        .token_program2022(SPL_TOKEN_2022_ID)
+        .reward_index(params.reward_index)
+        .emissions_per_second_x64(params.emissions_per_second_x64)
+        .open_time(params.open_time)
+        .end_time(params.end_time)
+        .add_remaining_accounts(&remaining_accounts)
         .instruction();
 
     ix.program_id = program_id;
 
     Ok(ix)
 }
+
+pub fn prepare_update_reward_infos_instruction(
+    params: UpdateRewardInfosParams,
+    program_id: Pubkey,
+) -> Result<Instruction> {
+    let mut ix = generated::instructions::UpdateRewardInfosBuilder::new()
+        .pool_state(params.pool_state)
+        .instruction();
+
+    ix.program_id = program_id;
+
+    Ok(ix)
+}
+
+/// Hands all the rewards of the pool over to `new_owner`. Only the program
+/// admin can sign it.
+pub fn prepare_transfer_reward_owner_instruction(
+    params: TransferRewardOwnerParams,
+    program_id: Pubkey,
+) -> Result<Instruction> {
+    let mut ix = generated::instructions::TransferRewardOwnerBuilder::new()
+        .authority(params.authority)
+        .pool_state(params.pool_state)
+        .new_owner(params.new_owner)
+        .instruction();
+
+    ix.program_id = program_id;
+
+    Ok(ix)
+}
+
+/// Builds the instruction funding a reward of the pool, after checking the
+/// schedule against the existing rewards at `current_timestamp`. A reward
+/// already paying out `reward_mint` is extended or restarted, otherwise the
+/// next free reward slot is initialized.
+pub fn prepare_fund_reward_instruction(
+    params: FundRewardParams,
+    pool_state: &PoolState,
+    operation_state: &OperationState,
+    current_timestamp: u64,
+    program_id: Pubkey,
+) -> Result<(Instruction, RewardScheduleQuote)> {
+    let existing_reward_index = pool_state
+        .reward_infos
+        .iter()
+        .position(|reward_info| reward_info.token_mint == params.reward_mint);
+
+    let Some(reward_index) = existing_reward_index else {
+        let quote = initialize_reward_quote(
+            pool_state,
+            operation_state,
+            params.reward_mint,
+            params.reward_funder,
+            params.open_time,
+            params.end_time,
+            params.emissions_per_second_x64,
+            current_timestamp,
+        )?;
+        let (ix, _) = prepare_initialize_reward_instruction(
+            InitializeRewardParams {
+                open_time: params.open_time,
+                end_time: params.end_time,
+                emissions_per_second_x64: params.emissions_per_second_x64,
+                reward_funder: params.reward_funder,
+                funder_token_account: params.funder_token_account,
+                amm_config: params.amm_config,
+                pool_state: params.pool_state,
+                reward_mint: params.reward_mint,
+                reward_token_program: params.reward_token_program,
+            },
+            program_id,
+        )?;
+        return Ok((ix, quote));
+    };
+
+    let quote = set_reward_params_quote(
+        pool_state,
+        reward_index as u8,
+        params.emissions_per_second_x64,
+        params.open_time,
+        params.end_time,
+        current_timestamp,
+    )?;
+    let needs_funding = quote.reward_amount > 0;
+    let ix = prepare_set_reward_params_instruction(
+        SetRewardParamsParams {
+            reward_index: quote.reward_index,
+            emissions_per_second_x64: params.emissions_per_second_x64,
+            open_time: params.open_time,
+            end_time: params.end_time,
+            authority: params.reward_funder,
+            amm_config: params.amm_config,
+            pool_state: params.pool_state,
+            authority_token_account: needs_funding.then_some(params.funder_token_account),
+            reward_mint: needs_funding.then_some(params.reward_mint),
+        },
+        program_id,
+    )?;
+
+    Ok((ix, quote))
+}
+
+pub fn prepare_collect_protocol_fee_instruction(
+    params: CollectProtocolFeeParams,
+    program_id: Pubkey,
+) -> Result<Instruction> {
+    let token_vault_0 =
+        derive::derive_pool_vault_pubkey(params.pool_state, params.mint0, Some(program_id)).0;
+    let token_vault_1 =
+        derive::derive_pool_vault_pubkey(params.pool_state, params.mint1, Some(program_id)).0;
+
+    let mut ix = generated::instructions::CollectProtocolFeeBuilder::new()
+        .owner(params.owner)
+        .pool_state(params.pool_state)
+        .amm_config(params.amm_config)
+        .token_vault0(token_vault_0)
+        .token_vault1(token_vault_1)
+        .vault0_mint(params.mint0)
+        .vault1_mint(params.mint1)
+        .recipient_token_account0(params.recipient_token_account0)
+        .recipient_token_account1(params.recipient_token_account1)
+        .token_program(SPL_TOKEN_ID)
+        .token_program2022(SPL_TOKEN_2022_ID)
+        .amount0_requested(params.amount0_requested)
+        .amount1_requested(params.amount1_requested)
+        .instruction();
+
+    ix.program_id = program_id;
+
+    Ok(ix)
+}
+
+pub fn prepare_collect_fund_fee_instruction(
+    params: CollectFundFeeParams,
+    program_id: Pubkey,
+) -> Result<Instruction> {
+    let token_vault_0 =
+        derive::derive_pool_vault_pubkey(params.pool_state, params.mint0, Some(program_id)).0;
+    let token_vault_1 =
+        derive::derive_pool_vault_pubkey(params.pool_state, params.mint1, Some(program_id)).0;
+
+    let mut ix = generated::instructions::CollectFundFeeBuilder::new()
+        .owner(params.owner)
+        .pool_state(params.pool_state)
+        .amm_config(params.amm_config)
+        .token_vault0(token_vault_0)
+        .token_vault1(token_vault_1)
+        .vault0_mint(params.mint0)
+        .vault1_mint(params.mint1)
+        .recipient_token_account0(params.recipient_token_account0)
+        .recipient_token_account1(params.recipient_token_account1)
+        .token_program(SPL_TOKEN_ID)
+        .token_program2022(SPL_TOKEN_2022_ID)
+        .amount0_requested(params.amount0_requested)
+        .amount1_requested(params.amount1_requested)
+        .instruction();
+
+    ix.program_id = program_id;
+
+    Ok(ix)
+}
+
+pub fn prepare_update_amm_config_instruction(
+    params: UpdateAmmConfigParams,
+    program_id: Pubkey,
+) -> Result<Instruction> {
+    let amm_config = derive::derive_amm_config_pubkey(params.config_index, Some(program_id)).0;
+
+    let remaining_accounts = match (params.param, params.new_owner) {
+        (3 | 4, Some(new_owner)) => vec![AccountMeta::new_readonly(new_owner, false)],
+        (3 | 4, None) => return Err(ClmmClientError::InvalidParameter),
+        _ => vec![],
+    };
+
+    let mut ix = generated::instructions::UpdateAmmConfigBuilder::new()
+        .owner(params.owner)
+        .amm_config(amm_config)
+        .param(params.param)
+        .value(params.value)
+        .add_remaining_accounts(&remaining_accounts)
+        .instruction();
+
+    ix.program_id = program_id;
+
+    Ok(ix)
+}
+
+pub fn prepare_update_pool_status_instruction(
+    params: UpdatePoolStatusParams,
+    program_id: Pubkey,
+) -> Result<Instruction> {
+    let mut ix = generated::instructions::UpdatePoolStatusBuilder::new()
+        .authority(params.authority)
+        .pool_state(params.pool_state)
+        .status(params.status)
+        .instruction();
+
+    ix.program_id = program_id;
+
+    Ok(ix)
+}
+
+pub fn prepare_create_operation_account_instruction(
+    params: CreateOperationAccountParams,
+    program_id: Pubkey,
+) -> Result<(Instruction, Pubkey)> {
+    let operation_state = derive::derive_operation_pubkey(Some(program_id)).0;
+
+    let mut ix = generated::instructions::CreateOperationAccountBuilder::new()
+        .owner(params.owner)
+        .operation_state(operation_state)
+        .instruction();
+
+    ix.program_id = program_id;
+
+    Ok((ix, operation_state))
+}
+
+pub fn prepare_update_operation_account_instruction(
+    params: UpdateOperationAccountParams,
+    program_id: Pubkey,
+) -> Result<Instruction> {
+    let operation_state = derive::derive_operation_pubkey(Some(program_id)).0;
+
+    let mut ix = generated::instructions::UpdateOperationAccountBuilder::new()
+        .owner(params.owner)
+        .operation_state(operation_state)
+        .param(params.param)
+        .keys(params.keys)
+        .instruction();
+
+    ix.program_id = program_id;
+
+    Ok(ix)
+}
+
+// Private functions
+
+/// The program only reads the tick array bitmap extension when one of the
+/// position tick arrays is out of the range of the default pool bitmap.
+fn get_open_position_remaining_accounts(
+    pool_state: Pubkey,
+    tick_spacing: u16,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    program_id: Pubkey,
+) -> Vec<AccountMeta> {
+    if !is_overflow_default_tickarray_bitmap(tick_spacing, &[tick_lower_index, tick_upper_index]) {
+        return vec![];
+    }
+
+    let tickarray_bitmap_extension =
+        derive::derive_tick_array_bitmap_pubkey(pool_state, Some(program_id)).0;
+    vec![AccountMeta::new(tickarray_bitmap_extension, false)]
+}
@@ -1,7 +1,11 @@
+pub mod analytics;
 pub mod constants;
+pub mod discovery;
 pub mod error;
 pub mod instructions;
 pub mod math;
 pub mod preview;
+pub mod quote;
 pub mod types;
 pub mod utils;
+pub mod validation;
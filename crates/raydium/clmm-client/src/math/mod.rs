@@ -1 +1,3 @@
 pub mod price;
+pub mod reward;
+pub mod token;
@@ -0,0 +1,42 @@
+use raydium_clmm::{
+    libraries::{big_num::U256, fixed_point_64},
+    math::full_math::MulDiv,
+};
+
+use crate::error::{ClmmClientError, Result};
+
+/// Calculate the emission rate that distributes `total_amount` evenly over
+/// `duration` seconds. The rate is rounded down so that the amount pulled by
+/// the program never exceeds `total_amount`.
+pub fn calculate_emissions_per_second_x64(total_amount: u64, duration: u64) -> Result<u128> {
+    if duration == 0 {
+        return Err(ClmmClientError::InvalidParameter);
+    }
+    U256::from(total_amount)
+        .mul_div_floor(U256::from(fixed_point_64::Q64), U256::from(duration))
+        .filter(|emissions| *emissions <= U256::from(u128::MAX))
+        .map(|emissions| emissions.as_u128())
+        .ok_or(ClmmClientError::ArithmeticOverflow)
+}
+
+/// Calculate the amount of reward tokens emitted at `emissions_per_second_x64`
+/// over `duration` seconds, rounded up the same way the program does when it
+/// funds a reward vault.
+pub fn calculate_reward_amount(emissions_per_second_x64: u128, duration: u64) -> Result<u64> {
+    U256::from(duration)
+        .mul_div_ceil(U256::from(emissions_per_second_x64), U256::from(fixed_point_64::Q64))
+        .filter(|amount| *amount <= U256::from(u64::MAX))
+        .map(|amount| amount.as_u64())
+        .ok_or(ClmmClientError::ArithmeticOverflow)
+}
+
+/// Calculate the amount of reward tokens emitted at `emissions_per_second_x64`
+/// over `duration` seconds, rounded down the same way the program does when it
+/// extends an ongoing reward.
+pub fn calculate_reward_amount_floor(emissions_per_second_x64: u128, duration: u64) -> Result<u64> {
+    U256::from(duration)
+        .mul_div_floor(U256::from(emissions_per_second_x64), U256::from(fixed_point_64::Q64))
+        .filter(|amount| *amount <= U256::from(u64::MAX))
+        .map(|amount| amount.as_u64())
+        .ok_or(ClmmClientError::ArithmeticOverflow)
+}
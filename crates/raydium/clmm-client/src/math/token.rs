@@ -0,0 +1,104 @@
+use solana_program::program_pack::Pack;
+use spl_token_2022::{
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+    state::Mint,
+};
+
+use crate::{
+    constants::BPS_DENOMINATOR,
+    error::{ClmmClientError, Result},
+    types::TransferFee,
+};
+
+/// Read the transfer fee of a Token-2022 mint for the given epoch. Returns
+/// `None` for classic SPL mints and mints without the transfer fee extension.
+pub fn get_transfer_fee(mint_data: &[u8], epoch: u64) -> Result<Option<TransferFee>> {
+    if mint_data.len() == Mint::LEN {
+        return Ok(None);
+    }
+    let mint = StateWithExtensions::<Mint>::unpack(mint_data)
+        .map_err(|_| ClmmClientError::InvalidMintAccount)?;
+    let Ok(config) = mint.get_extension::<TransferFeeConfig>() else {
+        return Ok(None);
+    };
+    let fee = config.get_epoch_fee(epoch);
+
+    Ok(Some(TransferFee::new_with_max(
+        u16::from(fee.transfer_fee_basis_points),
+        u64::from(fee.maximum_fee),
+    )))
+}
+
+/// Apply a transfer fee to an amount, e.g. the amount received after sending
+/// 10000 with a 100 bps fee is 9900.
+pub fn apply_transfer_fee(amount: u64, transfer_fee: TransferFee) -> Result<u64> {
+    if transfer_fee.fee_bps > BPS_DENOMINATOR {
+        return Err(ClmmClientError::InvalidTransferFee);
+    }
+    if transfer_fee.fee_bps == 0 || amount == 0 {
+        return Ok(amount);
+    }
+    let numerator = u128::from(amount) * u128::from(transfer_fee.fee_bps);
+    let raw_fee: u64 = numerator
+        .div_ceil(BPS_DENOMINATOR.into())
+        .try_into()
+        .map_err(|_| ClmmClientError::ArithmeticOverflow)?;
+    let fee_amount = raw_fee.min(transfer_fee.max_fee);
+    Ok(amount - fee_amount)
+}
+
+/// Reverse the application of a transfer fee, e.g. to receive 9900 with a 100
+/// bps fee, 10000 has to be sent.
+pub fn reverse_apply_transfer_fee(amount: u64, transfer_fee: TransferFee) -> Result<u64> {
+    if transfer_fee.fee_bps > BPS_DENOMINATOR {
+        Err(ClmmClientError::InvalidTransferFee)
+    } else if transfer_fee.fee_bps == 0 || amount == 0 {
+        Ok(amount)
+    } else if transfer_fee.fee_bps == BPS_DENOMINATOR {
+        amount.checked_add(transfer_fee.max_fee).ok_or(ClmmClientError::ArithmeticOverflow)
+    } else {
+        let numerator = u128::from(amount) * u128::from(BPS_DENOMINATOR);
+        let denominator = u128::from(BPS_DENOMINATOR - transfer_fee.fee_bps);
+        let raw_pre_fee_amount = numerator.div_ceil(denominator);
+        let fee_amount = raw_pre_fee_amount - u128::from(amount);
+        if fee_amount >= u128::from(transfer_fee.max_fee) {
+            amount.checked_add(transfer_fee.max_fee).ok_or(ClmmClientError::ArithmeticOverflow)
+        } else {
+            raw_pre_fee_amount.try_into().map_err(|_| ClmmClientError::ArithmeticOverflow)
+        }
+    }
+}
+
+/// Get the maximum amount with a slippage tolerance, e.g. 10000 with 100 bps
+/// slippage tolerance is 10100.
+pub fn get_max_amount_with_slippage_tolerance(
+    amount: u64,
+    slippage_tolerance_bps: u16,
+) -> Result<u64> {
+    if slippage_tolerance_bps > BPS_DENOMINATOR {
+        return Err(ClmmClientError::InvalidSlippageTolerance);
+    }
+    let numerator =
+        u128::from(amount) * (u128::from(BPS_DENOMINATOR) + u128::from(slippage_tolerance_bps));
+    numerator
+        .div_ceil(BPS_DENOMINATOR.into())
+        .try_into()
+        .map_err(|_| ClmmClientError::ArithmeticOverflow)
+}
+
+/// Get the minimum amount with a slippage tolerance, e.g. 10000 with 100 bps
+/// slippage tolerance is 9900.
+pub fn get_min_amount_with_slippage_tolerance(
+    amount: u64,
+    slippage_tolerance_bps: u16,
+) -> Result<u64> {
+    if slippage_tolerance_bps > BPS_DENOMINATOR {
+        return Err(ClmmClientError::InvalidSlippageTolerance);
+    }
+    let numerator =
+        u128::from(amount) * (u128::from(BPS_DENOMINATOR) - u128::from(slippage_tolerance_bps));
+    numerator
+        .checked_div(BPS_DENOMINATOR.into())
+        .and_then(|result| result.try_into().ok())
+        .ok_or(ClmmClientError::ArithmeticOverflow)
+}
@@ -1,37 +1,94 @@
+use raydium_clmm::{constants::FEE_RATE_DENOMINATOR_VALUE, utils::derive};
+use rust_decimal::Decimal;
+
 use crate::{
     error::{ClmmClientError, Result},
-    types::PreviewSwapV2Params,
+    math::price::normalize_price_sqrt,
+    quote::map_swap_error,
+    types::{PreviewSwapV2Params, SwapV2Preview},
 };
 
-pub fn preview_swap_v2(
-    params: PreviewSwapV2Params,
-) -> Result<raydium_clmm::math::swap_v2::SwapState> {
+pub fn preview_swap_v2(params: PreviewSwapV2Params) -> Result<SwapV2Preview> {
     let PreviewSwapV2Params {
         amount,
         sqrt_price_limit_x64,
         is_base_input,
         zero_for_one,
-        trade_fee_rate,
+        program_id,
+        pool_id,
+        amm_config,
         pool_state,
         tick_array_bitmap,
         mut tick_array_accounts,
     } = params;
 
-    let start_tick_index =
-        tick_array_accounts.front().ok_or(ClmmClientError::NoTickArrayAvailable)?.start_tick_index;
+    if tick_array_accounts.is_empty() {
+        return Err(ClmmClientError::NoTickArrayAvailable);
+    }
+    let (is_pool_current_tick_array, start_tick_index) = pool_state
+        .get_first_initialized_tick_array(&Some(tick_array_bitmap.clone()), zero_for_one)?;
 
-    let (swap_state, _) = raydium_clmm::math::swap_v2::compute_swap(
+    let (swap_state, tick_array_indices) = raydium_clmm::math::swap_v2::compute_swap(
         zero_for_one,
         is_base_input,
-        true,
-        trade_fee_rate,
+        is_pool_current_tick_array,
+        amm_config.trade_fee_rate,
         amount,
         start_tick_index,
         sqrt_price_limit_x64,
         &pool_state,
         &tick_array_bitmap,
         &mut tick_array_accounts,
-    )?;
+    )
+    .map_err(map_swap_error)?;
+
+    // The program takes the protocol and fund fees out of every step fee
+    let mut protocol_fee = 0u64;
+    let mut fund_fee = 0u64;
+    for step_fee_amount in &swap_state.step_fee_amounts {
+        let step_protocol_fee = get_fee_share(*step_fee_amount, amm_config.protocol_fee_rate)?;
+        let step_fund_fee = get_fee_share(*step_fee_amount, amm_config.fund_fee_rate)?;
+        protocol_fee = protocol_fee
+            .checked_add(step_protocol_fee)
+            .ok_or(ClmmClientError::ArithmeticOverflow)?;
+        fund_fee =
+            fund_fee.checked_add(step_fund_fee).ok_or(ClmmClientError::ArithmeticOverflow)?;
+    }
+    let lp_fee = swap_state.fee_amount - protocol_fee - fund_fee;
+
+    let price_ratio = normalize_price_sqrt(swap_state.sqrt_price_x64)
+        / normalize_price_sqrt(pool_state.sqrt_price_x64);
+    let price_impact = (price_ratio * price_ratio - Decimal::ONE).abs();
+
+    let tick_array_accounts = tick_array_indices
+        .into_iter()
+        .map(|start_tick_index| {
+            derive::derive_tick_array_pubkey(pool_id, start_tick_index, Some(program_id)).0
+        })
+        .collect();
+
+    Ok(SwapV2Preview {
+        amount_in: swap_state.amount_in,
+        amount_out: swap_state.amount_out,
+        remaining_amount: swap_state.remaining_amount,
+        trade_fee: swap_state.fee_amount,
+        protocol_fee,
+        fund_fee,
+        lp_fee,
+        sqrt_price_x64_before: pool_state.sqrt_price_x64,
+        sqrt_price_x64_after: swap_state.sqrt_price_x64,
+        tick_after: swap_state.tick,
+        liquidity_after: swap_state.liquidity,
+        price_impact,
+        crossed_ticks: swap_state.crossed_ticks,
+        tick_array_accounts,
+    })
+}
+
+// Private functions
 
-    Ok(swap_state)
+fn get_fee_share(fee_amount: u64, fee_rate: u32) -> Result<u64> {
+    (u128::from(fee_amount) * u128::from(fee_rate) / u128::from(FEE_RATE_DENOMINATOR_VALUE))
+        .try_into()
+        .map_err(|_| ClmmClientError::ArithmeticOverflow)
 }
@@ -0,0 +1,79 @@
+use raydium_clmm::{
+    generated::{
+        accounts::{PersonalPositionState, PoolState},
+        types::TickState,
+    },
+    libraries::{big_num::U128, fixed_point_64},
+    math::full_math::MulDiv,
+    state::get_fee_growth_inside,
+};
+
+use crate::{
+    error::{ClmmClientError, Result},
+    math::token::apply_transfer_fee,
+    types::{CollectFeesQuote, TransferFee},
+};
+
+/// Calculate the fees owed to a personal position
+///
+/// # Parameters
+/// - `pool_state` - The pool state
+/// - `position` - The personal position state
+/// - `tick_lower` - The tick state at the lower tick index of the position
+/// - `tick_upper` - The tick state at the upper tick index of the position
+/// - `transfer_fee_0` - The transfer fee for token 0
+/// - `transfer_fee_1` - The transfer fee for token 1
+///
+/// # Returns
+/// - A CollectFeesQuote struct containing the fees received for token 0 and
+///   token 1
+pub fn collect_fees_quote(
+    pool_state: &PoolState,
+    position: &PersonalPositionState,
+    tick_lower: &TickState,
+    tick_upper: &TickState,
+    transfer_fee_0: Option<TransferFee>,
+    transfer_fee_1: Option<TransferFee>,
+) -> Result<CollectFeesQuote> {
+    let (fee_growth_inside_0_x64, fee_growth_inside_1_x64) = get_fee_growth_inside(
+        tick_lower,
+        tick_upper,
+        pool_state.tick_current,
+        pool_state.fee_growth_global0_x64,
+        pool_state.fee_growth_global1_x64,
+    );
+
+    let withdrawable_fee_0 = get_latest_token_fees(
+        position.token_fees_owed0,
+        position.fee_growth_inside0_last_x64,
+        fee_growth_inside_0_x64,
+        position.liquidity,
+    )?;
+    let withdrawable_fee_1 = get_latest_token_fees(
+        position.token_fees_owed1,
+        position.fee_growth_inside1_last_x64,
+        fee_growth_inside_1_x64,
+        position.liquidity,
+    )?;
+
+    let fee_owed_0 = apply_transfer_fee(withdrawable_fee_0, transfer_fee_0.unwrap_or_default())?;
+    let fee_owed_1 = apply_transfer_fee(withdrawable_fee_1, transfer_fee_1.unwrap_or_default())?;
+
+    Ok(CollectFeesQuote { fee_owed_0, fee_owed_1 })
+}
+
+// Private functions
+
+fn get_latest_token_fees(
+    last_total_fees: u64,
+    fee_growth_inside_last_x64: u128,
+    fee_growth_inside_latest_x64: u128,
+    liquidity: u128,
+) -> Result<u64> {
+    let fee_growth_delta =
+        U128::from(fee_growth_inside_latest_x64.wrapping_sub(fee_growth_inside_last_x64))
+            .mul_div_floor(U128::from(liquidity), U128::from(fixed_point_64::Q64))
+            .ok_or(ClmmClientError::ArithmeticOverflow)?
+            .to_underflow_u64();
+    last_total_fees.checked_add(fee_growth_delta).ok_or(ClmmClientError::ArithmeticOverflow)
+}
@@ -0,0 +1,409 @@
+use raydium_clmm::{
+    generated::accounts::PoolState,
+    math::{
+        liquidity::{
+            get_delta_amount_0_unsigned, get_delta_amount_1_unsigned, get_liquidity_from_amounts,
+            get_liquidity_from_single_amount_0, get_liquidity_from_single_amount_1,
+        },
+        tick::get_sqrt_price_at_tick,
+    },
+};
+
+use crate::{
+    error::Result,
+    math::token::{
+        apply_transfer_fee, get_max_amount_with_slippage_tolerance,
+        get_min_amount_with_slippage_tolerance, reverse_apply_transfer_fee,
+    },
+    types::{DecreaseLiquidityQuote, IncreaseLiquidityQuote, TransferFee},
+};
+
+/// Calculate the quote for increasing liquidity
+///
+/// # Parameters
+/// - `liquidity_delta` - The amount of liquidity to increase
+/// - `slippage_tolerance_bps` - The slippage tolerance in bps
+/// - `pool_state` - The pool state
+/// - `tick_lower_index` - The lower tick index of the position
+/// - `tick_upper_index` - The upper tick index of the position
+/// - `transfer_fee_0` - The transfer fee for token 0
+/// - `transfer_fee_1` - The transfer fee for token 1
+///
+/// # Returns
+/// - An IncreaseLiquidityQuote struct containing the estimated token amounts
+pub fn increase_liquidity_quote(
+    liquidity_delta: u128,
+    slippage_tolerance_bps: u16,
+    pool_state: &PoolState,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    transfer_fee_0: Option<TransferFee>,
+    transfer_fee_1: Option<TransferFee>,
+) -> Result<IncreaseLiquidityQuote> {
+    if liquidity_delta == 0 {
+        return Ok(IncreaseLiquidityQuote::default());
+    }
+
+    let (tick_lower_index, tick_upper_index) =
+        order_tick_indexes(tick_lower_index, tick_upper_index);
+    let (amount0_before_fee, amount1_before_fee) = get_amounts_from_liquidity(
+        liquidity_delta,
+        pool_state,
+        tick_lower_index,
+        tick_upper_index,
+        true,
+    )?;
+
+    let amount0_est =
+        reverse_apply_transfer_fee(amount0_before_fee, transfer_fee_0.unwrap_or_default())?;
+    let amount1_est =
+        reverse_apply_transfer_fee(amount1_before_fee, transfer_fee_1.unwrap_or_default())?;
+
+    let amount0_max = get_max_amount_with_slippage_tolerance(amount0_est, slippage_tolerance_bps)?;
+    let amount1_max = get_max_amount_with_slippage_tolerance(amount1_est, slippage_tolerance_bps)?;
+
+    Ok(IncreaseLiquidityQuote {
+        liquidity_delta,
+        amount0_est,
+        amount1_est,
+        amount0_max,
+        amount1_max,
+    })
+}
+
+/// Calculate the quote for increasing liquidity given a token 0 amount
+///
+/// # Parameters
+/// - `amount_0` - The amount of token 0 to deposit, including transfer fees
+/// - `slippage_tolerance_bps` - The slippage tolerance in bps
+/// - `pool_state` - The pool state
+/// - `tick_lower_index` - The lower tick index of the position
+/// - `tick_upper_index` - The upper tick index of the position
+/// - `transfer_fee_0` - The transfer fee for token 0
+/// - `transfer_fee_1` - The transfer fee for token 1
+///
+/// # Returns
+/// - An IncreaseLiquidityQuote struct containing the estimated token amounts
+pub fn increase_liquidity_quote_0(
+    amount_0: u64,
+    slippage_tolerance_bps: u16,
+    pool_state: &PoolState,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    transfer_fee_0: Option<TransferFee>,
+    transfer_fee_1: Option<TransferFee>,
+) -> Result<IncreaseLiquidityQuote> {
+    let amount_0 = apply_transfer_fee(amount_0, transfer_fee_0.unwrap_or_default())?;
+    let (sqrt_price_lower, sqrt_price_upper) =
+        get_sqrt_price_range(tick_lower_index, tick_upper_index)?;
+
+    let liquidity = get_liquidity_from_single_amount_0(
+        pool_state.sqrt_price_x64,
+        sqrt_price_lower,
+        sqrt_price_upper,
+        amount_0,
+    )?;
+
+    increase_liquidity_quote(
+        liquidity,
+        slippage_tolerance_bps,
+        pool_state,
+        tick_lower_index,
+        tick_upper_index,
+        transfer_fee_0,
+        transfer_fee_1,
+    )
+}
+
+/// Calculate the quote for increasing liquidity given a token 1 amount
+///
+/// # Parameters
+/// - `amount_1` - The amount of token 1 to deposit, including transfer fees
+/// - `slippage_tolerance_bps` - The slippage tolerance in bps
+/// - `pool_state` - The pool state
+/// - `tick_lower_index` - The lower tick index of the position
+/// - `tick_upper_index` - The upper tick index of the position
+/// - `transfer_fee_0` - The transfer fee for token 0
+/// - `transfer_fee_1` - The transfer fee for token 1
+///
+/// # Returns
+/// - An IncreaseLiquidityQuote struct containing the estimated token amounts
+pub fn increase_liquidity_quote_1(
+    amount_1: u64,
+    slippage_tolerance_bps: u16,
+    pool_state: &PoolState,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    transfer_fee_0: Option<TransferFee>,
+    transfer_fee_1: Option<TransferFee>,
+) -> Result<IncreaseLiquidityQuote> {
+    let amount_1 = apply_transfer_fee(amount_1, transfer_fee_1.unwrap_or_default())?;
+    let (sqrt_price_lower, sqrt_price_upper) =
+        get_sqrt_price_range(tick_lower_index, tick_upper_index)?;
+
+    let liquidity = get_liquidity_from_single_amount_1(
+        pool_state.sqrt_price_x64,
+        sqrt_price_lower,
+        sqrt_price_upper,
+        amount_1,
+    )?;
+
+    increase_liquidity_quote(
+        liquidity,
+        slippage_tolerance_bps,
+        pool_state,
+        tick_lower_index,
+        tick_upper_index,
+        transfer_fee_0,
+        transfer_fee_1,
+    )
+}
+
+/// Calculate the quote for increasing liquidity given both token amounts. The
+/// liquidity is the maximum that can be provided by both amounts.
+///
+/// # Parameters
+/// - `amount_0` - The amount of token 0 available, including transfer fees
+/// - `amount_1` - The amount of token 1 available, including transfer fees
+/// - `slippage_tolerance_bps` - The slippage tolerance in bps
+/// - `pool_state` - The pool state
+/// - `tick_lower_index` - The lower tick index of the position
+/// - `tick_upper_index` - The upper tick index of the position
+/// - `transfer_fee_0` - The transfer fee for token 0
+/// - `transfer_fee_1` - The transfer fee for token 1
+///
+/// # Returns
+/// - An IncreaseLiquidityQuote struct containing the estimated token amounts
+#[allow(clippy::too_many_arguments)]
+pub fn increase_liquidity_quote_from_amounts(
+    amount_0: u64,
+    amount_1: u64,
+    slippage_tolerance_bps: u16,
+    pool_state: &PoolState,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    transfer_fee_0: Option<TransferFee>,
+    transfer_fee_1: Option<TransferFee>,
+) -> Result<IncreaseLiquidityQuote> {
+    let amount_0 = apply_transfer_fee(amount_0, transfer_fee_0.unwrap_or_default())?;
+    let amount_1 = apply_transfer_fee(amount_1, transfer_fee_1.unwrap_or_default())?;
+    let (sqrt_price_lower, sqrt_price_upper) =
+        get_sqrt_price_range(tick_lower_index, tick_upper_index)?;
+
+    let liquidity = get_liquidity_from_amounts(
+        pool_state.sqrt_price_x64,
+        sqrt_price_lower,
+        sqrt_price_upper,
+        amount_0,
+        amount_1,
+    )?;
+
+    increase_liquidity_quote(
+        liquidity,
+        slippage_tolerance_bps,
+        pool_state,
+        tick_lower_index,
+        tick_upper_index,
+        transfer_fee_0,
+        transfer_fee_1,
+    )
+}
+
+/// Calculate the quote for decreasing liquidity
+///
+/// # Parameters
+/// - `liquidity_delta` - The amount of liquidity to decrease
+/// - `slippage_tolerance_bps` - The slippage tolerance in bps
+/// - `pool_state` - The pool state
+/// - `tick_lower_index` - The lower tick index of the position
+/// - `tick_upper_index` - The upper tick index of the position
+/// - `transfer_fee_0` - The transfer fee for token 0
+/// - `transfer_fee_1` - The transfer fee for token 1
+///
+/// # Returns
+/// - A DecreaseLiquidityQuote struct containing the estimated token amounts
+pub fn decrease_liquidity_quote(
+    liquidity_delta: u128,
+    slippage_tolerance_bps: u16,
+    pool_state: &PoolState,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    transfer_fee_0: Option<TransferFee>,
+    transfer_fee_1: Option<TransferFee>,
+) -> Result<DecreaseLiquidityQuote> {
+    if liquidity_delta == 0 {
+        return Ok(DecreaseLiquidityQuote::default());
+    }
+
+    let (tick_lower_index, tick_upper_index) =
+        order_tick_indexes(tick_lower_index, tick_upper_index);
+    let (amount0_before_fee, amount1_before_fee) = get_amounts_from_liquidity(
+        liquidity_delta,
+        pool_state,
+        tick_lower_index,
+        tick_upper_index,
+        false,
+    )?;
+
+    let amount0_est = apply_transfer_fee(amount0_before_fee, transfer_fee_0.unwrap_or_default())?;
+    let amount1_est = apply_transfer_fee(amount1_before_fee, transfer_fee_1.unwrap_or_default())?;
+
+    let amount0_min = get_min_amount_with_slippage_tolerance(amount0_est, slippage_tolerance_bps)?;
+    let amount1_min = get_min_amount_with_slippage_tolerance(amount1_est, slippage_tolerance_bps)?;
+
+    Ok(DecreaseLiquidityQuote {
+        liquidity_delta,
+        amount0_est,
+        amount1_est,
+        amount0_min,
+        amount1_min,
+    })
+}
+
+/// Calculate the quote for decreasing liquidity given a token 0 amount
+///
+/// # Parameters
+/// - `amount_0` - The amount of token 0 to receive, after transfer fees
+/// - `slippage_tolerance_bps` - The slippage tolerance in bps
+/// - `pool_state` - The pool state
+/// - `tick_lower_index` - The lower tick index of the position
+/// - `tick_upper_index` - The upper tick index of the position
+/// - `transfer_fee_0` - The transfer fee for token 0
+/// - `transfer_fee_1` - The transfer fee for token 1
+///
+/// # Returns
+/// - A DecreaseLiquidityQuote struct containing the estimated token amounts
+pub fn decrease_liquidity_quote_0(
+    amount_0: u64,
+    slippage_tolerance_bps: u16,
+    pool_state: &PoolState,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    transfer_fee_0: Option<TransferFee>,
+    transfer_fee_1: Option<TransferFee>,
+) -> Result<DecreaseLiquidityQuote> {
+    let amount_0 = reverse_apply_transfer_fee(amount_0, transfer_fee_0.unwrap_or_default())?;
+    let (sqrt_price_lower, sqrt_price_upper) =
+        get_sqrt_price_range(tick_lower_index, tick_upper_index)?;
+
+    let liquidity = get_liquidity_from_single_amount_0(
+        pool_state.sqrt_price_x64,
+        sqrt_price_lower,
+        sqrt_price_upper,
+        amount_0,
+    )?;
+
+    decrease_liquidity_quote(
+        liquidity,
+        slippage_tolerance_bps,
+        pool_state,
+        tick_lower_index,
+        tick_upper_index,
+        transfer_fee_0,
+        transfer_fee_1,
+    )
+}
+
+/// Calculate the quote for decreasing liquidity given a token 1 amount
+///
+/// # Parameters
+/// - `amount_1` - The amount of token 1 to receive, after transfer fees
+/// - `slippage_tolerance_bps` - The slippage tolerance in bps
+/// - `pool_state` - The pool state
+/// - `tick_lower_index` - The lower tick index of the position
+/// - `tick_upper_index` - The upper tick index of the position
+/// - `transfer_fee_0` - The transfer fee for token 0
+/// - `transfer_fee_1` - The transfer fee for token 1
+///
+/// # Returns
+/// - A DecreaseLiquidityQuote struct containing the estimated token amounts
+pub fn decrease_liquidity_quote_1(
+    amount_1: u64,
+    slippage_tolerance_bps: u16,
+    pool_state: &PoolState,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    transfer_fee_0: Option<TransferFee>,
+    transfer_fee_1: Option<TransferFee>,
+) -> Result<DecreaseLiquidityQuote> {
+    let amount_1 = reverse_apply_transfer_fee(amount_1, transfer_fee_1.unwrap_or_default())?;
+    let (sqrt_price_lower, sqrt_price_upper) =
+        get_sqrt_price_range(tick_lower_index, tick_upper_index)?;
+
+    let liquidity = get_liquidity_from_single_amount_1(
+        pool_state.sqrt_price_x64,
+        sqrt_price_lower,
+        sqrt_price_upper,
+        amount_1,
+    )?;
+
+    decrease_liquidity_quote(
+        liquidity,
+        slippage_tolerance_bps,
+        pool_state,
+        tick_lower_index,
+        tick_upper_index,
+        transfer_fee_0,
+        transfer_fee_1,
+    )
+}
+
+// Private functions
+
+fn order_tick_indexes(tick_index_1: i32, tick_index_2: i32) -> (i32, i32) {
+    if tick_index_1 < tick_index_2 {
+        (tick_index_1, tick_index_2)
+    } else {
+        (tick_index_2, tick_index_1)
+    }
+}
+
+fn get_sqrt_price_range(tick_index_1: i32, tick_index_2: i32) -> Result<(u128, u128)> {
+    let (tick_lower_index, tick_upper_index) = order_tick_indexes(tick_index_1, tick_index_2);
+    Ok((get_sqrt_price_at_tick(tick_lower_index)?, get_sqrt_price_at_tick(tick_upper_index)?))
+}
+
+// Mirrors `get_delta_amounts_signed` used by the program, which compares
+// against the pool's current tick rather than its sqrt price.
+pub(crate) fn get_amounts_from_liquidity(
+    liquidity_delta: u128,
+    pool_state: &PoolState,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    round_up: bool,
+) -> Result<(u64, u64)> {
+    let (sqrt_price_lower, sqrt_price_upper) =
+        get_sqrt_price_range(tick_lower_index, tick_upper_index)?;
+
+    if pool_state.tick_current < tick_lower_index {
+        let amount_0 = get_delta_amount_0_unsigned(
+            sqrt_price_lower,
+            sqrt_price_upper,
+            liquidity_delta,
+            round_up,
+        )?;
+        Ok((amount_0, 0))
+    } else if pool_state.tick_current < tick_upper_index {
+        let amount_0 = get_delta_amount_0_unsigned(
+            pool_state.sqrt_price_x64,
+            sqrt_price_upper,
+            liquidity_delta,
+            round_up,
+        )?;
+        let amount_1 = get_delta_amount_1_unsigned(
+            sqrt_price_lower,
+            pool_state.sqrt_price_x64,
+            liquidity_delta,
+            round_up,
+        )?;
+        Ok((amount_0, amount_1))
+    } else {
+        let amount_1 = get_delta_amount_1_unsigned(
+            sqrt_price_lower,
+            sqrt_price_upper,
+            liquidity_delta,
+            round_up,
+        )?;
+        Ok((0, amount_1))
+    }
+}
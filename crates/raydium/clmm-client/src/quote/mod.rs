@@ -0,0 +1,9 @@
+mod fees;
+mod liquidity;
+mod rewards;
+mod swap;
+
+pub use fees::*;
+pub use liquidity::*;
+pub use rewards::*;
+pub use swap::*;
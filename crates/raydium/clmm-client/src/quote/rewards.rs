@@ -0,0 +1,267 @@
+use raydium_clmm::{
+    constants::REWARD_NUM,
+    generated::{
+        accounts::{OperationState, PersonalPositionState, PoolState},
+        types::TickState,
+    },
+    libraries::{big_num::U256, fixed_point_64},
+    math::full_math::MulDiv,
+    state::get_reward_growths_inside,
+};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    constants::{ADMIN_KEY, INCREASE_EMISSIONS_PERIOD, MAX_REWARD_PERIOD, MIN_REWARD_PERIOD},
+    error::{ClmmClientError, Result},
+    math::{
+        reward::{calculate_reward_amount, calculate_reward_amount_floor},
+        token::apply_transfer_fee,
+    },
+    types::{
+        CollectRewardQuote, CollectRewardsQuote, RemainingRewardQuote, RemainingRewardsQuote,
+        RewardScheduleQuote, TransferFee,
+    },
+};
+
+/// Calculate the rewards owed to a personal position
+///
+/// The pool reward emissions are brought up to `current_timestamp` the same
+/// way the program does before collecting, so the quote includes the rewards
+/// emitted since the last pool update.
+///
+/// # Parameters
+/// - `pool_state` - The pool state
+/// - `position` - The personal position state
+/// - `tick_lower` - The tick state at the lower tick index of the position
+/// - `tick_upper` - The tick state at the upper tick index of the position
+/// - `current_timestamp` - The current unix timestamp
+/// - `transfer_fee_0` - The transfer fee for reward token 0
+/// - `transfer_fee_1` - The transfer fee for reward token 1
+/// - `transfer_fee_2` - The transfer fee for reward token 2
+///
+/// # Returns
+/// - A CollectRewardsQuote struct containing the rewards received for the 3
+///   reward tokens
+#[allow(clippy::too_many_arguments)]
+pub fn collect_rewards_quote(
+    pool_state: &PoolState,
+    position: &PersonalPositionState,
+    tick_lower: &TickState,
+    tick_upper: &TickState,
+    current_timestamp: u64,
+    transfer_fee_0: Option<TransferFee>,
+    transfer_fee_1: Option<TransferFee>,
+    transfer_fee_2: Option<TransferFee>,
+) -> Result<CollectRewardsQuote> {
+    let reward_infos = pool_state.get_updated_reward_infos(current_timestamp)?;
+    let reward_growths_inside =
+        get_reward_growths_inside(tick_lower, tick_upper, pool_state.tick_current, &reward_infos);
+    let transfer_fees = [transfer_fee_0, transfer_fee_1, transfer_fee_2];
+    let mut rewards = [CollectRewardQuote::default(); REWARD_NUM];
+
+    for i in 0..REWARD_NUM {
+        let reward_growth_delta =
+            reward_growths_inside[i].wrapping_sub(position.reward_infos[i].growth_inside_last_x64);
+        let reward_owed_delta = U256::from(reward_growth_delta)
+            .mul_div_floor(U256::from(position.liquidity), U256::from(fixed_point_64::Q64))
+            .ok_or(ClmmClientError::ArithmeticOverflow)?
+            .to_underflow_u64();
+
+        let withdrawable_reward = position.reward_infos[i]
+            .reward_amount_owed
+            .checked_add(reward_owed_delta)
+            .ok_or(ClmmClientError::ArithmeticOverflow)?;
+        let rewards_owed =
+            apply_transfer_fee(withdrawable_reward, transfer_fees[i].unwrap_or_default())?;
+        rewards[i] = CollectRewardQuote { rewards_owed };
+    }
+
+    Ok(CollectRewardsQuote { rewards })
+}
+
+/// Calculate the reward slot and the funding amount of a new reward, applying
+/// the checks the program does when initializing it. Only the program admin
+/// and the operation owners can initialize rewards.
+///
+/// # Parameters
+/// - `pool_state` - The pool state
+/// - `operation_state` - The operation state of the program
+/// - `reward_mint` - The mint of the reward token
+/// - `reward_funder` - The account funding the reward
+/// - `open_time` - The unix timestamp the emissions start at
+/// - `end_time` - The unix timestamp the emissions end at
+/// - `emissions_per_second_x64` - The emission rate, as Q64.64
+/// - `current_timestamp` - The current unix timestamp
+///
+/// # Returns
+/// - A RewardScheduleQuote struct containing the reward slot to initialize and
+///   the amount of reward tokens to fund it with
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_reward_quote(
+    pool_state: &PoolState,
+    operation_state: &OperationState,
+    reward_mint: Pubkey,
+    reward_funder: Pubkey,
+    open_time: u64,
+    end_time: u64,
+    emissions_per_second_x64: u128,
+    current_timestamp: u64,
+) -> Result<RewardScheduleQuote> {
+    if open_time >= end_time || end_time <= current_timestamp || emissions_per_second_x64 == 0 {
+        return Err(ClmmClientError::InvalidRewardSchedule);
+    }
+    validate_reward_period(end_time - open_time)?;
+    if reward_funder != ADMIN_KEY && !operation_state.operation_owners.contains(&reward_funder) {
+        return Err(ClmmClientError::RewardAuthorityNotApproved);
+    }
+
+    let reward_index = pool_state
+        .reward_infos
+        .iter()
+        .position(|reward_info| !reward_info.initialized())
+        .ok_or(ClmmClientError::RewardSlotsFull)?;
+    let reward_mints: Vec<Pubkey> =
+        pool_state.reward_infos.iter().map(|reward_info| reward_info.token_mint).collect();
+    if reward_mints.contains(&reward_mint) {
+        return Err(ClmmClientError::RewardMintNotAllowed);
+    }
+
+    // One of the first two rewards must be a pool token or a whitelisted mint
+    if reward_index == REWARD_NUM - 2
+        && !reward_mints.contains(&pool_state.token_mint0)
+        && !reward_mints.contains(&pool_state.token_mint1)
+        && reward_mint != pool_state.token_mint0
+        && reward_mint != pool_state.token_mint1
+        && !operation_state.whitelist_mints.contains(&reward_mint)
+    {
+        return Err(ClmmClientError::RewardMintNotAllowed);
+    }
+
+    Ok(RewardScheduleQuote {
+        reward_index: reward_index as u8,
+        reward_amount: calculate_reward_amount(emissions_per_second_x64, end_time - open_time)?,
+    })
+}
+
+/// Calculate the additional funding needed to change the schedule of a reward,
+/// applying the checks the program does for the reward authority. An ended
+/// reward is restarted with the new schedule, an ongoing one is extended to
+/// `end_time` and only keeps its own open time, though the program still
+/// requires `open_time` to be in the future. Operators of the program are not
+/// bound by these checks.
+///
+/// # Parameters
+/// - `pool_state` - The pool state
+/// - `reward_index` - The index of the reward to update
+/// - `emissions_per_second_x64` - The new emission rate, as Q64.64
+/// - `open_time` - The unix timestamp the emissions restart at
+/// - `end_time` - The new unix timestamp the emissions end at
+/// - `current_timestamp` - The current unix timestamp
+///
+/// # Returns
+/// - A RewardScheduleQuote struct containing the amount of reward tokens the
+///   authority has to add
+pub fn set_reward_params_quote(
+    pool_state: &PoolState,
+    reward_index: u8,
+    emissions_per_second_x64: u128,
+    open_time: u64,
+    end_time: u64,
+    current_timestamp: u64,
+) -> Result<RewardScheduleQuote> {
+    if reward_index as usize >= REWARD_NUM {
+        return Err(ClmmClientError::InvalidRewardIndex);
+    }
+    let reward_infos = pool_state.get_updated_reward_infos(current_timestamp)?;
+    let reward_info = &reward_infos[reward_index as usize];
+    if !reward_info.initialized() {
+        return Err(ClmmClientError::InvalidRewardIndex);
+    }
+
+    if open_time <= current_timestamp {
+        return Err(ClmmClientError::InvalidRewardSchedule);
+    }
+
+    let reward_amount = if reward_info.last_update_time == reward_info.end_time {
+        if emissions_per_second_x64 == 0 {
+            return Err(ClmmClientError::InvalidRewardSchedule);
+        }
+        let period =
+            end_time.checked_sub(open_time).ok_or(ClmmClientError::InvalidRewardSchedule)?;
+        validate_reward_period(period)?;
+        calculate_reward_amount(emissions_per_second_x64, period)?
+    } else {
+        let time_left = reward_info
+            .end_time
+            .checked_sub(current_timestamp)
+            .ok_or(ClmmClientError::InvalidRewardSchedule)?;
+        let extension = end_time
+            .checked_sub(reward_info.end_time)
+            .ok_or(ClmmClientError::InvalidRewardSchedule)?;
+        validate_reward_period(extension)?;
+        if emissions_per_second_x64 < reward_info.emissions_per_second_x64
+            || (emissions_per_second_x64 > reward_info.emissions_per_second_x64
+                && time_left > INCREASE_EMISSIONS_PERIOD)
+        {
+            return Err(ClmmClientError::InvalidRewardSchedule);
+        }
+        let increase_amount = calculate_reward_amount_floor(
+            emissions_per_second_x64 - reward_info.emissions_per_second_x64,
+            time_left,
+        )?;
+        calculate_reward_amount_floor(emissions_per_second_x64, extension)?
+            .checked_add(increase_amount)
+            .ok_or(ClmmClientError::ArithmeticOverflow)?
+    };
+
+    Ok(RewardScheduleQuote { reward_index, reward_amount })
+}
+
+/// Calculate the emitted, claimed and not yet emitted amounts of the pool
+/// rewards
+///
+/// # Parameters
+/// - `pool_state` - The pool state
+/// - `current_timestamp` - The current unix timestamp
+///
+/// # Returns
+/// - A RemainingRewardsQuote struct containing the amounts for the 3 reward
+///   tokens
+pub fn remaining_rewards_quote(
+    pool_state: &PoolState,
+    current_timestamp: u64,
+) -> Result<RemainingRewardsQuote> {
+    let reward_infos = pool_state.get_updated_reward_infos(current_timestamp)?;
+    let mut rewards = [RemainingRewardQuote::default(); REWARD_NUM];
+
+    for (reward, reward_info) in rewards.iter_mut().zip(reward_infos.iter()) {
+        if !reward_info.initialized() {
+            continue;
+        }
+        let emission_start = reward_info.last_update_time.max(reward_info.open_time);
+        *reward = RemainingRewardQuote {
+            reward_mint: reward_info.token_mint,
+            emitted: reward_info.reward_total_emissioned,
+            claimed: reward_info.reward_claimed,
+            unemitted: U256::from(reward_info.end_time.saturating_sub(emission_start))
+                .mul_div_floor(
+                    U256::from(reward_info.emissions_per_second_x64),
+                    U256::from(fixed_point_64::Q64),
+                )
+                .filter(|unemitted| *unemitted <= U256::from(u64::MAX))
+                .ok_or(ClmmClientError::ArithmeticOverflow)?
+                .as_u64(),
+        };
+    }
+
+    Ok(RemainingRewardsQuote { rewards })
+}
+
+// Private functions
+
+fn validate_reward_period(period: u64) -> Result<()> {
+    if !(MIN_REWARD_PERIOD..=MAX_REWARD_PERIOD).contains(&period) {
+        return Err(ClmmClientError::InvalidRewardSchedule);
+    }
+    Ok(())
+}
@@ -0,0 +1,205 @@
+use std::collections::VecDeque;
+
+use raydium_clmm::{
+    generated::accounts::{AmmConfig, PoolState, TickArrayBitmapExtension, TickArrayState},
+    math::{
+        swap_v2::{compute_swap, compute_swap_by_specified_sqrt_price, SwapError, SwapState},
+        tick::{MAX_SQRT_PRICE_X64, MIN_SQRT_PRICE_X64},
+    },
+    state::PoolStateError,
+};
+use solana_client_core::math::sqrt_price_after_impact;
+
+use crate::{
+    error::{ClmmClientError, Result},
+    types::SwapPriceQuote,
+};
+
+/// Calculate the swap moving the pool price to a target price. The input
+/// token is token 0 if the target price is below the pool price, token 1
+/// otherwise.
+///
+/// # Parameters
+/// - `target_sqrt_price_x64` - The sqrt price to move the pool price to
+/// - `amm_config` - The AMM config of the pool
+/// - `pool_state` - The pool state
+/// - `tick_array_bitmap` - The tick array bitmap extension of the pool
+/// - `tick_arrays` - The tick arrays in swap direction, starting from the
+///   current one
+///
+/// # Returns
+/// - A SwapPriceQuote struct containing the amounts to swap to reach the price
+pub fn swap_to_price_quote(
+    target_sqrt_price_x64: u128,
+    amm_config: &AmmConfig,
+    pool_state: &PoolState,
+    tick_array_bitmap: &TickArrayBitmapExtension,
+    tick_arrays: &VecDeque<TickArrayState>,
+) -> Result<SwapPriceQuote> {
+    let zero_for_one = target_sqrt_price_x64 < pool_state.sqrt_price_x64;
+    let (is_pool_current_tick_array, start_tick_index) =
+        get_first_tick_array(pool_state, tick_array_bitmap, zero_for_one, tick_arrays)?;
+
+    let (swap_state, _) = compute_swap_by_specified_sqrt_price(
+        is_pool_current_tick_array,
+        amm_config.trade_fee_rate,
+        start_tick_index,
+        target_sqrt_price_x64,
+        pool_state,
+        tick_array_bitmap,
+        &mut tick_arrays.clone(),
+    )
+    .map_err(map_swap_error)?;
+
+    Ok(get_swap_price_quote(zero_for_one, pool_state, swap_state))
+}
+
+/// Calculate the largest swap keeping the price impact within a number of
+/// basis points.
+///
+/// # Parameters
+/// - `price_impact_bps` - The maximum price movement in bps
+/// - `zero_for_one` - If true, token 0 is swapped in and the price moves down.
+///   Otherwise, token 1 is swapped in and the price moves up.
+/// - `amm_config` - The AMM config of the pool
+/// - `pool_state` - The pool state
+/// - `tick_array_bitmap` - The tick array bitmap extension of the pool
+/// - `tick_arrays` - The tick arrays in swap direction, starting from the
+///   current one
+///
+/// # Returns
+/// - A SwapPriceQuote struct containing the maximum input amount (trade fee
+///   included) and the matching output amount
+pub fn max_swap_amount_quote(
+    price_impact_bps: u16,
+    zero_for_one: bool,
+    amm_config: &AmmConfig,
+    pool_state: &PoolState,
+    tick_array_bitmap: &TickArrayBitmapExtension,
+    tick_arrays: &VecDeque<TickArrayState>,
+) -> Result<SwapPriceQuote> {
+    let target_sqrt_price_x64 =
+        get_sqrt_price_after_impact(pool_state.sqrt_price_x64, price_impact_bps, zero_for_one)?;
+    if target_sqrt_price_x64 == pool_state.sqrt_price_x64 {
+        return Ok(get_swap_price_quote(
+            zero_for_one,
+            pool_state,
+            SwapState {
+                sqrt_price_x64: pool_state.sqrt_price_x64,
+                tick: pool_state.tick_current,
+                liquidity: pool_state.liquidity,
+                ..Default::default()
+            },
+        ));
+    }
+
+    swap_to_price_quote(
+        target_sqrt_price_x64,
+        amm_config,
+        pool_state,
+        tick_array_bitmap,
+        tick_arrays,
+    )
+}
+
+/// Calculate the pool price resulting from a swap.
+///
+/// # Parameters
+/// - `amount` - The input amount if `is_base_input`, the output amount
+///   otherwise
+/// - `is_base_input` - If true, `amount` is the input amount
+/// - `zero_for_one` - If true, token 0 is swapped for token 1
+/// - `amm_config` - The AMM config of the pool
+/// - `pool_state` - The pool state
+/// - `tick_array_bitmap` - The tick array bitmap extension of the pool
+/// - `tick_arrays` - The tick arrays in swap direction, starting from the
+///   current one
+///
+/// # Returns
+/// - A SwapPriceQuote struct containing the price after the swap
+pub fn swap_price_quote(
+    amount: u64,
+    is_base_input: bool,
+    zero_for_one: bool,
+    amm_config: &AmmConfig,
+    pool_state: &PoolState,
+    tick_array_bitmap: &TickArrayBitmapExtension,
+    tick_arrays: &VecDeque<TickArrayState>,
+) -> Result<SwapPriceQuote> {
+    let (is_pool_current_tick_array, start_tick_index) =
+        get_first_tick_array(pool_state, tick_array_bitmap, zero_for_one, tick_arrays)?;
+
+    let (swap_state, _) = compute_swap(
+        zero_for_one,
+        is_base_input,
+        is_pool_current_tick_array,
+        amm_config.trade_fee_rate,
+        amount,
+        start_tick_index,
+        0,
+        pool_state,
+        tick_array_bitmap,
+        &mut tick_arrays.clone(),
+    )
+    .map_err(map_swap_error)?;
+
+    Ok(get_swap_price_quote(zero_for_one, pool_state, swap_state))
+}
+
+/// Maps the swap engine errors raised when the loaded tick arrays run out, or
+/// when no initialized tick array is left in the swap direction, to the
+/// matching client errors.
+pub(crate) fn map_swap_error(err: SwapError) -> ClmmClientError {
+    match err {
+        SwapError::NoTickArrayAvailable
+        | SwapError::NoMoreTickArraysAvailable
+        | SwapError::TickArrayStartTickIndexDoesNotMatch => ClmmClientError::NoTickArrayAvailable,
+        SwapError::TickArrayStartTickIndexOutOfRangeLimit => {
+            PoolStateError::InsufficientLiquidityForDirection.into()
+        }
+        err => err.into(),
+    }
+}
+
+// Private functions
+
+fn get_first_tick_array(
+    pool_state: &PoolState,
+    tick_array_bitmap: &TickArrayBitmapExtension,
+    zero_for_one: bool,
+    tick_arrays: &VecDeque<TickArrayState>,
+) -> Result<(bool, i32)> {
+    if tick_arrays.is_empty() {
+        return Err(ClmmClientError::NoTickArrayAvailable);
+    }
+    Ok(pool_state
+        .get_first_initialized_tick_array(&Some(tick_array_bitmap.clone()), zero_for_one)?)
+}
+
+fn get_swap_price_quote(
+    zero_for_one: bool,
+    pool_state: &PoolState,
+    swap_state: SwapState,
+) -> SwapPriceQuote {
+    SwapPriceQuote {
+        zero_for_one,
+        amount_in: swap_state.amount_in,
+        amount_out: swap_state.amount_out,
+        trade_fee: swap_state.fee_amount,
+        sqrt_price_x64_before: pool_state.sqrt_price_x64,
+        sqrt_price_x64_after: swap_state.sqrt_price_x64,
+        tick_after: swap_state.tick,
+    }
+}
+
+fn get_sqrt_price_after_impact(
+    sqrt_price_x64: u128,
+    price_impact_bps: u16,
+    zero_for_one: bool,
+) -> Result<u128> {
+    let target_sqrt_price_x64 =
+        sqrt_price_after_impact(sqrt_price_x64, price_impact_bps, zero_for_one, false)
+            .ok_or(ClmmClientError::InvalidParameter)?;
+
+    Ok(target_sqrt_price_x64.clamp(MIN_SQRT_PRICE_X64 + 1, MAX_SQRT_PRICE_X64 - 1))
+}
@@ -1,6 +1,10 @@
 use std::collections::VecDeque;
 
-use raydium_clmm::generated::accounts::{PoolState, TickArrayBitmapExtension, TickArrayState};
+use raydium_clmm::{
+    constants::REWARD_NUM,
+    generated::accounts::{AmmConfig, PoolState, TickArrayBitmapExtension, TickArrayState},
+};
+use rust_decimal::Decimal;
 use solana_sdk::pubkey::Pubkey;
 
 #[derive(Clone, Debug, Default)]
@@ -29,6 +33,7 @@ pub struct CreatePoolParams {
 pub struct OpenPositionV2Params {
     pub tick_lower_index: i32,
     pub tick_upper_index: i32,
+    pub tick_spacing: u16,
     pub liquidity: u128,
     pub amount0_max: u64,
     pub amount1_max: u64,
@@ -103,14 +108,325 @@ pub struct DecreaseLiquidityV2Params {
     pub tick_array_upper: Pubkey,
 }
 
+/// The accounts of a position the v2 liquidity builders cannot derive from
+/// their params.
+#[derive(Clone, Debug)]
+pub struct PositionAccounts {
+    /// The token program of the position NFT, i.e. the owner of its mint.
+    pub nft_token_program: Pubkey,
+    /// Where the rewards of the position are paid out when decreasing
+    /// liquidity, one for each initialized reward of the pool, in reward
+    /// order. The program refuses the decrease when one is missing.
+    pub reward_recipients: Vec<RewardRecipient>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RewardRecipient {
+    pub reward_mint: Pubkey,
+    pub recipient_token_account: Pubkey,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ClosePositionParams {
+    pub nft_owner: Pubkey,
+    pub position_nft_mint: Pubkey,
+    pub nft_token_program: Pubkey,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SwapRouterBaseInParams {
+    pub amount_in: u64,
+    pub amount_out_minimum: u64,
+    pub payer: Pubkey,
+    pub input_token_account: Pubkey,
+    pub input_mint: Pubkey,
+    pub input_token_program: Pubkey,
+    pub hops: Vec<SwapRouterHop>,
+}
+
+/// A single pool of a `swap_router_base_in` route. The input of a hop is the
+/// output of the previous one.
+#[derive(Clone, Debug, Default)]
+pub struct SwapRouterHop {
+    pub amm_config: Pubkey,
+    pub pool_state: Pubkey,
+    pub output_mint: Pubkey,
+    pub output_token_account: Pubkey,
+    pub tick_array_accounts: Vec<Pubkey>,
+}
+
+/// Collects what is left of an ended reward. The program only pays it back to
+/// the pool owner, who has to sign as `reward_funder`.
+#[derive(Clone, Debug, Default)]
+pub struct CollectRemainingRewardsParams {
+    pub reward_index: u8,
+    pub reward_funder: Pubkey,
+    pub funder_token_account: Pubkey,
+    pub pool_state: Pubkey,
+    pub reward_mint: Pubkey,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct InitializeRewardParams {
+    pub open_time: u64,
+    pub end_time: u64,
+    pub emissions_per_second_x64: u128,
+    pub reward_funder: Pubkey,
+    pub funder_token_account: Pubkey,
+    pub amm_config: Pubkey,
+    pub pool_state: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_token_program: Pubkey,
+}
+
+/// `authority_token_account` and `reward_mint` are only needed when the new
+/// parameters require the authority to fund additional rewards.
+#[derive(Clone, Debug, Default)]
+pub struct SetRewardParamsParams {
+    pub reward_index: u8,
+    pub emissions_per_second_x64: u128,
+    pub open_time: u64,
+    pub end_time: u64,
+    pub authority: Pubkey,
+    pub amm_config: Pubkey,
+    pub pool_state: Pubkey,
+    pub authority_token_account: Option<Pubkey>,
+    pub reward_mint: Option<Pubkey>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TransferRewardOwnerParams {
+    pub authority: Pubkey,
+    pub pool_state: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+/// Funds a reward of the pool. A new reward slot is initialized unless
+/// `reward_mint` is already rewarded, in which case that reward is extended or
+/// restarted with the new schedule.
+#[derive(Clone, Debug, Default)]
+pub struct FundRewardParams {
+    pub open_time: u64,
+    pub end_time: u64,
+    pub emissions_per_second_x64: u128,
+    pub reward_funder: Pubkey,
+    pub funder_token_account: Pubkey,
+    pub amm_config: Pubkey,
+    pub pool_state: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_token_program: Pubkey,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct UpdateRewardInfosParams {
+    pub pool_state: Pubkey,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CollectProtocolFeeParams {
+    pub amount0_requested: u64,
+    pub amount1_requested: u64,
+    pub owner: Pubkey,
+    pub amm_config: Pubkey,
+    pub pool_state: Pubkey,
+    pub mint0: Pubkey,
+    pub mint1: Pubkey,
+    pub recipient_token_account0: Pubkey,
+    pub recipient_token_account1: Pubkey,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CollectFundFeeParams {
+    pub amount0_requested: u64,
+    pub amount1_requested: u64,
+    pub owner: Pubkey,
+    pub amm_config: Pubkey,
+    pub pool_state: Pubkey,
+    pub mint0: Pubkey,
+    pub mint1: Pubkey,
+    pub recipient_token_account0: Pubkey,
+    pub recipient_token_account1: Pubkey,
+}
+
+/// `new_owner` is required when `param` updates the config owner (3) or the
+/// fund owner (4).
+#[derive(Clone, Debug, Default)]
+pub struct UpdateAmmConfigParams {
+    pub param: u8,
+    pub value: u32,
+    pub owner: Pubkey,
+    pub config_index: u16,
+    pub new_owner: Option<Pubkey>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct UpdatePoolStatusParams {
+    pub status: u8,
+    pub authority: Pubkey,
+    pub pool_state: Pubkey,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CreateOperationAccountParams {
+    pub owner: Pubkey,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct UpdateOperationAccountParams {
+    pub param: u8,
+    pub keys: Vec<Pubkey>,
+    pub owner: Pubkey,
+}
+
 #[derive(Clone, Debug)]
 pub struct PreviewSwapV2Params {
     pub amount: u64,
     pub sqrt_price_limit_x64: u128,
     pub is_base_input: bool,
     pub zero_for_one: bool,
-    pub trade_fee_rate: u32,
+    pub program_id: Pubkey,
+    pub pool_id: Pubkey,
+    pub amm_config: AmmConfig,
     pub pool_state: PoolState,
     pub tick_array_bitmap: TickArrayBitmapExtension,
     pub tick_array_accounts: VecDeque<TickArrayState>,
 }
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SwapPriceQuote {
+    pub zero_for_one: bool,
+    /// The amount swapped in, the trade fee included.
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub trade_fee: u64,
+    pub sqrt_price_x64_before: u128,
+    pub sqrt_price_x64_after: u128,
+    pub tick_after: i32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SwapV2Preview {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub remaining_amount: u64,
+    /// The trade fee paid in the input token, included in `amount_in`.
+    pub trade_fee: u64,
+    /// The part of the trade fee accrued to the protocol.
+    pub protocol_fee: u64,
+    /// The part of the trade fee accrued to the fund.
+    pub fund_fee: u64,
+    /// The part of the trade fee accrued to the liquidity providers.
+    pub lp_fee: u64,
+    pub sqrt_price_x64_before: u128,
+    pub sqrt_price_x64_after: u128,
+    pub tick_after: i32,
+    pub liquidity_after: u128,
+    /// The relative change of the pool price caused by the swap.
+    pub price_impact: Decimal,
+    pub crossed_ticks: Vec<i32>,
+    /// The tick arrays traversed by the swap, in the order the program expects
+    /// them as remaining accounts.
+    pub tick_array_accounts: Vec<Pubkey>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct TransferFee {
+    pub fee_bps: u16,
+    pub max_fee: u64,
+}
+
+impl TransferFee {
+    pub fn new(fee_bps: u16) -> Self { Self { fee_bps, max_fee: u64::MAX } }
+
+    pub fn new_with_max(fee_bps: u16, max_fee: u64) -> Self { Self { fee_bps, max_fee } }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct IncreaseLiquidityQuote {
+    pub liquidity_delta: u128,
+    pub amount0_est: u64,
+    pub amount1_est: u64,
+    pub amount0_max: u64,
+    pub amount1_max: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct DecreaseLiquidityQuote {
+    pub liquidity_delta: u128,
+    pub amount0_est: u64,
+    pub amount1_est: u64,
+    pub amount0_min: u64,
+    pub amount1_min: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct CollectFeesQuote {
+    pub fee_owed_0: u64,
+    pub fee_owed_1: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct CollectRewardsQuote {
+    pub rewards: [CollectRewardQuote; REWARD_NUM],
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct CollectRewardQuote {
+    pub rewards_owed: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct PoolFeeReport {
+    pub elapsed_seconds: u64,
+    pub volume_in_0: u128,
+    pub volume_in_1: u128,
+    pub volume_out_0: u128,
+    pub volume_out_1: u128,
+    /// The fees accrued to the liquidity providers.
+    pub lp_fee_0: u64,
+    pub lp_fee_1: u64,
+    pub protocol_fee_0: u64,
+    pub protocol_fee_1: u64,
+    pub fund_fee_0: u64,
+    pub fund_fee_1: u64,
+    /// The fees earned per unit of in-range liquidity, as Q64.64.
+    pub fee_growth_0_x64: u128,
+    pub fee_growth_1_x64: u128,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct RewardScheduleQuote {
+    pub reward_index: u8,
+    /// The amount of reward tokens the program pulls from the funder, before
+    /// transfer fees.
+    pub reward_amount: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct RemainingRewardsQuote {
+    pub rewards: [RemainingRewardQuote; REWARD_NUM],
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct RemainingRewardQuote {
+    pub reward_mint: Pubkey,
+    pub emitted: u64,
+    pub claimed: u64,
+    /// The rewards left to emit until the end of the reward.
+    pub unemitted: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AmmConfigInfo {
+    pub address: Pubkey,
+    pub amm_config: AmmConfig,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolInfo {
+    pub address: Pubkey,
+    pub amm_config: Pubkey,
+    pub tick_spacing: u16,
+    pub trade_fee_rate: u32,
+    pub pool_state: PoolState,
+}
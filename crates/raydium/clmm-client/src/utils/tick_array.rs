@@ -4,37 +4,57 @@ use raydium_clmm::{
 };
 use solana_program::pubkey::Pubkey;
 
-pub fn load_cur_and_next_five_tick_array_pubkey(
+use crate::error::Result;
+
+/// Load the pubkeys of the tick array holding the current price and of up to
+/// `max_next_tick_arrays` initialized tick arrays after it in the swap
+/// direction. Fewer arrays are returned when the pool runs out of initialized
+/// tick arrays; a pool without any in the swap direction is an error.
+pub fn load_cur_and_next_tick_array_pubkeys(
     pool_id: Pubkey,
     pool_state: &PoolState,
     tickarray_bitmap_extension: &TickArrayBitmapExtension,
     zero_for_one: bool,
+    max_next_tick_arrays: usize,
     program_id: Option<Pubkey>,
-) -> Vec<Pubkey> {
-    let (_, mut current_vaild_tick_array_start_index) = pool_state
-        .get_first_initialized_tick_array(&Some(tickarray_bitmap_extension.clone()), zero_for_one)
-        .unwrap();
-    let mut tick_array_keys = Vec::new();
+) -> Result<Vec<Pubkey>> {
+    let tickarray_bitmap_extension = Some(tickarray_bitmap_extension.clone());
+    let (_, mut current_vaild_tick_array_start_index) =
+        pool_state.get_first_initialized_tick_array(&tickarray_bitmap_extension, zero_for_one)?;
+    let mut tick_array_keys = Vec::with_capacity(max_next_tick_arrays + 1);
     tick_array_keys.push(
         derive_tick_array_pubkey(pool_id, current_vaild_tick_array_start_index, program_id).0,
     );
-    let mut max_array_size = 5;
-    while max_array_size != 0 {
-        let next_tick_array_index = pool_state
-            .next_initialized_tick_array_start_index(
-                &Some(tickarray_bitmap_extension.clone()),
-                current_vaild_tick_array_start_index,
-                zero_for_one,
-            )
-            .unwrap();
-        if next_tick_array_index.is_none() {
+    for _ in 0..max_next_tick_arrays {
+        let Some(next_tick_array_index) = pool_state.next_initialized_tick_array_start_index(
+            &tickarray_bitmap_extension,
+            current_vaild_tick_array_start_index,
+            zero_for_one,
+        )?
+        else {
             break;
-        }
-        current_vaild_tick_array_start_index = next_tick_array_index.unwrap();
+        };
+        current_vaild_tick_array_start_index = next_tick_array_index;
         tick_array_keys.push(
             derive_tick_array_pubkey(pool_id, current_vaild_tick_array_start_index, program_id).0,
         );
-        max_array_size -= 1;
     }
-    tick_array_keys
+    Ok(tick_array_keys)
+}
+
+pub fn load_cur_and_next_five_tick_array_pubkey(
+    pool_id: Pubkey,
+    pool_state: &PoolState,
+    tickarray_bitmap_extension: &TickArrayBitmapExtension,
+    zero_for_one: bool,
+    program_id: Option<Pubkey>,
+) -> Result<Vec<Pubkey>> {
+    load_cur_and_next_tick_array_pubkeys(
+        pool_id,
+        pool_state,
+        tickarray_bitmap_extension,
+        zero_for_one,
+        5,
+        program_id,
+    )
 }
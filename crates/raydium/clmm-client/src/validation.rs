@@ -0,0 +1,90 @@
+use raydium_clmm::{generated::accounts::PoolState, state::PoolStatusBitIndex};
+use solana_program::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use spl_token_2022::{
+    extension::{
+        transfer_hook::TransferHook, BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
+    state::Mint,
+};
+
+use crate::error::{ClmmClientError, Result};
+
+/// The Token-2022 mint extensions the program accepts for pool tokens.
+const SUPPORTED_MINT_EXTENSIONS: [ExtensionType; 5] = [
+    ExtensionType::TransferFeeConfig,
+    ExtensionType::MetadataPointer,
+    ExtensionType::TokenMetadata,
+    ExtensionType::InterestBearingConfig,
+    ExtensionType::ScaledUiAmount,
+];
+
+/// Check that the operation is enabled in the pool status.
+pub fn validate_pool_operation(
+    pool_state: &PoolState,
+    operation: PoolStatusBitIndex,
+) -> Result<()> {
+    if !pool_state.is_operation_allowed(operation) {
+        return Err(ClmmClientError::PoolOperationDisabled(operation));
+    }
+    Ok(())
+}
+
+/// Check that the pool accepts swaps at `current_timestamp`.
+pub fn validate_swap(pool_state: &PoolState, current_timestamp: u64) -> Result<()> {
+    validate_pool_operation(pool_state, PoolStatusBitIndex::Swap)?;
+    if !pool_state.is_open(current_timestamp) {
+        return Err(ClmmClientError::PoolNotOpen { open_time: pool_state.open_time });
+    }
+    Ok(())
+}
+
+/// Check that positions can be opened or increased in the pool.
+pub fn validate_increase_liquidity(pool_state: &PoolState) -> Result<()> {
+    validate_pool_operation(pool_state, PoolStatusBitIndex::OpenPositionOrIncreaseLiquidity)
+}
+
+/// Check that liquidity can be removed from the pool. The program only refuses
+/// a decrease, whatever the liquidity removed, once removing liquidity and
+/// collecting fees and rewards are all disabled.
+pub fn validate_decrease_liquidity(pool_state: &PoolState) -> Result<()> {
+    validate_pool_operation(pool_state, PoolStatusBitIndex::DecreaseLiquidity)
+        .or_else(|_| validate_collect(pool_state))
+}
+
+/// Check that fees or rewards can be collected from the pool.
+pub fn validate_collect(pool_state: &PoolState) -> Result<()> {
+    validate_pool_operation(pool_state, PoolStatusBitIndex::CollectFee)
+        .or_else(|_| validate_pool_operation(pool_state, PoolStatusBitIndex::CollectReward))
+}
+
+/// Check that a mint can be used as a pool token. Classic SPL mints and
+/// Token-2022 mints with transfer fees, metadata or interest are accepted, as
+/// are the mints whitelisted in the operation state. Mints with a transfer hook
+/// program are always refused: the program does not forward the extra accounts
+/// of the hook when moving tokens, so every transfer would fail.
+pub fn validate_pool_mint(
+    mint: Pubkey,
+    mint_data: &[u8],
+    whitelist_mints: &[Pubkey],
+) -> Result<()> {
+    if mint_data.len() == Mint::LEN {
+        return Ok(());
+    }
+    let mint_state = StateWithExtensions::<Mint>::unpack(mint_data)
+        .map_err(|_| ClmmClientError::InvalidMintAccount)?;
+    if let Ok(transfer_hook) = mint_state.get_extension::<TransferHook>() {
+        if Option::<Pubkey>::from(transfer_hook.program_id).is_some() {
+            return Err(ClmmClientError::UnsupportedMint(mint));
+        }
+    }
+    if whitelist_mints.contains(&mint) {
+        return Ok(());
+    }
+    let extensions =
+        mint_state.get_extension_types().map_err(|_| ClmmClientError::InvalidMintAccount)?;
+    if !extensions.iter().all(|extension| SUPPORTED_MINT_EXTENSIONS.contains(extension)) {
+        return Err(ClmmClientError::UnsupportedMint(mint));
+    }
+    Ok(())
+}
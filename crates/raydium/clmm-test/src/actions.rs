@@ -1,19 +1,24 @@
-use std::collections::VecDeque;
-
 use litesvm::{types::TransactionMetadata, LiteSVM};
 use program_test_utils::sign_and_send_transaction;
-use raydium_clmm::{math::swap_v2::SwapState, utils::derive};
-use raydium_clmm_client::{preview::preview_swap_v2, types::PreviewSwapV2Params};
+use raydium_clmm::utils::derive;
+use raydium_clmm_client::{
+    preview::preview_swap_v2,
+    types::{PositionAccounts, PreviewSwapV2Params, RewardScheduleQuote, SwapV2Preview},
+};
 use solana_sdk::{
+    clock::Clock,
+    instruction::Instruction,
     pubkey::Pubkey,
     signature::{Keypair, Signer},
 };
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 
 use crate::{
-    error::Result,
+    error::{ClmmTestError, Result},
     operations::RaydiumClmmTest,
     types::{
-        DecreaseLiquidityV2Params, IncreaseLiquidityV2Params, OpenPositionV2Params, SwapV2Params,
+        DecreaseLiquidityV2Params, FundRewardParams, IncreaseLiquidityV2Params,
+        OpenPositionV2Params, SwapRouterBaseInParams, SwapV2Params,
     },
 };
 
@@ -37,6 +42,7 @@ impl RaydiumClmmTest {
                     token_account1: params.user_token_account1,
                     tick_lower_index: params.tick_lower_index,
                     tick_upper_index: params.tick_upper_index,
+                    tick_spacing: self.fee_config.tick_spacing,
                     liquidity: params.liquidity,
                     amount0_max: params.amount0_max,
                     amount1_max: params.amount1_max,
@@ -59,6 +65,7 @@ impl RaydiumClmmTest {
     ) -> Result<TransactionMetadata> {
         let personal_position_account =
             self.get_personal_position_state(svm, params.position_nft_mint)?;
+        let nft_token_program = self.get_nft_token_program(svm, params.position_nft_mint)?;
 
         let protocol_position = derive::derive_protocol_position_pubkey(
             self.pool_state,
@@ -84,7 +91,7 @@ impl RaydiumClmmTest {
         .0;
 
         let instruction =
-            raydium_clmm_client::instructions::prepare_increase_liquidity_v2_instruction(
+            raydium_clmm_client::instructions::prepare_increase_liquidity_v2_instruction_for_position(
                 raydium_clmm_client::types::IncreaseLiquidityV2Params {
                     pool_state: self.pool_state,
                     position_nft_mint: params.position_nft_mint,
@@ -102,6 +109,7 @@ impl RaydiumClmmTest {
                     amount0_max: params.amount0_max,
                     amount1_max: params.amount1_max,
                 },
+                &PositionAccounts { nft_token_program, reward_recipients: Vec::new() },
                 self.program_id,
             )?;
 
@@ -116,6 +124,21 @@ impl RaydiumClmmTest {
         params: SwapV2Params,
         signer: &Keypair,
     ) -> Result<TransactionMetadata> {
+        let instruction = self.prepare_swap_v2(svm, params, signer.pubkey())?;
+
+        let metadata = sign_and_send_transaction!(svm, &[instruction], signer)?;
+
+        Ok(metadata)
+    }
+
+    /// Builds the swap instruction through the current tick array and the
+    /// next five in the swap direction.
+    pub fn prepare_swap_v2(
+        &self,
+        svm: &LiteSVM,
+        params: SwapV2Params,
+        payer: Pubkey,
+    ) -> Result<Instruction> {
         let (
             input_mint,
             output_mint,
@@ -153,7 +176,7 @@ impl RaydiumClmmTest {
                 &tick_array_bitmap_extension,
                 params.zero_for_one,
                 Some(self.program_id),
-            );
+            )?;
 
         let instruction = raydium_clmm_client::instructions::prepare_swap_v2_instruction(
             raydium_clmm_client::types::SwapV2Params {
@@ -171,20 +194,48 @@ impl RaydiumClmmTest {
                 observation_state: self.observation_state,
                 input_mint,
                 output_mint,
-                payer: signer.pubkey(),
+                payer,
                 tick_array_accounts,
             },
             self.program_id,
         )?;
 
-        let metadata = sign_and_send_transaction!(svm, &[instruction], signer)?;
-
-        Ok(metadata)
+        Ok(instruction)
     }
 
-    pub fn preview_swap_v2(&self, svm: &LiteSVM, params: SwapV2Params) -> Result<SwapState> {
+    /// Swaps through the pool with `swap_router_base_in`, as a single hop
+    /// route.
+    pub fn swap_router_base_in(
+        &self,
+        svm: &mut LiteSVM,
+        params: SwapRouterBaseInParams,
+        signer: &Keypair,
+    ) -> Result<TransactionMetadata> {
+        let (
+            input_mint,
+            output_mint,
+            input_token_program,
+            input_token_account,
+            output_token_account,
+        ) = if params.zero_for_one {
+            (
+                self.token_pair.mint0,
+                self.token_pair.mint1,
+                self.token_pair.token_program_id0,
+                params.user_token_account0,
+                params.user_token_account1,
+            )
+        } else {
+            (
+                self.token_pair.mint1,
+                self.token_pair.mint0,
+                self.token_pair.token_program_id1,
+                params.user_token_account1,
+                params.user_token_account0,
+            )
+        };
+
         let pool_state_account = self.get_pool_state(svm)?;
-        let amm_config = self.get_amm_config(svm)?;
         let tick_array_bitmap_extension = self.get_tick_array_bitmap(svm)?;
 
         let tick_array_accounts =
@@ -194,26 +245,54 @@ impl RaydiumClmmTest {
                 &tick_array_bitmap_extension,
                 params.zero_for_one,
                 Some(self.program_id),
-            );
+            )?;
 
-        let mut tick_arrays = VecDeque::new();
-        for tick_array_account in tick_array_accounts {
-            let tick_array = self.get_tick_array(svm, tick_array_account)?;
-            tick_arrays.push_back(tick_array.clone());
-        }
+        let instruction =
+            raydium_clmm_client::instructions::prepare_swap_router_base_in_instruction(
+                raydium_clmm_client::types::SwapRouterBaseInParams {
+                    amount_in: params.amount_in,
+                    amount_out_minimum: params.amount_out_minimum,
+                    payer: signer.pubkey(),
+                    input_token_account,
+                    input_mint,
+                    input_token_program,
+                    hops: vec![raydium_clmm_client::types::SwapRouterHop {
+                        amm_config: self.amm_config,
+                        pool_state: self.pool_state,
+                        output_mint,
+                        output_token_account,
+                        tick_array_accounts,
+                    }],
+                },
+                self.program_id,
+            )?;
+
+        let metadata = sign_and_send_transaction!(svm, &[instruction], signer)?;
+
+        Ok(metadata)
+    }
+
+    pub fn preview_swap_v2(&self, svm: &LiteSVM, params: SwapV2Params) -> Result<SwapV2Preview> {
+        let pool_state_account = self.get_pool_state(svm)?;
+        let amm_config = self.get_amm_config(svm)?;
+        let tick_array_bitmap_extension = self.get_tick_array_bitmap(svm)?;
+
+        let tick_arrays = self.get_swap_tick_arrays(svm, params.zero_for_one)?;
 
-        let swap_state = preview_swap_v2(PreviewSwapV2Params {
+        let swap_preview = preview_swap_v2(PreviewSwapV2Params {
             amount: params.amount,
             sqrt_price_limit_x64: params.sqrt_price_limit_x64,
             is_base_input: params.is_base_input,
             zero_for_one: params.zero_for_one,
-            trade_fee_rate: amm_config.trade_fee_rate,
+            program_id: self.program_id,
+            pool_id: self.pool_state,
+            amm_config,
             pool_state: pool_state_account,
             tick_array_bitmap: tick_array_bitmap_extension,
             tick_array_accounts: tick_arrays,
         })?;
 
-        Ok(swap_state)
+        Ok(swap_preview)
     }
 
     pub fn decrease_liquidity_v2(
@@ -222,8 +301,61 @@ impl RaydiumClmmTest {
         params: DecreaseLiquidityV2Params,
         signer: &Keypair,
     ) -> Result<TransactionMetadata> {
+        let personal_position_account =
+            self.get_personal_position_state(svm, params.position_nft_mint)?;
+        let nft_token_program = self.get_nft_token_program(svm, params.position_nft_mint)?;
+
+        let protocol_position = derive::derive_protocol_position_pubkey(
+            self.pool_state,
+            personal_position_account.tick_lower_index,
+            personal_position_account.tick_upper_index,
+            Some(self.program_id),
+        )
+        .0;
+
+        let tick_array_lower = derive::derive_tick_array_pubkey_by_tick_index(
+            self.pool_state,
+            personal_position_account.tick_lower_index,
+            self.fee_config.tick_spacing,
+            Some(self.program_id),
+        )
+        .0;
+        let tick_array_upper = derive::derive_tick_array_pubkey_by_tick_index(
+            self.pool_state,
+            personal_position_account.tick_upper_index,
+            self.fee_config.tick_spacing,
+            Some(self.program_id),
+        )
+        .0;
+
+        // Rewards in a pool token go to the recipient of that token, the others
+        // to the associated token account of the signer
+        let pool_state_account = self.get_pool_state(svm)?;
+        let mut reward_recipients = Vec::new();
+        for reward_info in
+            pool_state_account.reward_infos.iter().filter(|reward_info| reward_info.initialized())
+        {
+            let recipient_token_account = if reward_info.token_mint == self.token_pair.mint0 {
+                params.recipient_token_account0
+            } else if reward_info.token_mint == self.token_pair.mint1 {
+                params.recipient_token_account1
+            } else {
+                let reward_mint_account =
+                    svm.get_account(&reward_info.token_mint).ok_or(ClmmTestError::MintNotFound)?;
+                get_associated_token_address_with_program_id(
+                    &signer.pubkey(),
+                    &reward_info.token_mint,
+                    &reward_mint_account.owner,
+                )
+            };
+            reward_recipients.push(raydium_clmm_client::types::RewardRecipient {
+                reward_mint: reward_info.token_mint,
+                recipient_token_account,
+            });
+        }
+
         let instruction =
-            raydium_clmm_client::instructions::prepare_decrease_liquidity_v2_instruction(
+            raydium_clmm_client::instructions::prepare_decrease_liquidity_v2_instruction_for_position(
                 raydium_clmm_client::types::DecreaseLiquidityV2Params {
                     pool_state: self.pool_state,
                     position_nft_mint: params.position_nft_mint,
@@ -234,13 +366,170 @@ impl RaydiumClmmTest {
                     nft_owner: signer.pubkey(),
                     mint0: self.token_pair.mint0,
                     mint1: self.token_pair.mint1,
-                    protocol_position: Pubkey::default(),
-                    tick_array_lower: Pubkey::default(),
-                    tick_array_upper: Pubkey::default(),
+                    protocol_position,
+                    tick_array_lower,
+                    tick_array_upper,
                     liquidity: params.liquidity,
                     amount0_min: params.amount0_min,
                     amount1_min: params.amount1_min,
                 },
+                &PositionAccounts { nft_token_program, reward_recipients },
+                self.program_id,
+            )?;
+
+        let metadata = sign_and_send_transaction!(svm, &[instruction], signer)?;
+
+        Ok(metadata)
+    }
+
+    /// Creates the operation account of the program, owned by the program
+    /// admin.
+    pub fn create_operation_account(
+        &self,
+        svm: &mut LiteSVM,
+        payer: &Keypair,
+    ) -> Result<TransactionMetadata> {
+        let (instruction, _) =
+            raydium_clmm_client::instructions::prepare_create_operation_account_instruction(
+                raydium_clmm_client::types::CreateOperationAccountParams {
+                    owner: crate::constants::ADMIN_KEY,
+                },
+                self.program_id,
+            )?;
+
+        let metadata = sign_and_send_transaction!(svm, &[instruction], payer)?;
+
+        Ok(metadata)
+    }
+
+    /// Sets the status bits of the pool, as the program admin. A set bit
+    /// disables the matching operation.
+    pub fn update_pool_status(
+        &self,
+        svm: &mut LiteSVM,
+        status: u8,
+        payer: &Keypair,
+    ) -> Result<TransactionMetadata> {
+        let instruction =
+            raydium_clmm_client::instructions::prepare_update_pool_status_instruction(
+                raydium_clmm_client::types::UpdatePoolStatusParams {
+                    status,
+                    authority: crate::constants::ADMIN_KEY,
+                    pool_state: self.pool_state,
+                },
+                self.program_id,
+            )?;
+
+        let metadata = sign_and_send_transaction!(svm, &[instruction], payer)?;
+
+        Ok(metadata)
+    }
+
+    /// Updates the operation owners or the whitelisted mints of the program,
+    /// `param` selecting the list and whether `keys` are added or removed.
+    pub fn update_operation_account(
+        &self,
+        svm: &mut LiteSVM,
+        param: u8,
+        keys: Vec<Pubkey>,
+        payer: &Keypair,
+    ) -> Result<TransactionMetadata> {
+        let instruction =
+            raydium_clmm_client::instructions::prepare_update_operation_account_instruction(
+                raydium_clmm_client::types::UpdateOperationAccountParams {
+                    param,
+                    keys,
+                    owner: crate::constants::ADMIN_KEY,
+                },
+                self.program_id,
+            )?;
+
+        let metadata = sign_and_send_transaction!(svm, &[instruction], payer)?;
+
+        Ok(metadata)
+    }
+
+    /// Hands the rewards of the pool over to `new_owner`, as the program admin.
+    pub fn transfer_reward_owner(
+        &self,
+        svm: &mut LiteSVM,
+        new_owner: Pubkey,
+        payer: &Keypair,
+    ) -> Result<TransactionMetadata> {
+        let instruction =
+            raydium_clmm_client::instructions::prepare_transfer_reward_owner_instruction(
+                raydium_clmm_client::types::TransferRewardOwnerParams {
+                    authority: crate::constants::ADMIN_KEY,
+                    pool_state: self.pool_state,
+                    new_owner,
+                },
+                self.program_id,
+            )?;
+
+        let metadata = sign_and_send_transaction!(svm, &[instruction], payer)?;
+
+        Ok(metadata)
+    }
+
+    /// Funds a reward of the pool at the current clock time, returning the
+    /// schedule quoted for it.
+    pub fn fund_reward(
+        &self,
+        svm: &mut LiteSVM,
+        params: FundRewardParams,
+        signer: &Keypair,
+    ) -> Result<(TransactionMetadata, RewardScheduleQuote)> {
+        let pool_state_account = self.get_pool_state(svm)?;
+        let operation_state_account = self.get_operation_state(svm)?;
+        let reward_mint_account =
+            svm.get_account(&params.reward_mint).ok_or(ClmmTestError::MintNotFound)?;
+        let current_timestamp = svm.get_sysvar::<Clock>().unix_timestamp as u64;
+
+        let (instruction, quote) =
+            raydium_clmm_client::instructions::prepare_fund_reward_instruction(
+                raydium_clmm_client::types::FundRewardParams {
+                    open_time: params.open_time,
+                    end_time: params.end_time,
+                    emissions_per_second_x64: params.emissions_per_second_x64,
+                    reward_funder: signer.pubkey(),
+                    funder_token_account: params.funder_token_account,
+                    amm_config: self.amm_config,
+                    pool_state: self.pool_state,
+                    reward_mint: params.reward_mint,
+                    reward_token_program: reward_mint_account.owner,
+                },
+                &pool_state_account,
+                &operation_state_account,
+                current_timestamp,
+                self.program_id,
+            )?;
+
+        let metadata = sign_and_send_transaction!(svm, &[instruction], signer)?;
+
+        Ok((metadata, quote))
+    }
+
+    /// Collects the rewards left in the vault of the reward at `reward_index`
+    /// once it has ended.
+    pub fn collect_remaining_rewards(
+        &self,
+        svm: &mut LiteSVM,
+        reward_index: u8,
+        funder_token_account: Pubkey,
+        signer: &Keypair,
+    ) -> Result<TransactionMetadata> {
+        let pool_state_account = self.get_pool_state(svm)?;
+        let reward_mint = pool_state_account.reward_infos[reward_index as usize].token_mint;
+
+        let instruction =
+            raydium_clmm_client::instructions::prepare_collect_remaining_rewards_instruction(
+                raydium_clmm_client::types::CollectRemainingRewardsParams {
+                    reward_index,
+                    reward_funder: signer.pubkey(),
+                    funder_token_account,
+                    pool_state: self.pool_state,
+                    reward_mint,
+                },
                 self.program_id,
             )?;
 
@@ -248,4 +537,10 @@ impl RaydiumClmmTest {
 
         Ok(metadata)
     }
+
+    fn get_nft_token_program(&self, svm: &LiteSVM, position_nft_mint: Pubkey) -> Result<Pubkey> {
+        let nft_mint_account =
+            svm.get_account(&position_nft_mint).ok_or(ClmmTestError::MintNotFound)?;
+        Ok(nft_mint_account.owner)
+    }
 }
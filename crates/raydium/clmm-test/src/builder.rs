@@ -80,7 +80,7 @@ impl RaydiumClmmTestBuilder {
             open_time: 0,
         });
         let (pool_state, _) =
-            create_pool(svm, &signer, program_id, mint0, mint1, amm_config, pool_params)?;
+            create_pool(svm, signer, program_id, mint0, mint1, amm_config, pool_params)?;
 
         let observation_state = derive::derive_observation_pubkey(pool_state, Some(program_id)).0;
         let tick_array_bitmap =
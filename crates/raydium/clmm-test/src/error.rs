@@ -34,6 +34,9 @@ pub enum ClmmTestError {
 
     #[error("Amm config not found")]
     AmmConfigNotFound,
+
+    #[error("Operation state not found")]
+    OperationStateNotFound,
 }
 
 impl From<litesvm::types::FailedTransactionMetadata> for ClmmTestError {
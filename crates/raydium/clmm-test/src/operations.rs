@@ -1,9 +1,11 @@
+use std::collections::VecDeque;
+
 use litesvm::LiteSVM;
-use program_test_utils::account::{get_solana_account_by_borsh, get_solana_account_by_pack};
+use program_test_utils::account::get_solana_account_by_borsh;
 use raydium_clmm::{generated, utils::derive};
 use rust_decimal::Decimal;
 use solana_client_core::types::MaybeAccount;
-use solana_sdk::pubkey::Pubkey;
+use solana_sdk::{program_pack::Pack, pubkey::Pubkey};
 
 use crate::{
     error::{ClmmTestError, Result},
@@ -47,6 +49,23 @@ impl RaydiumClmmTest {
         Ok(pool_state.data)
     }
 
+    pub fn get_operation_state(
+        &self,
+        svm: &LiteSVM,
+    ) -> Result<generated::accounts::OperationState> {
+        let operation_state = derive::derive_operation_pubkey(Some(self.program_id)).0;
+        let operation_state = if let MaybeAccount::Exists(account) = get_solana_account_by_borsh::<
+            generated::accounts::OperationState,
+        >(svm, &operation_state)
+        {
+            account
+        } else {
+            return Err(ClmmTestError::OperationStateNotFound);
+        };
+
+        Ok(operation_state.data)
+    }
+
     pub fn get_tick_array_bitmap(
         &self,
         svm: &LiteSVM,
@@ -110,19 +129,44 @@ impl RaydiumClmmTest {
         Ok(tick_array.data)
     }
 
+    /// Loads the current and next tick arrays of the pool in swap direction.
+    pub fn get_swap_tick_arrays(
+        &self,
+        svm: &LiteSVM,
+        zero_for_one: bool,
+    ) -> Result<VecDeque<generated::accounts::TickArrayState>> {
+        let pool_state = self.get_pool_state(svm)?;
+        let tick_array_bitmap_extension = self.get_tick_array_bitmap(svm)?;
+
+        let tick_array_accounts =
+            raydium_clmm_client::utils::tick_array::load_cur_and_next_five_tick_array_pubkey(
+                self.pool_state,
+                &pool_state,
+                &tick_array_bitmap_extension,
+                zero_for_one,
+                Some(self.program_id),
+            )?;
+
+        tick_array_accounts
+            .into_iter()
+            .map(|tick_array_account| self.get_tick_array(svm, tick_array_account))
+            .collect()
+    }
+
     pub fn get_token_account(
         &self,
         svm: &LiteSVM,
         token_account: Pubkey,
     ) -> Result<spl_token::state::Account> {
-        let token_account = if let MaybeAccount::Exists(account) =
-            get_solana_account_by_pack::<spl_token::state::Account>(svm, &token_account)
-        {
-            account
-        } else {
-            return Err(ClmmTestError::TokenAccountNotFound.into());
-        };
+        let account = svm.get_account(&token_account).ok_or(ClmmTestError::TokenAccountNotFound)?;
+        // Token-2022 accounts start with the same layout, followed by their
+        // extensions
+        let token_account = account
+            .data
+            .get(..spl_token::state::Account::LEN)
+            .and_then(|data| spl_token::state::Account::unpack_from_slice(data).ok())
+            .ok_or(ClmmTestError::TokenAccountNotFound)?;
 
-        Ok(token_account.data)
+        Ok(token_account)
     }
 }
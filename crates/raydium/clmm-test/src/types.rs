@@ -1,3 +1,4 @@
+use program_test_utils::token::MintExtension;
 use solana_sdk::pubkey::Pubkey;
 
 #[derive(Clone, Debug)]
@@ -30,6 +31,12 @@ pub struct CreateMintsParams {
     pub decimals_b: u8,
     pub token_program_id_a: Option<Pubkey>,
     pub token_program_id_b: Option<Pubkey>,
+    /// Token-2022 extensions of mint A. Mint A is created with Token-2022
+    /// whenever this is not empty.
+    pub extensions_a: Vec<MintExtension>,
+    /// Token-2022 extensions of mint B. Mint B is created with Token-2022
+    /// whenever this is not empty.
+    pub extensions_b: Vec<MintExtension>,
 }
 
 impl Default for CreateMintsParams {
@@ -39,6 +46,8 @@ impl Default for CreateMintsParams {
             decimals_b: 6,
             token_program_id_a: Some(spl_token::ID),
             token_program_id_b: Some(spl_token::ID),
+            extensions_a: vec![],
+            extensions_b: vec![],
         }
     }
 }
@@ -102,6 +111,15 @@ pub struct SwapV2Params {
     pub user_token_account1: Pubkey,
 }
 
+#[derive(Clone, Debug, Default)]
+pub struct SwapRouterBaseInParams {
+    pub amount_in: u64,
+    pub amount_out_minimum: u64,
+    pub zero_for_one: bool,
+    pub user_token_account0: Pubkey,
+    pub user_token_account1: Pubkey,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct DecreaseLiquidityV2Params {
     pub liquidity: u128,
@@ -111,3 +129,12 @@ pub struct DecreaseLiquidityV2Params {
     pub recipient_token_account0: Pubkey,
     pub recipient_token_account1: Pubkey,
 }
+
+#[derive(Clone, Debug, Default)]
+pub struct FundRewardParams {
+    pub reward_mint: Pubkey,
+    pub funder_token_account: Pubkey,
+    pub open_time: u64,
+    pub end_time: u64,
+    pub emissions_per_second_x64: u128,
+}
@@ -1,13 +1,14 @@
 use litesvm::{types::TransactionMetadata, LiteSVM};
 use program_test_utils::{
-    account::get_solana_account_by_pack, sign_and_send_transaction, token::create_mint,
+    sign_and_send_transaction,
+    token::{create_mint, create_mint_with_extensions, MintExtension},
 };
-use solana_client_core::types::MaybeAccount;
 use solana_sdk::{
     pubkey::Pubkey,
     signature::{Keypair, Signer},
 };
 use spl_token::ID as SPL_TOKEN_ID;
+use spl_token_2022::{extension::StateWithExtensions, state::Mint};
 
 use crate::{
     error::{ClmmTestError, Result},
@@ -22,10 +23,10 @@ pub fn create_mints(
     let token_program_id_a = params.token_program_id_a.unwrap_or(SPL_TOKEN_ID);
     let token_program_id_b = params.token_program_id_b.unwrap_or(SPL_TOKEN_ID);
 
-    let (mint_a, _) =
-        create_mint(svm, admin, &admin.pubkey(), params.decimals_a, token_program_id_a).unwrap();
-    let (mint_b, _) =
-        create_mint(svm, admin, &admin.pubkey(), params.decimals_b, token_program_id_b).unwrap();
+    let (mint_a, token_program_id_a) =
+        create_test_mint(svm, admin, params.decimals_a, token_program_id_a, &params.extensions_a)?;
+    let (mint_b, token_program_id_b) =
+        create_test_mint(svm, admin, params.decimals_b, token_program_id_b, &params.extensions_b)?;
     let (mint_0, mint_1, decimals_0, decimals_1, token_program_id_0, token_program_id_1) =
         if mint_a < mint_b {
             (
@@ -57,29 +58,10 @@ pub fn get_mints(
 ) -> Result<(Pubkey, Pubkey, u8, u8, Pubkey, Pubkey)> {
     let (mint_0, mint_1) = if mint_a < mint_b { (mint_a, mint_b) } else { (mint_b, mint_a) };
 
-    let mint_0_account = if let MaybeAccount::Exists(account) =
-        get_solana_account_by_pack::<spl_token::state::Mint>(svm, &mint_0)
-    {
-        account
-    } else {
-        return Err(ClmmTestError::MintNotFound.into());
-    };
-    let mint_1_account = if let MaybeAccount::Exists(account) =
-        get_solana_account_by_pack::<spl_token::state::Mint>(svm, &mint_1)
-    {
-        account
-    } else {
-        return Err(ClmmTestError::MintNotFound.into());
-    };
-
-    Ok((
-        mint_0,
-        mint_1,
-        mint_0_account.data.decimals,
-        mint_1_account.data.decimals,
-        mint_0_account.account.owner,
-        mint_1_account.account.owner,
-    ))
+    let (decimals_0, token_program_id_0) = get_mint_info(svm, &mint_0)?;
+    let (decimals_1, token_program_id_1) = get_mint_info(svm, &mint_1)?;
+
+    Ok((mint_0, mint_1, decimals_0, decimals_1, token_program_id_0, token_program_id_1))
 }
 
 pub fn create_amm_config(
@@ -118,6 +100,8 @@ pub fn create_pool(
     amm_config: Pubkey,
     params: CreatePoolParams,
 ) -> Result<(Pubkey, TransactionMetadata)> {
+    let (_, token_program0) = get_mint_info(svm, &mint0)?;
+    let (_, token_program1) = get_mint_info(svm, &mint1)?;
     let (instruction, pool_state) =
         raydium_clmm_client::instructions::prepare_create_pool_instruction(
             raydium_clmm_client::types::CreatePoolParams {
@@ -125,8 +109,8 @@ pub fn create_pool(
                 amm_config,
                 mint0,
                 mint1,
-                token_program0: SPL_TOKEN_ID,
-                token_program1: SPL_TOKEN_ID,
+                token_program0,
+                token_program1,
                 sqrt_price_x64: params.sqrt_price_x64,
                 open_time: params.open_time,
             },
@@ -137,3 +121,25 @@ pub fn create_pool(
 
     Ok((pool_state, metadata))
 }
+
+fn create_test_mint(
+    svm: &mut LiteSVM,
+    admin: &Keypair,
+    decimals: u8,
+    token_program_id: Pubkey,
+    extensions: &[MintExtension],
+) -> Result<(Pubkey, Pubkey)> {
+    if extensions.is_empty() {
+        let (mint, _) = create_mint(svm, admin, &admin.pubkey(), decimals, token_program_id)?;
+        return Ok((mint, token_program_id));
+    }
+    let (mint, _) = create_mint_with_extensions(svm, admin, &admin.pubkey(), decimals, extensions)?;
+    Ok((mint, spl_token_2022::ID))
+}
+
+fn get_mint_info(svm: &LiteSVM, mint: &Pubkey) -> Result<(u8, Pubkey)> {
+    let account = svm.get_account(mint).ok_or(ClmmTestError::MintNotFound)?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&account.data)
+        .map_err(|_| ClmmTestError::MintNotFound)?;
+    Ok((mint_state.base.decimals, account.owner))
+}
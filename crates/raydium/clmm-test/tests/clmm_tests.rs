@@ -1,14 +1,55 @@
 use litesvm::LiteSVM;
 use program_test_utils::{
     svm::update_clock,
-    token::{get_or_create_ata, mint_to},
+    token::{create_mint_with_extensions, get_or_create_ata, mint_to, MintExtension},
+};
+use proptest::prelude::*;
+use raydium_clmm::{
+    generated::types::TickState,
+    math::{
+        tick::{get_array_start_index, get_sqrt_price_at_tick},
+        tickarray_bitmap::is_overflow_default_tickarray_bitmap,
+    },
+    state::{PoolStateError, PoolStatusBitIndex},
+    utils::derive,
+    ID,
+};
+use raydium_clmm_client::{
+    analytics::{fee_apr, pool_fee_report},
+    constants::{INCREASE_EMISSIONS_PERIOD, MIN_REWARD_PERIOD, SECONDS_PER_YEAR},
+    discovery::{fetch_amm_configs, fetch_pools_by_mints, find_amm_config},
+    error::ClmmClientError,
+    instructions::{
+        prepare_create_pool_instruction_checked, prepare_decrease_liquidity_v2_instruction_checked,
+        prepare_increase_liquidity_v2_instruction_checked,
+        prepare_open_position_v2_instruction_checked, prepare_swap_v2_instruction_checked,
+    },
+    math::{
+        price::normalize_price_sqrt,
+        reward::{calculate_emissions_per_second_x64, calculate_reward_amount},
+        token::apply_transfer_fee,
+    },
+    quote::{
+        collect_fees_quote, collect_rewards_quote, max_swap_amount_quote, remaining_rewards_quote,
+        swap_price_quote, swap_to_price_quote,
+    },
+    types::PositionAccounts,
+    utils::tick_array::load_cur_and_next_tick_array_pubkeys,
+    validation::{
+        validate_collect, validate_decrease_liquidity, validate_increase_liquidity,
+        validate_pool_mint, validate_swap,
+    },
 };
-use raydium_clmm::ID;
 use raydium_clmm_test::{
     builder::RaydiumClmmTestBuilder,
+    error::ClmmTestError,
     operations::RaydiumClmmTest,
-    types::{IncreaseLiquidityV2Params, OpenPositionV2Params, SwapV2Params},
+    types::{
+        CreateAmmConfigParams, CreateMintsParams, DecreaseLiquidityV2Params, FundRewardParams,
+        IncreaseLiquidityV2Params, OpenPositionV2Params, SwapRouterBaseInParams, SwapV2Params,
+    },
 };
+use rust_decimal::Decimal;
 use solana_sdk::{
     pubkey::Pubkey,
     signature::{Keypair, Signer},
@@ -18,6 +59,17 @@ use solana_sdk::{
 const INITIAL_LIQUIDITY: u128 = 1_000_000_000;
 const MAX_AMOUNT: u64 = 1_000_000_000_000_000_000;
 const INCREASE_LIQUIDITY: u128 = 10_000;
+const FAR_POSITION_LIQUIDITY: u128 = 1_000_000_000_000_000;
+const SWAP_FEE_AMM_CONFIG: CreateAmmConfigParams = CreateAmmConfigParams {
+    config_index: 0,
+    tick_spacing: 1,
+    trade_fee_rate: 2500,
+    protocol_fee_rate: 120_000,
+    fund_fee_rate: 40_000,
+};
+const TRANSFER_FEE_BPS: u16 = 100;
+/// `UpdateOperationAccount` param adding operation owners
+const UPDATE_OPERATION_OWNER: u8 = 0;
 
 #[cfg(test)]
 mod tests {
@@ -107,6 +159,15 @@ mod tests {
         // Perform a swap
         let _unused = clmm_test.swap_v2(&mut svm, params, &user0.keypair)?;
 
+        let pool_state = clmm_test.get_pool_state(&svm)?;
+        assert_eq!(pool_state.sqrt_price_x64, swap_state.sqrt_price_x64_after);
+        assert_eq!(pool_state.tick_current, swap_state.tick_after);
+        assert_eq!(
+            swap_state.lp_fee + swap_state.protocol_fee + swap_state.fund_fee,
+            swap_state.trade_fee
+        );
+        assert!(!swap_state.tick_array_accounts.is_empty());
+
         let token_account_0_after = clmm_test.get_token_account(&mut svm, user0.token_account0)?;
         let token_account_1_after = clmm_test.get_token_account(&mut svm, user0.token_account1)?;
 
@@ -174,42 +235,701 @@ mod tests {
 
         Ok(())
     }
-}
 
-fn create_fixture() -> Result<Fixture, Box<dyn std::error::Error>> {
-    let program_id = ID;
-    let mut svm = LiteSVM::new().with_sigverify(false);
-    svm.add_program(program_id, include_bytes!("fixtures/raydium_clmm.so"));
-    let metadata_program_id =
-        solana_program::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
-    svm.add_program(metadata_program_id, include_bytes!("fixtures/metaplex_metadata.so"));
+    #[test]
+    fn test_open_position_far_from_zero_tick_spacing_1() -> Result<(), Box<dyn std::error::Error>> {
+        // Out of the default bitmap range [-30720, 30720)
+        open_position_far_from_zero(1, 40_020, 40_080, true)
+    }
 
-    let admin = Keypair::new();
+    #[test]
+    fn test_open_position_far_from_zero_tick_spacing_10() -> Result<(), Box<dyn std::error::Error>>
+    {
+        // Out of the default bitmap range [-307200, 307200)
+        open_position_far_from_zero(10, -310_600, -310_000, true)
+    }
 
-    let _unused = svm.airdrop(&admin.pubkey(), 1_000_000_000).unwrap();
+    #[test]
+    fn test_open_position_far_from_zero_tick_spacing_60() -> Result<(), Box<dyn std::error::Error>>
+    {
+        // The default bitmap covers every tick for tick spacings of 60 and above
+        open_position_far_from_zero(60, 400_020, 400_080, false)
+    }
 
-    let clmm_test = RaydiumClmmTestBuilder::new().build(&mut svm, &admin).unwrap();
-    let user0 =
-        create_user(&mut svm, &admin, &clmm_test.token_pair.mint0, &clmm_test.token_pair.mint1);
-    let user1 =
-        create_user(&mut svm, &admin, &clmm_test.token_pair.mint0, &clmm_test.token_pair.mint1);
-    let user2 =
-        create_user(&mut svm, &admin, &clmm_test.token_pair.mint0, &clmm_test.token_pair.mint1);
+    #[test]
+    fn test_open_position_far_from_zero_tick_spacing_120() -> Result<(), Box<dyn std::error::Error>>
+    {
+        open_position_far_from_zero(120, -400_080, -399_960, false)
+    }
+
+    #[test]
+    fn test_tick_array_discovery_without_liquidity() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { clmm_test, svm, admin: _, user0: _, user1: _, user2: _ } =
+            create_fixture_with(FixtureParams {
+                amm_config_params: CreateAmmConfigParams { tick_spacing: 1, ..Default::default() },
+                ..Default::default()
+            })?;
+
+        let pool_state = clmm_test.get_pool_state(&svm)?;
+        let tick_array_bitmap_extension = clmm_test.get_tick_array_bitmap(&svm)?;
+
+        for zero_for_one in [true, false] {
+            let result = load_cur_and_next_tick_array_pubkeys(
+                clmm_test.pool_state,
+                &pool_state,
+                &tick_array_bitmap_extension,
+                zero_for_one,
+                3,
+                Some(clmm_test.program_id),
+            );
+            assert!(matches!(
+                result,
+                Err(ClmmClientError::PoolStateError(
+                    PoolStateError::InsufficientLiquidityForDirection
+                ))
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_swap_preview_matches_program() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { clmm_test, mut svm, admin: _, user0, user1: _, user2: _ } =
+            create_fixture_with(FixtureParams {
+                amm_config_params: SWAP_FEE_AMM_CONFIG,
+                with_positions: true,
+                ..Default::default()
+            })?;
+        update_clock(&mut svm, 1, 1000);
+
+        // Swaps replayed in order: within a range, landing on and crossing
+        // initialized ticks in both directions, exact input and exact output
+        let swaps = [
+            (false, true, 100),
+            (false, true, 3_000),
+            (true, false, 2_000),
+            (true, true, 5_000),
+            (false, false, 4_000),
+            (true, true, 1),
+            (false, true, 2_000),
+        ];
+        for (zero_for_one, is_base_input, amount) in swaps {
+            assert_swap_matches_program(
+                &clmm_test,
+                &mut svm,
+                &user0,
+                zero_for_one,
+                is_base_input,
+                amount,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn swap_preview_matches_program_fuzz(
+            swaps in prop::collection::vec(
+                (proptest::bool::ANY, proptest::bool::ANY, 1..10_000u64),
+                1..8,
+            ),
+        ) {
+            let Fixture { clmm_test, mut svm, admin: _, user0, user1: _, user2: _ } =
+                create_fixture_with(FixtureParams {
+                amm_config_params: SWAP_FEE_AMM_CONFIG,
+                with_positions: true,
+                ..Default::default()
+            }).unwrap();
+            update_clock(&mut svm, 1, 1000);
+
+            for (zero_for_one, is_base_input, amount) in swaps {
+                assert_swap_matches_program(
+                    &clmm_test,
+                    &mut svm,
+                    &user0,
+                    zero_for_one,
+                    is_base_input,
+                    amount,
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_swap_to_price_quotes() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { clmm_test, mut svm, admin: _, user0, user1: _, user2: _ } =
+            create_fixture_with(FixtureParams {
+                amm_config_params: SWAP_FEE_AMM_CONFIG,
+                with_positions: true,
+                ..Default::default()
+            })?;
+        update_clock(&mut svm, 1, 1000);
+
+        let amm_config = clmm_test.get_amm_config(&svm)?;
+        let pool_state = clmm_test.get_pool_state(&svm)?;
+        let tick_array_bitmap_extension = clmm_test.get_tick_array_bitmap(&svm)?;
+        let tick_arrays = clmm_test.get_swap_tick_arrays(&svm, false)?;
+
+        // Moving the price up to tick 40 crosses the initialized ticks on the way
+        let target_sqrt_price_x64 = get_sqrt_price_at_tick(40)?;
+        let quote = swap_to_price_quote(
+            target_sqrt_price_x64,
+            &amm_config,
+            &pool_state,
+            &tick_array_bitmap_extension,
+            &tick_arrays,
+        )?;
+        assert!(!quote.zero_for_one);
+        assert_eq!(quote.sqrt_price_x64_after, target_sqrt_price_x64);
+        assert_eq!(quote.tick_after, 40);
+
+        let price_quote = swap_price_quote(
+            quote.amount_in,
+            true,
+            false,
+            &amm_config,
+            &pool_state,
+            &tick_array_bitmap_extension,
+            &tick_arrays,
+        )?;
+        assert_eq!(price_quote.amount_in, quote.amount_in);
+
+        // Swapping the quoted amount with the target as price limit lands on it
+        let _unused = clmm_test.swap_v2(
+            &mut svm,
+            SwapV2Params {
+                amount: quote.amount_in,
+                other_amount_threshold: 0,
+                sqrt_price_limit_x64: target_sqrt_price_x64,
+                is_base_input: true,
+                zero_for_one: false,
+                user_token_account0: user0.token_account0,
+                user_token_account1: user0.token_account1,
+            },
+            &user0.keypair,
+        )?;
+        let pool_state = clmm_test.get_pool_state(&svm)?;
+        assert_eq!(pool_state.sqrt_price_x64, target_sqrt_price_x64);
+        assert_eq!(pool_state.tick_current, quote.tick_after);
+
+        // The largest swap down within 50 bps of price impact
+        let tick_arrays = clmm_test.get_swap_tick_arrays(&svm, true)?;
+        let quote = max_swap_amount_quote(
+            50,
+            true,
+            &amm_config,
+            &pool_state,
+            &tick_array_bitmap_extension,
+            &tick_arrays,
+        )?;
+        assert!(quote.zero_for_one);
+        assert!(quote.amount_in > 0);
+        let price_ratio =
+            (quote.sqrt_price_x64_after as f64 / quote.sqrt_price_x64_before as f64).powi(2);
+        assert!((price_ratio - 0.995).abs() < 1e-9);
+
+        // Only the first loaded tick array is not enough to move the price by 5%
+        let first_tick_array = tick_arrays.iter().take(1).cloned().collect();
+        let result = max_swap_amount_quote(
+            500,
+            true,
+            &amm_config,
+            &pool_state,
+            &tick_array_bitmap_extension,
+            &first_tick_array,
+        );
+        assert!(matches!(result, Err(ClmmClientError::NoTickArrayAvailable)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pool_fee_report() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { clmm_test, mut svm, admin: _, user0, user1, user2: _ } =
+            create_fixture_with(FixtureParams {
+                amm_config_params: SWAP_FEE_AMM_CONFIG,
+                with_positions: true,
+                ..Default::default()
+            })?;
+        update_clock(&mut svm, 1, 1000);
+
+        // A position deep enough for the swap to stay within its range
+        let (tick_lower_index, tick_upper_index) = (-60, 60);
+        let (position_nft_mint, ..) = clmm_test.open_position_v2(
+            &mut svm,
+            OpenPositionV2Params {
+                tick_lower_index,
+                tick_upper_index,
+                liquidity: INITIAL_LIQUIDITY,
+                amount0_max: MAX_AMOUNT,
+                amount1_max: MAX_AMOUNT,
+                user_token_account0: user0.token_account0,
+                user_token_account1: user0.token_account1,
+            },
+            &user0.keypair,
+        )?;
+        let pool_state_before = clmm_test.get_pool_state(&svm)?;
+
+        let amount = 100_000;
+        let _unused = clmm_test.swap_v2(
+            &mut svm,
+            SwapV2Params {
+                amount,
+                other_amount_threshold: 0,
+                sqrt_price_limit_x64: 0,
+                is_base_input: true,
+                zero_for_one: true,
+                user_token_account0: user1.token_account0,
+                user_token_account1: user1.token_account1,
+            },
+            &user1.keypair,
+        )?;
+        update_clock(&mut svm, 2, 1000 + 86_400);
+        let pool_state_after = clmm_test.get_pool_state(&svm)?;
+        assert!(pool_state_after.tick_current >= tick_lower_index);
+
+        let report = pool_fee_report(&pool_state_before, 1000, &pool_state_after, 1000 + 86_400)?;
+        assert_eq!(report.elapsed_seconds, 86_400);
+        assert_eq!(report.volume_in_0, amount as u128);
+        assert_eq!(report.volume_in_1, 0);
+        assert_eq!(
+            report.volume_out_1,
+            pool_state_after.swap_out_amount_token1 - pool_state_before.swap_out_amount_token1
+        );
+        assert!(report.lp_fee_0 > 0);
+        assert!(report.protocol_fee_0 > 0);
+        assert!(report.fund_fee_0 > 0);
+        assert_eq!(report.lp_fee_1 + report.protocol_fee_1 + report.fund_fee_1, 0);
+
+        // The APR is the yield of the fees the program pays out to the
+        // position, valued like the liquidity it pays out when closing it
+        let fees = decrease_liquidity(&clmm_test, &mut svm, &user0, position_nft_mint, 0)?;
+        assert!(fees[0] > 0);
+        assert_eq!(fees[1], 0);
+        let amounts =
+            decrease_liquidity(&clmm_test, &mut svm, &user0, position_nft_mint, INITIAL_LIQUIDITY)?;
+
+        let price_sqrt = normalize_price_sqrt(pool_state_after.sqrt_price_x64);
+        let price = price_sqrt * price_sqrt;
+        let fee_value = Decimal::from(fees[0]) * price + Decimal::from(fees[1]);
+        let position_value = Decimal::from(amounts[0]) * price + Decimal::from(amounts[1]);
+        let apr = fee_apr(
+            &report,
+            &pool_state_after,
+            INITIAL_LIQUIDITY,
+            tick_lower_index,
+            tick_upper_index,
+        )?;
+        assert_eq!(
+            apr,
+            fee_value / position_value * (Decimal::from(SECONDS_PER_YEAR) / Decimal::from(86_400))
+        );
+
+        // Snapshots in the wrong order are rejected
+        let result = pool_fee_report(&pool_state_after, 1000, &pool_state_before, 2000);
+        assert!(matches!(result, Err(ClmmClientError::InconsistentPoolSnapshots)));
+        let result = pool_fee_report(&pool_state_before, 2000, &pool_state_after, 1000);
+        assert!(matches!(result, Err(ClmmClientError::InconsistentPoolSnapshots)));
 
-    // Define different tick ranges
-    let tick_ranges =
-        [(-120, -60), (-60, -30), (-10, 0), (0, 23), (1, 50), (-30, 30), (30, 60), (60, 120)];
+        Ok(())
+    }
+
+    #[test]
+    fn test_fund_reward() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { clmm_test, mut svm, admin, user0, user1, user2: _ } = create_fixture()?;
+        let _unused = clmm_test.create_operation_account(&mut svm, &admin)?;
+        let (operator, funder) = (&user0, &user1);
+        let reward_mint = clmm_test.token_pair.mint0;
+        let reward_vault =
+            derive::derive_pool_reward_vault_pubkey(clmm_test.pool_state, reward_mint, Some(ID)).0;
+
+        let open_time = 2000;
+        let end_time = open_time + MIN_REWARD_PERIOD;
+        let emissions_per_second_x64 =
+            calculate_emissions_per_second_x64(1_000_000_000, MIN_REWARD_PERIOD)?;
+
+        // Only the operators can start a reward
+        let params = FundRewardParams {
+            reward_mint,
+            funder_token_account: operator.token_account0,
+            open_time,
+            end_time,
+            emissions_per_second_x64,
+        };
+        let result = clmm_test.fund_reward(&mut svm, params.clone(), &operator.keypair);
+        assert!(matches!(
+            result,
+            Err(ClmmTestError::ClientError(ClmmClientError::RewardAuthorityNotApproved))
+        ));
+        let _unused = clmm_test.update_operation_account(
+            &mut svm,
+            UPDATE_OPERATION_OWNER,
+            vec![operator.keypair.pubkey()],
+            &admin,
+        )?;
+
+        // A new reward takes the first slot and pulls the quoted amount
+        let operator_before = clmm_test.get_token_account(&svm, operator.token_account0)?.amount;
+        let (_, quote) = clmm_test.fund_reward(&mut svm, params, &operator.keypair)?;
+        assert_eq!(quote.reward_index, 0);
+        assert!(quote.reward_amount <= 1_000_000_000);
+        assert_eq!(clmm_test.get_token_account(&svm, reward_vault)?.amount, quote.reward_amount);
+        assert_eq!(
+            operator_before - clmm_test.get_token_account(&svm, operator.token_account0)?.amount,
+            quote.reward_amount
+        );
+
+        let reward_info = clmm_test.get_pool_state(&svm)?.reward_infos[0].clone();
+        assert_eq!(reward_info.token_mint, reward_mint);
+        assert_eq!(reward_info.authority, operator.keypair.pubkey());
+        assert_eq!(reward_info.emissions_per_second_x64, emissions_per_second_x64);
+        assert_eq!(reward_info.open_time, open_time);
+        assert_eq!(reward_info.end_time, end_time);
+
+        // Close to its end, the new reward owner extends it at a higher rate
+        // and the program pulls the quoted top-up
+        let _unused = clmm_test.transfer_reward_owner(&mut svm, funder.keypair.pubkey(), &admin)?;
+        let current_timestamp = end_time - INCREASE_EMISSIONS_PERIOD / 2;
+        update_clock(&mut svm, 3, current_timestamp as i64);
+        let new_end_time = end_time + MIN_REWARD_PERIOD;
+        let new_emissions_per_second_x64 = emissions_per_second_x64 * 2;
+        let (_, quote) = clmm_test.fund_reward(
+            &mut svm,
+            FundRewardParams {
+                reward_mint,
+                funder_token_account: funder.token_account0,
+                open_time: current_timestamp + 1,
+                end_time: new_end_time,
+                emissions_per_second_x64: new_emissions_per_second_x64,
+            },
+            &funder.keypair,
+        )?;
+        assert_eq!(quote.reward_index, 0);
+        let vault_balance = clmm_test.get_token_account(&svm, reward_vault)?.amount;
+        assert_eq!(
+            vault_balance,
+            calculate_reward_amount(emissions_per_second_x64, MIN_REWARD_PERIOD)?
+                + quote.reward_amount
+        );
+
+        let pool_state = clmm_test.get_pool_state(&svm)?;
+        let reward_info = &pool_state.reward_infos[0];
+        assert_eq!(reward_info.emissions_per_second_x64, new_emissions_per_second_x64);
+        assert_eq!(reward_info.end_time, new_end_time);
+        assert_eq!(reward_info.last_update_time, current_timestamp);
+
+        // The vault holds what was and is left to emit, up to the program
+        // rounding the emitted amount up
+        let remaining = remaining_rewards_quote(&pool_state, current_timestamp)?.rewards[0];
+        assert_eq!(remaining.reward_mint, reward_mint);
+        assert_eq!(remaining.emitted, reward_info.reward_total_emissioned);
+        assert!(vault_balance.abs_diff(remaining.emitted + remaining.unemitted) <= 1);
+
+        // Lowering the rate of an ongoing reward is rejected
+        let result = clmm_test.fund_reward(
+            &mut svm,
+            FundRewardParams {
+                reward_mint,
+                funder_token_account: funder.token_account0,
+                open_time: current_timestamp + 1,
+                end_time: new_end_time + MIN_REWARD_PERIOD,
+                emissions_per_second_x64,
+            },
+            &funder.keypair,
+        );
+        assert!(matches!(
+            result,
+            Err(ClmmTestError::ClientError(ClmmClientError::InvalidRewardSchedule))
+        ));
+
+        // Once ended, the reward restarts with a new schedule
+        let current_timestamp = new_end_time + 1;
+        update_clock(&mut svm, 4, current_timestamp as i64);
+        let open_time = current_timestamp + 100;
+        let end_time = open_time + MIN_REWARD_PERIOD;
+        let (_, quote) = clmm_test.fund_reward(
+            &mut svm,
+            FundRewardParams {
+                reward_mint,
+                funder_token_account: funder.token_account0,
+                open_time,
+                end_time,
+                emissions_per_second_x64,
+            },
+            &funder.keypair,
+        )?;
+        assert_eq!(
+            clmm_test.get_token_account(&svm, reward_vault)?.amount,
+            vault_balance + quote.reward_amount
+        );
+        let reward_info = &clmm_test.get_pool_state(&svm)?.reward_infos[0];
+        assert_eq!(reward_info.emissions_per_second_x64, emissions_per_second_x64);
+        assert_eq!(reward_info.open_time, open_time);
+        assert_eq!(reward_info.end_time, end_time);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_swap_router_base_in() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { clmm_test, mut svm, admin: _, user0: _, user1, user2: _ } = create_fixture()?;
+        update_clock(&mut svm, 1, 1000);
+
+        for zero_for_one in [true, false] {
+            let preview = clmm_test.preview_swap_v2(
+                &svm,
+                SwapV2Params {
+                    amount: 10_000,
+                    other_amount_threshold: 0,
+                    sqrt_price_limit_x64: 0,
+                    is_base_input: true,
+                    zero_for_one,
+                    user_token_account0: user1.token_account0,
+                    user_token_account1: user1.token_account1,
+                },
+            )?;
+            let params = SwapRouterBaseInParams {
+                amount_in: 10_000,
+                amount_out_minimum: preview.amount_out + 1,
+                zero_for_one,
+                user_token_account0: user1.token_account0,
+                user_token_account1: user1.token_account1,
+            };
+            assert!(clmm_test
+                .swap_router_base_in(&mut svm, params.clone(), &user1.keypair)
+                .is_err());
+
+            let user_accounts = [user1.token_account0, user1.token_account1];
+            let vaults = [clmm_test.token_vault0, clmm_test.token_vault1];
+            let (user_before, _) = get_balances(&clmm_test, &svm, user_accounts, vaults)?;
+            let _unused = clmm_test.swap_router_base_in(
+                &mut svm,
+                SwapRouterBaseInParams { amount_out_minimum: preview.amount_out, ..params },
+                &user1.keypair,
+            )?;
+            let (user_after, _) = get_balances(&clmm_test, &svm, user_accounts, vaults)?;
+
+            let (input, output) = if zero_for_one { (0, 1) } else { (1, 0) };
+            assert_eq!(user_before[input] - user_after[input], preview.amount_in);
+            assert_eq!(user_after[output] - user_before[output], preview.amount_out);
+            let pool_state = clmm_test.get_pool_state(&svm)?;
+            assert_eq!(pool_state.sqrt_price_x64, preview.sqrt_price_x64_after);
+            assert_eq!(pool_state.tick_current, preview.tick_after);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_collect_remaining_rewards() -> Result<(), Box<dyn std::error::Error>> {
+        // Without liquidity in the pool, none of the reward is emitted
+        let Fixture { clmm_test, mut svm, admin, user0, user1: _, user2: _ } =
+            create_fixture_with(FixtureParams::default())?;
+        let _unused = clmm_test.create_operation_account(&mut svm, &admin)?;
+        let _unused = clmm_test.update_operation_account(
+            &mut svm,
+            UPDATE_OPERATION_OWNER,
+            vec![user0.keypair.pubkey()],
+            &admin,
+        )?;
+        update_clock(&mut svm, 1, 1000);
+        let open_time = 2000;
+        let end_time = open_time + MIN_REWARD_PERIOD;
+        let (_, quote) = clmm_test.fund_reward(
+            &mut svm,
+            FundRewardParams {
+                reward_mint: clmm_test.token_pair.mint0,
+                funder_token_account: user0.token_account0,
+                open_time,
+                end_time,
+                emissions_per_second_x64: calculate_emissions_per_second_x64(
+                    1_000_000_000,
+                    MIN_REWARD_PERIOD,
+                )?,
+            },
+            &user0.keypair,
+        )?;
 
-    let mut position_nft_mints = Vec::new();
+        update_clock(&mut svm, 2, end_time as i64 + 1);
+        let remaining =
+            remaining_rewards_quote(&clmm_test.get_pool_state(&svm)?, end_time + 1)?.rewards[0];
+        assert_eq!(remaining.emitted, remaining.claimed);
 
-    // Open positions in different ranges
-    for (tick_lower, tick_upper) in tick_ranges {
+        // The leftovers go back to the pool owner, not to the reward funder
+        assert!(clmm_test
+            .collect_remaining_rewards(
+                &mut svm,
+                quote.reward_index,
+                user0.token_account0,
+                &user0.keypair
+            )
+            .is_err());
+        let (owner_token_account, _) =
+            get_or_create_ata(&mut svm, &admin, &clmm_test.token_pair.mint0, &admin.pubkey())?;
+        let _unused = clmm_test.collect_remaining_rewards(
+            &mut svm,
+            quote.reward_index,
+            owner_token_account,
+            &admin,
+        )?;
+        assert_eq!(
+            clmm_test.get_token_account(&svm, owner_token_account)?.amount,
+            quote.reward_amount
+        );
+        let reward_vault = derive::derive_pool_reward_vault_pubkey(
+            clmm_test.pool_state,
+            clmm_test.token_pair.mint0,
+            Some(ID),
+        )
+        .0;
+        assert_eq!(clmm_test.get_token_account(&svm, reward_vault)?.amount, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_collect_quotes_match_program() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { clmm_test, mut svm, admin, user0, user1, user2: _ } =
+            create_fixture_with(FixtureParams {
+                amm_config_params: SWAP_FEE_AMM_CONFIG,
+                with_positions: true,
+                ..Default::default()
+            })?;
+        update_clock(&mut svm, 1, 1000);
+        let (tick_lower_index, tick_upper_index) = (-60, 60);
+        let (position_nft_mint, ..) = clmm_test.open_position_v2(
+            &mut svm,
+            OpenPositionV2Params {
+                tick_lower_index,
+                tick_upper_index,
+                liquidity: INITIAL_LIQUIDITY,
+                amount0_max: MAX_AMOUNT,
+                amount1_max: MAX_AMOUNT,
+                user_token_account0: user0.token_account0,
+                user_token_account1: user0.token_account1,
+            },
+            &user0.keypair,
+        )?;
+
+        let _unused = clmm_test.create_operation_account(&mut svm, &admin)?;
+        let _unused = clmm_test.update_operation_account(
+            &mut svm,
+            UPDATE_OPERATION_OWNER,
+            vec![user0.keypair.pubkey()],
+            &admin,
+        )?;
+        let _unused = clmm_test.fund_reward(
+            &mut svm,
+            FundRewardParams {
+                reward_mint: clmm_test.token_pair.mint1,
+                funder_token_account: user0.token_account1,
+                open_time: 1001,
+                end_time: 1001 + MIN_REWARD_PERIOD,
+                emissions_per_second_x64: calculate_emissions_per_second_x64(
+                    1_000_000_000,
+                    MIN_REWARD_PERIOD,
+                )?,
+            },
+            &user0.keypair,
+        )?;
+
+        // Swaps both ways accrue fees in both tokens to the position
+        for (slot, timestamp, zero_for_one) in [(2, 2000, true), (3, 3000, false)] {
+            update_clock(&mut svm, slot, timestamp);
+            let _unused = clmm_test.swap_v2(
+                &mut svm,
+                SwapV2Params {
+                    amount: 100_000,
+                    other_amount_threshold: 0,
+                    sqrt_price_limit_x64: 0,
+                    is_base_input: true,
+                    zero_for_one,
+                    user_token_account0: user1.token_account0,
+                    user_token_account1: user1.token_account1,
+                },
+                &user1.keypair,
+            )?;
+        }
+
+        let current_timestamp = 5000;
+        update_clock(&mut svm, 4, current_timestamp as i64);
+        let pool_state = clmm_test.get_pool_state(&svm)?;
+        let position = clmm_test.get_personal_position_state(&svm, position_nft_mint)?;
+        let tick_lower = get_tick_state(&clmm_test, &svm, tick_lower_index)?;
+        let tick_upper = get_tick_state(&clmm_test, &svm, tick_upper_index)?;
+        let fees_quote =
+            collect_fees_quote(&pool_state, &position, &tick_lower, &tick_upper, None, None)?;
+        let rewards_quote = collect_rewards_quote(
+            &pool_state,
+            &position,
+            &tick_lower,
+            &tick_upper,
+            current_timestamp,
+            None,
+            None,
+            None,
+        )?;
+        assert!(fees_quote.fee_owed_0 > 0);
+        assert!(fees_quote.fee_owed_1 > 0);
+        assert!(rewards_quote.rewards[0].rewards_owed > 0);
+
+        // Collecting pays out the quoted fees and rewards, the reward being
+        // paid in token 1
+        let amounts = decrease_liquidity(&clmm_test, &mut svm, &user0, position_nft_mint, 0)?;
+        assert_eq!(
+            amounts,
+            [fees_quote.fee_owed_0, fees_quote.fee_owed_1 + rewards_quote.rewards[0].rewards_owed]
+        );
+        let position = clmm_test.get_personal_position_state(&svm, position_nft_mint)?;
+        assert_eq!(position.token_fees_owed0, 0);
+        assert_eq!(position.token_fees_owed1, 0);
+        assert!(position
+            .reward_infos
+            .iter()
+            .all(|reward_info| reward_info.reward_amount_owed == 0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_token22_flows() -> Result<(), Box<dyn std::error::Error>> {
+        let transfer_fee =
+            vec![MintExtension::TransferFee { fee_bps: TRANSFER_FEE_BPS, max_fee: u64::MAX }];
+        let cases = [
+            (vec![], vec![]),
+            (transfer_fee.clone(), vec![]),
+            (vec![], transfer_fee.clone()),
+            (transfer_fee.clone(), transfer_fee),
+        ];
+
+        // Mints with a transfer hook are refused, see
+        // `test_transfer_hook_mint_rejected`
+        for (extensions_a, extensions_b) in cases {
+            assert_token22_flow(CreateMintsParams {
+                extensions_a,
+                extensions_b,
+                ..Default::default()
+            })?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pool_status_checks() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { clmm_test, mut svm, admin, user0, user1: _, user2: _ } = create_fixture()?;
+        update_clock(&mut svm, 1, 1000);
         let (position_nft_mint, ..) = clmm_test.open_position_v2(
             &mut svm,
             OpenPositionV2Params {
-                tick_lower_index: tick_lower,
-                tick_upper_index: tick_upper,
-                liquidity: 1_000_000,
+                tick_lower_index: -60,
+                tick_upper_index: 60,
+                liquidity: INITIAL_LIQUIDITY,
                 amount0_max: MAX_AMOUNT,
                 amount1_max: MAX_AMOUNT,
                 user_token_account0: user0.token_account0,
@@ -217,12 +937,325 @@ fn create_fixture() -> Result<Fixture, Box<dyn std::error::Error>> {
             },
             &user0.keypair,
         )?;
+        let increase_params = IncreaseLiquidityV2Params {
+            liquidity: INCREASE_LIQUIDITY,
+            amount0_max: MAX_AMOUNT,
+            amount1_max: MAX_AMOUNT,
+            user_token_account0: user0.token_account0,
+            user_token_account1: user0.token_account1,
+            position_nft_mint,
+        };
+        let decrease_params = |liquidity| DecreaseLiquidityV2Params {
+            liquidity,
+            amount0_min: 0,
+            amount1_min: 0,
+            position_nft_mint,
+            recipient_token_account0: user0.token_account0,
+            recipient_token_account1: user0.token_account1,
+        };
+        let swap_params = SwapV2Params {
+            amount: 1_000,
+            other_amount_threshold: 0,
+            sqrt_price_limit_x64: 0,
+            is_base_input: true,
+            zero_for_one: true,
+            user_token_account0: user0.token_account0,
+            user_token_account1: user0.token_account1,
+        };
+        let position_accounts =
+            PositionAccounts { nft_token_program: spl_token::ID, reward_recipients: vec![] };
+        let disabled = |operation: PoolStatusBitIndex| {
+            move |result: Result<_, ClmmClientError>| {
+                matches!(
+                    result,
+                    Err(ClmmClientError::PoolOperationDisabled(op)) if op == operation
+                )
+            }
+        };
+
+        // Opening positions and adding liquidity
+        let _unused = clmm_test.update_pool_status(
+            &mut svm,
+            1 << PoolStatusBitIndex::OpenPositionOrIncreaseLiquidity as u8,
+            &admin,
+        )?;
+        let pool_state = clmm_test.get_pool_state(&svm)?;
+        let is_disabled = disabled(PoolStatusBitIndex::OpenPositionOrIncreaseLiquidity);
+        assert!(is_disabled(validate_increase_liquidity(&pool_state)));
+        assert!(is_disabled(
+            prepare_open_position_v2_instruction_checked(Default::default(), &pool_state, ID)
+                .map(|_| ())
+        ));
+        assert!(is_disabled(
+            prepare_increase_liquidity_v2_instruction_checked(
+                Default::default(),
+                &position_accounts,
+                &pool_state,
+                ID
+            )
+            .map(|_| ())
+        ));
+        assert!(clmm_test
+            .increase_liquidity_v2(&mut svm, increase_params.clone(), &user0.keypair)
+            .is_err());
+
+        // Removing liquidity is refused only once collecting fees and rewards
+        // are disabled too
+        let _unused = clmm_test.update_pool_status(
+            &mut svm,
+            1 << PoolStatusBitIndex::DecreaseLiquidity as u8
+                | 1 << PoolStatusBitIndex::CollectFee as u8,
+            &admin,
+        )?;
+        let pool_state = clmm_test.get_pool_state(&svm)?;
+        validate_decrease_liquidity(&pool_state)?;
+        validate_collect(&pool_state)?;
+        let _unused =
+            clmm_test.decrease_liquidity_v2(&mut svm, decrease_params(1), &user0.keypair)?;
+
+        let _unused = clmm_test.update_pool_status(
+            &mut svm,
+            1 << PoolStatusBitIndex::DecreaseLiquidity as u8
+                | 1 << PoolStatusBitIndex::CollectFee as u8
+                | 1 << PoolStatusBitIndex::CollectReward as u8,
+            &admin,
+        )?;
+        let pool_state = clmm_test.get_pool_state(&svm)?;
+        let is_disabled = disabled(PoolStatusBitIndex::CollectReward);
+        assert!(is_disabled(validate_collect(&pool_state)));
+        assert!(is_disabled(validate_decrease_liquidity(&pool_state)));
+        assert!(is_disabled(
+            prepare_decrease_liquidity_v2_instruction_checked(
+                Default::default(),
+                &position_accounts,
+                &pool_state,
+                ID
+            )
+            .map(|_| ())
+        ));
+        // A new blockhash, so that the program sees the decrease sent above again
+        svm.expire_blockhash();
+        for liquidity in [0, 1] {
+            assert!(clmm_test
+                .decrease_liquidity_v2(&mut svm, decrease_params(liquidity), &user0.keypair)
+                .is_err());
+        }
+
+        // Swapping
+        let _unused =
+            clmm_test.update_pool_status(&mut svm, 1 << PoolStatusBitIndex::Swap as u8, &admin)?;
+        let pool_state = clmm_test.get_pool_state(&svm)?;
+        let is_disabled = disabled(PoolStatusBitIndex::Swap);
+        assert!(is_disabled(validate_swap(&pool_state, 1000)));
+        assert!(is_disabled(
+            prepare_swap_v2_instruction_checked(Default::default(), &pool_state, 1000, ID)
+                .map(|_| ())
+        ));
+        assert!(clmm_test.swap_v2(&mut svm, swap_params.clone(), &user0.keypair).is_err());
+
+        // Everything is back once the pool is enabled again
+        svm.expire_blockhash();
+        let _unused = clmm_test.update_pool_status(&mut svm, 0, &admin)?;
+        let pool_state = clmm_test.get_pool_state(&svm)?;
+        validate_increase_liquidity(&pool_state)?;
+        validate_decrease_liquidity(&pool_state)?;
+        validate_swap(&pool_state, 1000)?;
+        let _unused = clmm_test.increase_liquidity_v2(&mut svm, increase_params, &user0.keypair)?;
+        let _unused = clmm_test.swap_v2(&mut svm, swap_params, &user0.keypair)?;
+
+        // Swaps are only accepted after the pool open time
+        let result = validate_swap(&pool_state, pool_state.open_time);
+        assert!(matches!(
+            result,
+            Err(ClmmClientError::PoolNotOpen { open_time }) if open_time == pool_state.open_time
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pool_mint_checks() -> Result<(), Box<dyn std::error::Error>> {
+        let mut svm = LiteSVM::new().with_sigverify(false);
+        let admin = Keypair::new();
+        let _unused = svm.airdrop(&admin.pubkey(), 1_000_000_000).unwrap();
+
+        let (mint, _) = create_mint_with_extensions(&mut svm, &admin, &admin.pubkey(), 6, &[])?;
+        let mint_data = svm.get_account(&mint).unwrap().data;
+        validate_pool_mint(mint, &mint_data, &[])?;
+
+        let transfer_fee =
+            MintExtension::TransferFee { fee_bps: TRANSFER_FEE_BPS, max_fee: u64::MAX };
+        let (fee_mint, _) =
+            create_mint_with_extensions(&mut svm, &admin, &admin.pubkey(), 6, &[transfer_fee])?;
+        let fee_mint_data = svm.get_account(&fee_mint).unwrap().data;
+        validate_pool_mint(fee_mint, &fee_mint_data, &[])?;
+
+        // An unsupported extension is only accepted once the mint is whitelisted
+        let permanent_delegate = MintExtension::PermanentDelegate { delegate: admin.pubkey() };
+        let (delegate_mint, _) = create_mint_with_extensions(
+            &mut svm,
+            &admin,
+            &admin.pubkey(),
+            6,
+            &[permanent_delegate],
+        )?;
+        let delegate_mint_data = svm.get_account(&delegate_mint).unwrap().data;
+        let result = validate_pool_mint(delegate_mint, &delegate_mint_data, &[]);
+        assert!(matches!(result, Err(ClmmClientError::UnsupportedMint(m)) if m == delegate_mint));
+        validate_pool_mint(delegate_mint, &delegate_mint_data, &[delegate_mint])?;
+
+        let result = validate_pool_mint(mint, &[0; 10], &[]);
+        assert!(matches!(result, Err(ClmmClientError::InvalidMintAccount)));
+
+        // The checked pool creation refuses either mint
+        let params = raydium_clmm_client::types::CreatePoolParams {
+            mint0: delegate_mint,
+            mint1: fee_mint,
+            ..Default::default()
+        };
+        let result = prepare_create_pool_instruction_checked(
+            params.clone(),
+            &delegate_mint_data,
+            &fee_mint_data,
+            &[],
+            ID,
+        );
+        assert!(matches!(result, Err(ClmmClientError::UnsupportedMint(m)) if m == delegate_mint));
+        let result = prepare_create_pool_instruction_checked(
+            raydium_clmm_client::types::CreatePoolParams {
+                mint0: fee_mint,
+                mint1: delegate_mint,
+                ..params.clone()
+            },
+            &fee_mint_data,
+            &delegate_mint_data,
+            &[],
+            ID,
+        );
+        assert!(matches!(result, Err(ClmmClientError::UnsupportedMint(m)) if m == delegate_mint));
+        let _unused = prepare_create_pool_instruction_checked(
+            params,
+            &delegate_mint_data,
+            &fee_mint_data,
+            &[delegate_mint],
+            ID,
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transfer_hook_mint_rejected() -> Result<(), Box<dyn std::error::Error>> {
+        let mut svm = LiteSVM::new().with_sigverify(false);
+        svm.add_program(ID, include_bytes!("fixtures/raydium_clmm.so"));
+        let admin = Keypair::new();
+        let _unused = svm.airdrop(&admin.pubkey(), 1_000_000_000).unwrap();
+
+        let transfer_hook = MintExtension::TransferHook { program_id: Pubkey::new_unique() };
+        let (mint, _) =
+            create_mint_with_extensions(&mut svm, &admin, &admin.pubkey(), 6, &[transfer_hook])?;
+        let mint_account = svm.get_account(&mint).unwrap();
+        let result = validate_pool_mint(mint, &mint_account.data, &[]);
+        assert!(matches!(result, Err(ClmmClientError::UnsupportedMint(m)) if m == mint));
+        // Whitelisting does not help, the program cannot forward the hook accounts
+        let result = validate_pool_mint(mint, &mint_account.data, &[mint]);
+        assert!(matches!(result, Err(ClmmClientError::UnsupportedMint(_))));
+
+        // The program refuses to create the pool as well
+        let result = RaydiumClmmTestBuilder::new()
+            .with_mints_params(CreateMintsParams {
+                extensions_a: vec![transfer_hook],
+                ..Default::default()
+            })
+            .build(&mut svm, &admin);
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_pools() -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { clmm_test, svm, .. } = create_fixture()?;
+        let fetch_accounts = |addresses: &[Pubkey]| {
+            Ok::<_, std::convert::Infallible>(
+                addresses.iter().map(|address| svm.get_account(address)).collect(),
+            )
+        };
+
+        let amm_configs = fetch_amm_configs(0..200, ID, fetch_accounts)?;
+        assert_eq!(amm_configs.len(), 1);
+        assert_eq!(amm_configs[0].address, clmm_test.amm_config);
+
+        let default_config = CreateAmmConfigParams::default();
+        let amm_config = find_amm_config(
+            &amm_configs,
+            default_config.tick_spacing,
+            default_config.trade_fee_rate,
+        );
+        assert_eq!(amm_config.map(|amm_config| amm_config.address), Some(clmm_test.amm_config));
+        assert!(find_amm_config(&amm_configs, 60, default_config.trade_fee_rate).is_none());
+
+        // The mints can be given in any order
+        let pools = fetch_pools_by_mints(
+            &amm_configs,
+            clmm_test.token_pair.mint1,
+            clmm_test.token_pair.mint0,
+            ID,
+            fetch_accounts,
+        )?;
+        assert_eq!(pools.len(), 1);
+        assert_eq!(pools[0].address, clmm_test.pool_state);
+        assert_eq!(pools[0].tick_spacing, default_config.tick_spacing);
+        assert!(pools[0].pool_state.liquidity > 0);
+
+        let pools = fetch_pools_by_mints(
+            &amm_configs,
+            clmm_test.token_pair.mint0,
+            Pubkey::new_unique(),
+            ID,
+            fetch_accounts,
+        )?;
+        assert!(pools.is_empty());
+
+        Ok(())
+    }
+
+    /// Opens a position, swaps both ways and removes liquidity in a pool
+    /// created with `mints_params`, checking the pool vaults receive what the
+    /// users send minus the transfer fees.
+    fn assert_token22_flow(
+        mints_params: CreateMintsParams,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { clmm_test, mut svm, admin: _, user0, user1, user2: _ } =
+            create_fixture_with(FixtureParams { mints_params, ..Default::default() })?;
         update_clock(&mut svm, 1, 1000);
 
+        let transfer_fees = [clmm_test.token_pair.mint0, clmm_test.token_pair.mint1].map(|mint| {
+            let mint_data = svm.get_account(&mint).unwrap().data;
+            raydium_clmm_client::math::token::get_transfer_fee(&mint_data, 0)
+                .unwrap()
+                .unwrap_or_default()
+        });
+        let vaults = [clmm_test.token_vault0, clmm_test.token_vault1];
+
+        let params = OpenPositionV2Params {
+            tick_lower_index: -60,
+            tick_upper_index: 60,
+            liquidity: INITIAL_LIQUIDITY,
+            amount0_max: MAX_AMOUNT,
+            amount1_max: MAX_AMOUNT,
+            user_token_account0: user0.token_account0,
+            user_token_account1: user0.token_account1,
+        };
+        let (position_nft_mint, ..) =
+            clmm_test.open_position_v2(&mut svm, params, &user0.keypair)?;
+
+        let user_accounts = [user0.token_account0, user0.token_account1];
+        let (user_before, vaults_before) = get_balances(&clmm_test, &svm, user_accounts, vaults)?;
         let _unused = clmm_test.increase_liquidity_v2(
             &mut svm,
             IncreaseLiquidityV2Params {
-                liquidity: 1_000_000,
+                liquidity: INCREASE_LIQUIDITY,
                 amount0_max: MAX_AMOUNT,
                 amount1_max: MAX_AMOUNT,
                 user_token_account0: user0.token_account0,
@@ -231,8 +1264,336 @@ fn create_fixture() -> Result<Fixture, Box<dyn std::error::Error>> {
             },
             &user0.keypair,
         )?;
+        let (user_after, vaults_after) = get_balances(&clmm_test, &svm, user_accounts, vaults)?;
+        for i in 0..2 {
+            let sent = user_before[i] - user_after[i];
+            assert_eq!(
+                vaults_after[i] - vaults_before[i],
+                apply_transfer_fee(sent, transfer_fees[i])?
+            );
+        }
+
+        let user_accounts = [user1.token_account0, user1.token_account1];
+        for zero_for_one in [true, false] {
+            let (input, output) = if zero_for_one { (0, 1) } else { (1, 0) };
+            let (user_before, vaults_before) =
+                get_balances(&clmm_test, &svm, user_accounts, vaults)?;
+            let _unused = clmm_test.swap_v2(
+                &mut svm,
+                SwapV2Params {
+                    amount: 10_000,
+                    other_amount_threshold: 0,
+                    sqrt_price_limit_x64: 0,
+                    is_base_input: true,
+                    zero_for_one,
+                    user_token_account0: user1.token_account0,
+                    user_token_account1: user1.token_account1,
+                },
+                &user1.keypair,
+            )?;
+            let (user_after, vaults_after) = get_balances(&clmm_test, &svm, user_accounts, vaults)?;
+
+            assert_eq!(user_before[input] - user_after[input], 10_000);
+            assert_eq!(
+                vaults_after[input] - vaults_before[input],
+                apply_transfer_fee(10_000, transfer_fees[input])?
+            );
+            let paid_out = vaults_before[output] - vaults_after[output];
+            assert!(paid_out > 0);
+            assert_eq!(
+                user_after[output] - user_before[output],
+                apply_transfer_fee(paid_out, transfer_fees[output])?
+            );
+        }
+
+        let user_accounts = [user0.token_account0, user0.token_account1];
+        let (user_before, vaults_before) = get_balances(&clmm_test, &svm, user_accounts, vaults)?;
+        let _unused = clmm_test.decrease_liquidity_v2(
+            &mut svm,
+            DecreaseLiquidityV2Params {
+                liquidity: INCREASE_LIQUIDITY,
+                amount0_min: 0,
+                amount1_min: 0,
+                position_nft_mint,
+                recipient_token_account0: user0.token_account0,
+                recipient_token_account1: user0.token_account1,
+            },
+            &user0.keypair,
+        )?;
+        let (user_after, vaults_after) = get_balances(&clmm_test, &svm, user_accounts, vaults)?;
+        for i in 0..2 {
+            let paid_out = vaults_before[i] - vaults_after[i];
+            assert_eq!(
+                user_after[i] - user_before[i],
+                apply_transfer_fee(paid_out, transfer_fees[i])?
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Removes `liquidity` from the position of `user`, returning the amounts
+    /// the program paid out, the position fees included.
+    fn decrease_liquidity(
+        clmm_test: &RaydiumClmmTest,
+        svm: &mut LiteSVM,
+        user: &User,
+        position_nft_mint: Pubkey,
+        liquidity: u128,
+    ) -> Result<[u64; 2], Box<dyn std::error::Error>> {
+        let user_accounts = [user.token_account0, user.token_account1];
+        let vaults = [clmm_test.token_vault0, clmm_test.token_vault1];
+        let (user_before, _) = get_balances(clmm_test, svm, user_accounts, vaults)?;
+        let _unused = clmm_test.decrease_liquidity_v2(
+            svm,
+            DecreaseLiquidityV2Params {
+                liquidity,
+                amount0_min: 0,
+                amount1_min: 0,
+                position_nft_mint,
+                recipient_token_account0: user.token_account0,
+                recipient_token_account1: user.token_account1,
+            },
+            &user.keypair,
+        )?;
+        let (user_after, _) = get_balances(clmm_test, svm, user_accounts, vaults)?;
+
+        Ok([user_after[0] - user_before[0], user_after[1] - user_before[1]])
+    }
+
+    fn get_tick_state(
+        clmm_test: &RaydiumClmmTest,
+        svm: &LiteSVM,
+        tick_index: i32,
+    ) -> Result<TickState, Box<dyn std::error::Error>> {
+        let tick_spacing = clmm_test.fee_config.tick_spacing;
+        let start_tick_index = get_array_start_index(tick_index, tick_spacing);
+        let tick_array = clmm_test.get_tick_array(
+            svm,
+            derive::derive_tick_array_pubkey(
+                clmm_test.pool_state,
+                start_tick_index,
+                Some(clmm_test.program_id),
+            )
+            .0,
+        )?;
+        let offset = ((tick_index - start_tick_index) / i32::from(tick_spacing)) as usize;
+        Ok(tick_array.ticks[offset].clone())
+    }
+
+    fn get_balances(
+        clmm_test: &RaydiumClmmTest,
+        svm: &LiteSVM,
+        user_accounts: [Pubkey; 2],
+        vaults: [Pubkey; 2],
+    ) -> Result<([u64; 2], [u64; 2]), Box<dyn std::error::Error>> {
+        let mut user_balances = [0; 2];
+        let mut vault_balances = [0; 2];
+        for i in 0..2 {
+            user_balances[i] = clmm_test.get_token_account(svm, user_accounts[i])?.amount;
+            vault_balances[i] = clmm_test.get_token_account(svm, vaults[i])?.amount;
+        }
+        Ok((user_balances, vault_balances))
+    }
+
+    /// Previews a swap, sends it, and checks the program moved exactly the
+    /// previewed amounts and left the pool where the preview said, or rejected
+    /// the swap if the preview failed.
+    fn assert_swap_matches_program(
+        clmm_test: &RaydiumClmmTest,
+        svm: &mut LiteSVM,
+        user: &User,
+        zero_for_one: bool,
+        is_base_input: bool,
+        amount: u64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let params = SwapV2Params {
+            amount,
+            other_amount_threshold: if is_base_input { 0 } else { u64::MAX },
+            sqrt_price_limit_x64: 0,
+            is_base_input,
+            zero_for_one,
+            user_token_account0: user.token_account0,
+            user_token_account1: user.token_account1,
+        };
+
+        let Ok(preview) = clmm_test.preview_swap_v2(svm, params.clone()) else {
+            assert!(clmm_test.swap_v2(svm, params, &user.keypair).is_err());
+            return Ok(());
+        };
+
+        let pool_state_before = clmm_test.get_pool_state(svm)?;
+        let token_account0_before = clmm_test.get_token_account(svm, user.token_account0)?;
+        let token_account1_before = clmm_test.get_token_account(svm, user.token_account1)?;
+
+        let _unused = clmm_test.swap_v2(svm, params, &user.keypair)?;
+
+        let pool_state = clmm_test.get_pool_state(svm)?;
+        let token_account0_after = clmm_test.get_token_account(svm, user.token_account0)?;
+        let token_account1_after = clmm_test.get_token_account(svm, user.token_account1)?;
+
+        assert_eq!(pool_state.sqrt_price_x64, preview.sqrt_price_x64_after);
+        assert_eq!(pool_state.tick_current, preview.tick_after);
+        assert_eq!(pool_state.liquidity, preview.liquidity_after);
+
+        let (amount_in, amount_out, protocol_fee, fund_fee) = if zero_for_one {
+            (
+                token_account0_before.amount - token_account0_after.amount,
+                token_account1_after.amount - token_account1_before.amount,
+                pool_state.protocol_fees_token0 - pool_state_before.protocol_fees_token0,
+                pool_state.fund_fees_token0 - pool_state_before.fund_fees_token0,
+            )
+        } else {
+            (
+                token_account1_before.amount - token_account1_after.amount,
+                token_account0_after.amount - token_account0_before.amount,
+                pool_state.protocol_fees_token1 - pool_state_before.protocol_fees_token1,
+                pool_state.fund_fees_token1 - pool_state_before.fund_fees_token1,
+            )
+        };
+        assert_eq!(amount_in, preview.amount_in);
+        assert_eq!(amount_out, preview.amount_out);
+        assert_eq!(protocol_fee, preview.protocol_fee);
+        assert_eq!(fund_fee, preview.fund_fee);
+
+        Ok(())
+    }
+
+    fn open_position_far_from_zero(
+        tick_spacing: u16,
+        tick_lower_index: i32,
+        tick_upper_index: i32,
+        requires_bitmap_extension: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Fixture { clmm_test, mut svm, admin: _, user0, user1: _, user2: _ } =
+            create_fixture_with(FixtureParams {
+                amm_config_params: CreateAmmConfigParams { tick_spacing, ..Default::default() },
+                ..Default::default()
+            })?;
+        update_clock(&mut svm, 1, 1000);
+
+        assert_eq!(
+            is_overflow_default_tickarray_bitmap(
+                tick_spacing,
+                &[tick_lower_index, tick_upper_index]
+            ),
+            requires_bitmap_extension
+        );
+
+        let (position_nft_mint, ..) = clmm_test.open_position_v2(
+            &mut svm,
+            OpenPositionV2Params {
+                tick_lower_index,
+                tick_upper_index,
+                liquidity: FAR_POSITION_LIQUIDITY,
+                amount0_max: MAX_AMOUNT,
+                amount1_max: MAX_AMOUNT,
+                user_token_account0: user0.token_account0,
+                user_token_account1: user0.token_account1,
+            },
+            &user0.keypair,
+        )?;
+
+        let personal_position = clmm_test.get_personal_position_state(&svm, position_nft_mint)?;
+        assert_eq!(personal_position.tick_lower_index, tick_lower_index);
+        assert_eq!(personal_position.tick_upper_index, tick_upper_index);
+        assert_eq!(personal_position.liquidity, FAR_POSITION_LIQUIDITY);
+
+        for tick_index in [tick_lower_index, tick_upper_index] {
+            let start_tick_index = get_array_start_index(tick_index, tick_spacing);
+            let tick_array = clmm_test.get_tick_array(
+                &svm,
+                derive::derive_tick_array_pubkey(
+                    clmm_test.pool_state,
+                    start_tick_index,
+                    Some(clmm_test.program_id),
+                )
+                .0,
+            )?;
+            assert_eq!(tick_array.start_tick_index, start_tick_index);
+
+            let offset = ((tick_index - start_tick_index) / i32::from(tick_spacing)) as usize;
+            assert_eq!(tick_array.ticks[offset].tick, tick_index);
+            assert_eq!(tick_array.ticks[offset].liquidity_gross, FAR_POSITION_LIQUIDITY);
+        }
+
+        Ok(())
+    }
+}
+
+fn create_fixture() -> Result<Fixture, Box<dyn std::error::Error>> {
+    create_fixture_with(FixtureParams { with_positions: true, ..Default::default() })
+}
+
+/// How [`create_fixture_with`] sets up the pool.
+#[derive(Default)]
+struct FixtureParams {
+    amm_config_params: CreateAmmConfigParams,
+    mints_params: CreateMintsParams,
+    /// Opens a position in each of a set of tick ranges around the initial
+    /// price. Without them the pool has no liquidity.
+    with_positions: bool,
+}
+
+fn create_fixture_with(params: FixtureParams) -> Result<Fixture, Box<dyn std::error::Error>> {
+    let program_id = ID;
+    let mut svm = LiteSVM::new().with_sigverify(false);
+    svm.add_program(program_id, include_bytes!("fixtures/raydium_clmm.so"));
+    let metadata_program_id =
+        solana_program::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+    svm.add_program(metadata_program_id, include_bytes!("fixtures/metaplex_metadata.so"));
+
+    let admin = Keypair::new();
+
+    let _unused = svm.airdrop(&admin.pubkey(), 1_000_000_000).unwrap();
+
+    let clmm_test = RaydiumClmmTestBuilder::new()
+        .with_amm_config_params(params.amm_config_params)
+        .with_mints_params(params.mints_params)
+        .build(&mut svm, &admin)
+        .unwrap();
+    let user0 =
+        create_user(&mut svm, &admin, &clmm_test.token_pair.mint0, &clmm_test.token_pair.mint1);
+    let user1 =
+        create_user(&mut svm, &admin, &clmm_test.token_pair.mint0, &clmm_test.token_pair.mint1);
+    let user2 =
+        create_user(&mut svm, &admin, &clmm_test.token_pair.mint0, &clmm_test.token_pair.mint1);
+
+    if params.with_positions {
+        // Define different tick ranges
+        let tick_ranges =
+            [(-120, -60), (-60, -30), (-10, 0), (0, 23), (1, 50), (-30, 30), (30, 60), (60, 120)];
 
-        position_nft_mints.push(position_nft_mint);
+        // Open positions in different ranges
+        for (tick_lower, tick_upper) in tick_ranges {
+            let (position_nft_mint, ..) = clmm_test.open_position_v2(
+                &mut svm,
+                OpenPositionV2Params {
+                    tick_lower_index: tick_lower,
+                    tick_upper_index: tick_upper,
+                    liquidity: 1_000_000,
+                    amount0_max: MAX_AMOUNT,
+                    amount1_max: MAX_AMOUNT,
+                    user_token_account0: user0.token_account0,
+                    user_token_account1: user0.token_account1,
+                },
+                &user0.keypair,
+            )?;
+            update_clock(&mut svm, 1, 1000);
+
+            let _unused = clmm_test.increase_liquidity_v2(
+                &mut svm,
+                IncreaseLiquidityV2Params {
+                    liquidity: 1_000_000,
+                    amount0_max: MAX_AMOUNT,
+                    amount1_max: MAX_AMOUNT,
+                    user_token_account0: user0.token_account0,
+                    user_token_account1: user0.token_account1,
+                    position_nft_mint,
+                },
+                &user0.keypair,
+            )?;
+        }
     }
 
     Ok(Fixture { clmm_test, svm, admin, user0, user1, user2 })
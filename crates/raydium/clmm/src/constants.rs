@@ -15,6 +15,8 @@ pub const TICK_ARRAY_BITMAP_SIZE: i32 = 512;
 
 pub const REWARD_NUM: usize = 3;
 
+pub const OBSERVATION_NUM: usize = 100;
+
 pub const FEE_RATE_DENOMINATOR_VALUE: u32 = 1_000_000;
 
 pub const EXTENSION_TICKARRAY_BITMAP_SIZE: usize = 14;
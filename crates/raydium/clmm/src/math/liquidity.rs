@@ -33,6 +33,142 @@ pub fn add_delta(x: u128, y: i128) -> Result<u128> {
     Ok(z)
 }
 
+/// Computes the amount of liquidity received for a given amount of token_0 and
+/// price range
+///
+/// # Formula
+///
+/// * `ΔL = Δx * (√P_upper * √P_lower) / (√P_upper - √P_lower)`
+pub fn get_liquidity_from_amount_0(
+    mut sqrt_ratio_a_x64: u128,
+    mut sqrt_ratio_b_x64: u128,
+    amount_0: u64,
+) -> Result<u128> {
+    // sqrt_ratio_a_x64 should hold the smaller value
+    if sqrt_ratio_a_x64 > sqrt_ratio_b_x64 {
+        std::mem::swap(&mut sqrt_ratio_a_x64, &mut sqrt_ratio_b_x64);
+    };
+    if sqrt_ratio_a_x64 == sqrt_ratio_b_x64 {
+        return Ok(0);
+    }
+
+    let intermediate = U256::from(sqrt_ratio_a_x64)
+        .mul_div_floor(U256::from(sqrt_ratio_b_x64), U256::from(fixed_point_64::Q64))
+        .ok_or(LiquidityError::MaxLiquidityOverflow)?;
+    let result = U256::from(amount_0)
+        .mul_div_floor(intermediate, U256::from(sqrt_ratio_b_x64 - sqrt_ratio_a_x64))
+        .ok_or(LiquidityError::MaxLiquidityOverflow)?;
+    if result > U256::from(u128::MAX) {
+        return Err(LiquidityError::MaxLiquidityOverflow);
+    }
+    Ok(result.as_u128())
+}
+
+/// Computes the amount of liquidity received for a given amount of token_1 and
+/// price range
+///
+/// # Formula
+///
+/// * `ΔL = Δy / (√P_upper - √P_lower)`
+pub fn get_liquidity_from_amount_1(
+    mut sqrt_ratio_a_x64: u128,
+    mut sqrt_ratio_b_x64: u128,
+    amount_1: u64,
+) -> Result<u128> {
+    // sqrt_ratio_a_x64 should hold the smaller value
+    if sqrt_ratio_a_x64 > sqrt_ratio_b_x64 {
+        std::mem::swap(&mut sqrt_ratio_a_x64, &mut sqrt_ratio_b_x64);
+    };
+    if sqrt_ratio_a_x64 == sqrt_ratio_b_x64 {
+        return Ok(0);
+    }
+
+    let result = U256::from(amount_1)
+        .mul_div_floor(
+            U256::from(fixed_point_64::Q64),
+            U256::from(sqrt_ratio_b_x64 - sqrt_ratio_a_x64),
+        )
+        .ok_or(LiquidityError::MaxLiquidityOverflow)?;
+    if result > U256::from(u128::MAX) {
+        return Err(LiquidityError::MaxLiquidityOverflow);
+    }
+    Ok(result.as_u128())
+}
+
+/// Computes the maximum amount of liquidity received for a given amount of
+/// token_0, token_1, the current pool prices and the prices at the tick
+/// boundaries
+pub fn get_liquidity_from_amounts(
+    sqrt_ratio_x64: u128,
+    mut sqrt_ratio_a_x64: u128,
+    mut sqrt_ratio_b_x64: u128,
+    amount_0: u64,
+    amount_1: u64,
+) -> Result<u128> {
+    // sqrt_ratio_a_x64 should hold the smaller value
+    if sqrt_ratio_a_x64 > sqrt_ratio_b_x64 {
+        std::mem::swap(&mut sqrt_ratio_a_x64, &mut sqrt_ratio_b_x64);
+    };
+
+    if sqrt_ratio_x64 <= sqrt_ratio_a_x64 {
+        // If P ≤ P_lower, only token_0 liquidity is active
+        get_liquidity_from_amount_0(sqrt_ratio_a_x64, sqrt_ratio_b_x64, amount_0)
+    } else if sqrt_ratio_x64 < sqrt_ratio_b_x64 {
+        // If P_lower < P < P_upper, active liquidity is the minimum of the liquidity
+        // provided by token_0 and token_1
+        let liquidity_0 = get_liquidity_from_amount_0(sqrt_ratio_x64, sqrt_ratio_b_x64, amount_0)?;
+        let liquidity_1 = get_liquidity_from_amount_1(sqrt_ratio_a_x64, sqrt_ratio_x64, amount_1)?;
+        Ok(u128::min(liquidity_0, liquidity_1))
+    } else {
+        // If P ≥ P_upper, only token_1 liquidity is active
+        get_liquidity_from_amount_1(sqrt_ratio_a_x64, sqrt_ratio_b_x64, amount_1)
+    }
+}
+
+/// Computes the amount of liquidity received for a given amount of token_0
+/// only, the current pool prices and the prices at the tick boundaries
+pub fn get_liquidity_from_single_amount_0(
+    sqrt_ratio_x64: u128,
+    mut sqrt_ratio_a_x64: u128,
+    mut sqrt_ratio_b_x64: u128,
+    amount_0: u64,
+) -> Result<u128> {
+    // sqrt_ratio_a_x64 should hold the smaller value
+    if sqrt_ratio_a_x64 > sqrt_ratio_b_x64 {
+        std::mem::swap(&mut sqrt_ratio_a_x64, &mut sqrt_ratio_b_x64);
+    };
+
+    if sqrt_ratio_x64 <= sqrt_ratio_a_x64 {
+        get_liquidity_from_amount_0(sqrt_ratio_a_x64, sqrt_ratio_b_x64, amount_0)
+    } else if sqrt_ratio_x64 < sqrt_ratio_b_x64 {
+        get_liquidity_from_amount_0(sqrt_ratio_x64, sqrt_ratio_b_x64, amount_0)
+    } else {
+        Ok(0)
+    }
+}
+
+/// Computes the amount of liquidity received for a given amount of token_1
+/// only, the current pool prices and the prices at the tick boundaries
+pub fn get_liquidity_from_single_amount_1(
+    sqrt_ratio_x64: u128,
+    mut sqrt_ratio_a_x64: u128,
+    mut sqrt_ratio_b_x64: u128,
+    amount_1: u64,
+) -> Result<u128> {
+    // sqrt_ratio_a_x64 should hold the smaller value
+    if sqrt_ratio_a_x64 > sqrt_ratio_b_x64 {
+        std::mem::swap(&mut sqrt_ratio_a_x64, &mut sqrt_ratio_b_x64);
+    };
+
+    if sqrt_ratio_x64 <= sqrt_ratio_a_x64 {
+        Ok(0)
+    } else if sqrt_ratio_x64 < sqrt_ratio_b_x64 {
+        get_liquidity_from_amount_1(sqrt_ratio_a_x64, sqrt_ratio_x64, amount_1)
+    } else {
+        get_liquidity_from_amount_1(sqrt_ratio_a_x64, sqrt_ratio_b_x64, amount_1)
+    }
+}
+
 /// Gets the delta amount_0 for given liquidity and price range
 ///
 /// # Formula
@@ -194,6 +330,9 @@ pub enum LiquidityError {
     #[error("Max token overflow")]
     MaxTokenOverflow,
 
+    #[error("Max liquidity overflow")]
+    MaxLiquidityOverflow,
+
     #[error("Liquidity add value error")]
     LiquidityAddValue,
 
@@ -205,3 +344,57 @@ pub enum LiquidityError {
 }
 
 pub type Result<T> = std::result::Result<T, LiquidityError>;
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::math::tick::{MAX_SQRT_PRICE_X64, MIN_SQRT_PRICE_X64};
+
+    proptest! {
+        #[test]
+        fn liquidity_from_amount_0_never_exceeds_amount(
+            sqrt_ratio_a_x64 in MIN_SQRT_PRICE_X64..MAX_SQRT_PRICE_X64,
+            sqrt_ratio_b_x64 in MIN_SQRT_PRICE_X64..MAX_SQRT_PRICE_X64,
+            amount_0 in 1..u64::MAX,
+        ) {
+            prop_assume!(sqrt_ratio_a_x64 != sqrt_ratio_b_x64);
+
+            if let Ok(liquidity) =
+                get_liquidity_from_amount_0(sqrt_ratio_a_x64, sqrt_ratio_b_x64, amount_0)
+            {
+                let amount = get_delta_amount_0_unsigned(
+                    sqrt_ratio_a_x64,
+                    sqrt_ratio_b_x64,
+                    liquidity,
+                    false,
+                )
+                .unwrap();
+                prop_assert!(amount <= amount_0);
+            }
+        }
+
+        #[test]
+        fn liquidity_from_amount_1_never_exceeds_amount(
+            sqrt_ratio_a_x64 in MIN_SQRT_PRICE_X64..MAX_SQRT_PRICE_X64,
+            sqrt_ratio_b_x64 in MIN_SQRT_PRICE_X64..MAX_SQRT_PRICE_X64,
+            amount_1 in 1..u64::MAX,
+        ) {
+            prop_assume!(sqrt_ratio_a_x64 != sqrt_ratio_b_x64);
+
+            if let Ok(liquidity) =
+                get_liquidity_from_amount_1(sqrt_ratio_a_x64, sqrt_ratio_b_x64, amount_1)
+            {
+                let amount = get_delta_amount_1_unsigned(
+                    sqrt_ratio_a_x64,
+                    sqrt_ratio_b_x64,
+                    liquidity,
+                    false,
+                )
+                .unwrap();
+                prop_assert!(amount <= amount_1);
+            }
+        }
+    }
+}
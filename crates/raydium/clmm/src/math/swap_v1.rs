@@ -1,7 +1,12 @@
-use std::{collections::VecDeque, ops::Neg};
+//! Legacy swap engine. [`super::swap_v2`] is the engine following the
+//! program, this one is only kept to be fuzzed against it until it is retired.
 
-use thiserror::Error;
+use std::{collections::VecDeque, ops::Neg};
 
+use super::swap_v2::{
+    calculate_target_price, handle_uninitialized_tick, process_next_tick, swap_step::SwapStep,
+    Result, SwapError, MAX_SWAP_STEP_COUNT,
+};
 use crate::{
     constants::FEE_RATE_DENOMINATOR_VALUE,
     generated::{
@@ -11,19 +16,6 @@ use crate::{
     math::{full_math::MulDiv, liquidity, sqrt_price, tick},
 };
 
-pub const MAX_SWAP_STEP_COUNT: u32 = 88;
-
-/// Result of a swap step
-#[derive(Default, Debug)]
-pub struct SwapStep {
-    /// The price after swapping the amount in/out, not to exceed the price
-    /// target
-    pub sqrt_price_next_x64: u128,
-    pub amount_in: u64,
-    pub amount_out: u64,
-    pub fee_amount: u64,
-}
-
 /// Computes the result of swapping some amount in, or amount out, given the
 /// parameters of the swap
 pub fn compute_swap_step(
@@ -105,8 +97,7 @@ pub fn compute_swap_step(
                 sqrt_price_current_x64,
                 liquidity,
                 true,
-            )
-            .unwrap();
+            )?;
         };
         // if max is reached for exact output case, entire amount_out is needed
         if !(max && !is_base_input) {
@@ -115,8 +106,7 @@ pub fn compute_swap_step(
                 sqrt_price_current_x64,
                 liquidity,
                 false,
-            )
-            .unwrap();
+            )?;
         };
     } else {
         if !(max && is_base_input) {
@@ -125,8 +115,7 @@ pub fn compute_swap_step(
                 swap_step.sqrt_price_next_x64,
                 liquidity,
                 true,
-            )
-            .unwrap();
+            )?;
         };
         if !(max && !is_base_input) {
             swap_step.amount_out = liquidity::get_delta_amount_0_unsigned(
@@ -134,8 +123,7 @@ pub fn compute_swap_step(
                 swap_step.sqrt_price_next_x64,
                 liquidity,
                 false,
-            )
-            .unwrap();
+            )?;
         };
     }
 
@@ -161,9 +149,6 @@ pub fn compute_swap_step(
     Ok(swap_step)
 }
 
-/// Computes the result of swapping some amount in, or amount out, given the
-/// parameters of the swap
-
 /// Pre calcumate amount_in or amount_out for the specified price range
 /// The amount maybe overflow of u64 due to the `sqrt_price_target_x64` maybe
 /// unreasonable. Therefore, this situation needs to be handled in
@@ -277,6 +262,7 @@ pub fn compute_swap(
 
     let mut tick_array_indices = VecDeque::new();
     tick_array_indices.push_back(tick_array_current.start_tick_index);
+    let mut current_tick_start = current_vaild_tick_array_start_index;
 
     // Main swap loop
     let mut loop_count = 0;
@@ -293,7 +279,7 @@ pub fn compute_swap(
 
         // Get next initialized tick
         let mut next_tick = process_next_tick(
-            &mut tick_array_current,
+            &tick_array_current,
             &mut tick_match_current_tick_array,
             state.tick,
             pool_state,
@@ -302,11 +288,11 @@ pub fn compute_swap(
 
         // Handle uninitialized tick
         if !next_tick.is_initialized() {
-            (tick_array_current, next_tick) = handle_uninitialized_tick(
+            (tick_array_current, next_tick, current_tick_start) = handle_uninitialized_tick(
                 tick_arrays,
                 pool_state,
                 tickarray_bitmap_extension,
-                current_vaild_tick_array_start_index,
+                current_tick_start,
                 zero_for_one,
                 &mut tick_array_indices,
             )?;
@@ -347,201 +333,6 @@ pub fn compute_swap(
     Ok((state, tick_array_indices))
 }
 
-pub fn compute_swap_by_specified_sqrt_price(
-    is_pool_current_tick_array: bool,
-    fee: u32,
-    current_vaild_tick_array_start_index: i32,
-    sqrt_price: u128,
-    pool_state: &PoolState,
-    tickarray_bitmap_extension: &TickArrayBitmapExtension,
-    tick_arrays: &mut VecDeque<TickArrayState>,
-) -> Result<(SwapState, VecDeque<i32>)> {
-    if sqrt_price == pool_state.sqrt_price_x64 {
-        return Ok((
-            SwapState {
-                amount_specified_remaining: 0,
-                amount_calculated: 0,
-                sqrt_price_x64: pool_state.sqrt_price_x64,
-                tick: pool_state.tick_current,
-                liquidity: pool_state.liquidity,
-            },
-            VecDeque::new(),
-        ));
-    }
-
-    const IS_BASE_INPUT: bool = true;
-
-    let zero_for_one = sqrt_price < pool_state.sqrt_price_x64;
-
-    // Check if target price is valid based on swap direction
-    if zero_for_one {
-        if sqrt_price < tick::MIN_SQRT_PRICE_X64 {
-            return Err(SwapError::SqrtPriceLimitX64TooSmall);
-        }
-    } else {
-        if sqrt_price > tick::MAX_SQRT_PRICE_X64 {
-            return Err(SwapError::SqrtPriceLimitX64TooLarge);
-        }
-    }
-
-    // Initialize swap state with maximum amount for price-based swap
-    let mut state = SwapState {
-        amount_specified_remaining: u64::MAX,
-        amount_calculated: 0,
-        sqrt_price_x64: pool_state.sqrt_price_x64,
-        tick: pool_state.tick_current,
-        liquidity: pool_state.liquidity,
-    };
-
-    // Initialize tick array tracking
-    let mut tick_array_current = tick_arrays.pop_front().ok_or(SwapError::NoTickArrayAvailable)?;
-
-    if tick_array_current.start_tick_index != current_vaild_tick_array_start_index {
-        return Err(SwapError::TickArrayStartTickIndexDoesNotMatch);
-    }
-
-    let mut tick_array_indices = VecDeque::from([tick_array_current.start_tick_index]);
-    let mut tick_match_current_tick_array = is_pool_current_tick_array;
-
-    // Main swap loop - continue until target price is reached
-    for loop_count in 0..MAX_SWAP_STEP_COUNT + 1 {
-        if state.sqrt_price_x64 == sqrt_price
-            || state.tick.clamp(tick::MIN_TICK, tick::MAX_TICK) != state.tick
-        {
-            break;
-        }
-        if loop_count == MAX_SWAP_STEP_COUNT {
-            return Err(SwapError::LoopCountLimit);
-        }
-        // Process current step
-        let mut step =
-            StepComputations { sqrt_price_start_x64: state.sqrt_price_x64, ..Default::default() };
-
-        // Get and process next tick
-        let mut next_tick = process_next_tick(
-            &mut tick_array_current,
-            &mut tick_match_current_tick_array,
-            state.tick,
-            pool_state,
-            zero_for_one,
-        )?;
-
-        // Handle case when tick is not initialized
-        if !next_tick.is_initialized() {
-            (tick_array_current, next_tick) = handle_uninitialized_tick(
-                tick_arrays,
-                pool_state,
-                tickarray_bitmap_extension,
-                current_vaild_tick_array_start_index,
-                zero_for_one,
-                &mut tick_array_indices,
-            )?;
-        }
-
-        // Calculate prices and execute swap step
-        step.tick_next = next_tick.tick.clamp(tick::MIN_TICK, tick::MAX_TICK);
-        step.initialized = next_tick.is_initialized();
-        step.sqrt_price_next_x64 = tick::get_sqrt_price_at_tick(step.tick_next)?;
-
-        let target_price =
-            calculate_target_price(zero_for_one, step.sqrt_price_next_x64, sqrt_price);
-        // Execute swap step and update state
-        let swap_result = compute_swap_step(
-            state.sqrt_price_x64,
-            target_price,
-            state.liquidity,
-            state.amount_specified_remaining,
-            fee,
-            IS_BASE_INPUT,
-            zero_for_one,
-        )?;
-        println!("swap_result: {:?}", swap_result);
-
-        update_state_from_swap(
-            &mut state,
-            &swap_result,
-            &mut step,
-            IS_BASE_INPUT,
-            zero_for_one,
-            &next_tick,
-        )?;
-        println!("state: {:?}", state);
-    }
-
-    Ok((state, tick_array_indices))
-}
-
-// Helper functions to break down the complexity
-fn process_next_tick(
-    tick_array: &TickArrayState,
-    tick_match_current: &mut bool,
-    current_tick: i32,
-    pool_state: &PoolState,
-    zero_for_one: bool,
-) -> Result<Box<TickState>> {
-    match tick_array
-        .next_initialized_tick(current_tick, pool_state.tick_spacing, zero_for_one)
-        .map_err(|_| SwapError::TickState)?
-    {
-        Some(tick_state) => Ok(Box::new(tick_state)),
-        None if !*tick_match_current => {
-            *tick_match_current = true;
-            Ok(Box::new(
-                tick_array
-                    .first_initialized_tick(zero_for_one)
-                    .map_err(|_| SwapError::TickState)?,
-            ))
-        }
-        None => Ok(Box::new(TickState {
-            tick: 0,
-            liquidity_net: 0,
-            liquidity_gross: 0,
-            fee_growth_outside0_x64: 0,
-            fee_growth_outside1_x64: 0,
-            reward_growths_outside_x64: [0; 3],
-            padding: [0; 13],
-        })),
-    }
-}
-
-fn handle_uninitialized_tick(
-    tick_arrays: &mut VecDeque<TickArrayState>,
-    pool_state: &PoolState,
-    bitmap_extension: &TickArrayBitmapExtension,
-    current_index: i32,
-    zero_for_one: bool,
-    indices: &mut VecDeque<i32>,
-) -> Result<(TickArrayState, Box<TickState>)> {
-    let next_index = pool_state
-        .next_initialized_tick_array_start_index(
-            &Some(bitmap_extension.clone()),
-            current_index,
-            zero_for_one,
-        )
-        .map_err(|_| SwapError::PoolState)?
-        .ok_or(SwapError::TickArrayStartTickIndexOutOfRangeLimit)?;
-
-    let next_array = tick_arrays.pop_front().ok_or(SwapError::NoMoreTickArraysAvailable)?;
-
-    if next_array.start_tick_index != next_index {
-        return Err(SwapError::TickArrayStartTickIndexDoesNotMatch);
-    }
-
-    indices.push_back(next_array.start_tick_index);
-    let first_tick =
-        next_array.first_initialized_tick(zero_for_one).map_err(|_| SwapError::TickState)?;
-
-    Ok((next_array, Box::new(first_tick)))
-}
-
-fn calculate_target_price(zero_for_one: bool, next_price: u128, limit_price: u128) -> u128 {
-    if (zero_for_one && next_price < limit_price) || (!zero_for_one && next_price > limit_price) {
-        limit_price
-    } else {
-        next_price
-    }
-}
-
 fn update_state_from_swap(
     state: &mut SwapState,
     swap_result: &SwapStep,
@@ -622,50 +413,3 @@ struct StepComputations {
     // how much fee is being paid in
     fee_amount: u64,
 }
-
-#[derive(Debug, Error)]
-pub enum SwapError {
-    #[error("Liquidity error: {0}")]
-    Liquidity(#[from] liquidity::LiquidityError),
-
-    #[error("Tick error: {0}")]
-    Tick(#[from] tick::TickError),
-
-    #[error("Tick state error")]
-    TickState,
-
-    #[error("Pool state error")]
-    PoolState,
-
-    #[error("Math overflow")]
-    MathOverflow,
-
-    #[error("Amount specified zero")]
-    AmountSpecifiedZero,
-
-    #[error("Sqrt price limit x64 too small")]
-    SqrtPriceLimitX64TooSmall,
-
-    #[error("Sqrt price limit x64 too large")]
-    SqrtPriceLimitX64TooLarge,
-
-    #[error("Tick array start tick index does not match")]
-    TickArrayStartTickIndexDoesNotMatch,
-
-    #[error("Tick array start tick index out of range limit")]
-    TickArrayStartTickIndexOutOfRangeLimit,
-
-    #[error("Loop count limit")]
-    LoopCountLimit,
-
-    #[error("No tick array available")]
-    NoTickArrayAvailable,
-
-    #[error("No more tick arrays available")]
-    NoMoreTickArraysAvailable,
-
-    #[error("Tick state not initialized")]
-    TickStateNotInitialized,
-}
-
-pub type Result<T> = std::result::Result<T, SwapError>;
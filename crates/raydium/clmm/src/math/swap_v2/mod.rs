@@ -14,6 +14,8 @@ use crate::{
 
 pub const MAX_SWAP_STEP_COUNT: u32 = 88;
 
+/// Simulates a swap the same way the program does. `sqrt_price_limit_x64`
+/// defaults to the price bound of the swap direction when it is 0.
 pub fn compute_swap(
     zero_for_one: bool,
     is_base_input: bool,
@@ -26,12 +28,10 @@ pub fn compute_swap(
     tickarray_bitmap_extension: &TickArrayBitmapExtension,
     tick_arrays: &mut VecDeque<TickArrayState>,
 ) -> Result<(SwapState, VecDeque<i32>)> {
-    // Input validation
     if amount_specified == 0 {
         return Err(SwapError::AmountSpecifiedZero);
     }
 
-    // Set price limit
     let sqrt_price_limit_x64 = if sqrt_price_limit_x64 == 0 {
         if zero_for_one {
             tick::MIN_SQRT_PRICE_X64 + 1
@@ -42,128 +42,37 @@ pub fn compute_swap(
         sqrt_price_limit_x64
     };
 
-    // Validate price limits
-    match zero_for_one {
-        true if sqrt_price_limit_x64 < tick::MIN_SQRT_PRICE_X64 => {
-            return Err(SwapError::SqrtPriceLimitX64TooSmall)
-        }
-        true if sqrt_price_limit_x64 >= pool_state.sqrt_price_x64 => {
-            return Err(SwapError::SqrtPriceLimitX64TooLarge)
-        }
-        false if sqrt_price_limit_x64 > tick::MAX_SQRT_PRICE_X64 => {
-            return Err(SwapError::SqrtPriceLimitX64TooLarge)
-        }
-        false if sqrt_price_limit_x64 <= pool_state.sqrt_price_x64 => {
-            return Err(SwapError::SqrtPriceLimitX64TooSmall)
-        }
-        _ => {}
+    if zero_for_one && sqrt_price_limit_x64 >= pool_state.sqrt_price_x64 {
+        return Err(SwapError::SqrtPriceLimitX64TooLarge);
     }
-
-    // Initialize state
-    let mut tick_match_current_tick_array = is_pool_current_tick_array;
-
-    let mut cache = SwapCache {
-        sqrt_price_x64: pool_state.sqrt_price_x64,
-        tick: pool_state.tick_current,
-        liquidity: pool_state.liquidity,
-        initialized: true,
-        amount_in: 0,
-        amount_out: 0,
-        fee_amount: 0,
-        remaining_amount: amount_specified,
-        amount_calculated: 0,
-    };
-
-    // Setup initial tick array
-    let mut tick_array_current = tick_arrays.pop_front().ok_or(SwapError::NoTickArrayAvailable)?;
-
-    if tick_array_current.start_tick_index != current_vaild_tick_array_start_index {
-        return Err(SwapError::TickArrayStartTickIndexDoesNotMatch);
+    if !zero_for_one && sqrt_price_limit_x64 <= pool_state.sqrt_price_x64 {
+        return Err(SwapError::SqrtPriceLimitX64TooSmall);
     }
-
-    let mut tick_array_indices = VecDeque::new();
-    tick_array_indices.push_back(tick_array_current.start_tick_index);
-
-    let mut current_tick_start = current_vaild_tick_array_start_index;
-
-    // Main swap loop - continue until target price is reached
-    for loop_count in 0..MAX_SWAP_STEP_COUNT + 1 {
-        if cache.sqrt_price_x64 == sqrt_price_limit_x64
-            || cache.tick.clamp(tick::MIN_TICK, tick::MAX_TICK) != cache.tick
-        {
-            break;
-        }
-
-        if cache.remaining_amount == 0 {
-            break;
-        }
-
-        if loop_count == MAX_SWAP_STEP_COUNT {
-            return Err(SwapError::LoopCountLimit);
-        }
-
-        // Get and process next tick
-        let mut next_tick_state = process_next_tick(
-            &mut tick_array_current,
-            &mut tick_match_current_tick_array,
-            cache.tick,
-            pool_state,
-            zero_for_one,
-        )?;
-
-        // Handle case when tick is not initialized
-        if !next_tick_state.is_initialized() {
-            (tick_array_current, next_tick_state, current_tick_start) = handle_uninitialized_tick(
-                tick_arrays,
-                pool_state,
-                tickarray_bitmap_extension,
-                current_tick_start,
-                zero_for_one,
-                &mut tick_array_indices,
-            )?;
-        }
-
-        // Calculate prices and execute swap step
-        let next_tick = next_tick_state.tick.clamp(tick::MIN_TICK, tick::MAX_TICK);
-        let next_sqrt_price_x64 = tick::get_sqrt_price_at_tick(next_tick)?;
-
-        if next_sqrt_price_x64 == cache.sqrt_price_x64 {
-            return Err(SwapError::InvalidNextSqrtPriceX64);
-        }
-
-        let target_price =
-            calculate_target_price(zero_for_one, next_sqrt_price_x64, sqrt_price_limit_x64);
-        // Execute swap step and update state
-        let swap_result = swap_step::compute_swap_step(
-            cache.sqrt_price_x64,
-            target_price,
-            cache.liquidity,
-            cache.remaining_amount,
-            fee,
-            is_base_input,
-            zero_for_one,
-        )?;
-        update_cache_from_swap_step(
-            &mut cache,
-            &swap_result,
-            zero_for_one,
-            &next_tick_state,
-            is_base_input,
-        )?;
+    check_sqrt_price_limit(sqrt_price_limit_x64, zero_for_one)?;
+
+    let (state, tick_array_indices) = swap_internal(
+        zero_for_one,
+        is_base_input,
+        is_pool_current_tick_array,
+        fee,
+        amount_specified,
+        current_vaild_tick_array_start_index,
+        sqrt_price_limit_x64,
+        pool_state,
+        tickarray_bitmap_extension,
+        tick_arrays,
+    )?;
+
+    // Like the program, reject swaps where either side rounds to nothing
+    if state.remaining_amount == amount_specified || state.amount_calculated == 0 {
+        return Err(SwapError::TooSmallInputOrOutputAmount);
     }
 
-    let state = SwapState {
-        amount_in: cache.amount_in,
-        amount_out: cache.amount_out,
-        sqrt_price_x64: cache.sqrt_price_x64,
-        tick: cache.tick,
-        liquidity: cache.liquidity,
-        remaining_amount: cache.remaining_amount,
-        amount_calculated: cache.amount_calculated,
-    };
     Ok((state, tick_array_indices))
 }
 
+/// Simulates an exact input swap moving the pool price to `sqrt_price`, which
+/// gives the amounts needed to reach that price.
 pub fn compute_swap_by_specified_sqrt_price(
     is_pool_current_tick_array: bool,
     fee: u32,
@@ -176,73 +85,85 @@ pub fn compute_swap_by_specified_sqrt_price(
     if sqrt_price == pool_state.sqrt_price_x64 {
         return Ok((
             SwapState {
-                amount_in: 0,
-                amount_out: 0,
                 sqrt_price_x64: pool_state.sqrt_price_x64,
                 tick: pool_state.tick_current,
                 liquidity: pool_state.liquidity,
                 remaining_amount: u64::MAX,
-                amount_calculated: 0,
+                ..Default::default()
             },
             VecDeque::new(),
         ));
     }
 
-    const IS_BASE_INPUT: bool = true;
     let zero_for_one = sqrt_price < pool_state.sqrt_price_x64;
+    check_sqrt_price_limit(sqrt_price, zero_for_one)?;
+
+    swap_internal(
+        zero_for_one,
+        true,
+        is_pool_current_tick_array,
+        fee,
+        u64::MAX,
+        current_vaild_tick_array_start_index,
+        sqrt_price,
+        pool_state,
+        tickarray_bitmap_extension,
+        tick_arrays,
+    )
+}
 
-    // Check if target price is valid based on swap direction
-    let _ = check_sqrt_price_limit(sqrt_price, zero_for_one)?;
+/// The swap loop of the program, without the account updates.
+#[allow(clippy::too_many_arguments)]
+fn swap_internal(
+    zero_for_one: bool,
+    is_base_input: bool,
+    is_pool_current_tick_array: bool,
+    fee: u32,
+    amount_specified: u64,
+    current_vaild_tick_array_start_index: i32,
+    sqrt_price_limit_x64: u128,
+    pool_state: &PoolState,
+    tickarray_bitmap_extension: &TickArrayBitmapExtension,
+    tick_arrays: &mut VecDeque<TickArrayState>,
+) -> Result<(SwapState, VecDeque<i32>)> {
+    let mut state = SwapState {
+        sqrt_price_x64: pool_state.sqrt_price_x64,
+        tick: pool_state.tick_current,
+        liquidity: pool_state.liquidity,
+        remaining_amount: amount_specified,
+        ..Default::default()
+    };
 
-    // Initialize tick array tracking
+    // Like the program, skip the tick arrays before the first initialized one
     let mut tick_array_current = tick_arrays.pop_front().ok_or(SwapError::NoTickArrayAvailable)?;
-
-    if tick_array_current.start_tick_index != current_vaild_tick_array_start_index {
-        return Err(SwapError::TickArrayStartTickIndexDoesNotMatch);
+    while tick_array_current.start_tick_index != current_vaild_tick_array_start_index {
+        tick_array_current =
+            tick_arrays.pop_front().ok_or(SwapError::TickArrayStartTickIndexDoesNotMatch)?;
     }
 
     let mut tick_array_indices = VecDeque::from([tick_array_current.start_tick_index]);
     let mut tick_match_current_tick_array = is_pool_current_tick_array;
-
-    let mut cache = SwapCache {
-        sqrt_price_x64: pool_state.sqrt_price_x64,
-        tick: pool_state.tick_current,
-        liquidity: pool_state.liquidity,
-        amount_in: 0,
-        amount_out: 0,
-        fee_amount: 0,
-        remaining_amount: u64::MAX,
-        initialized: true,
-        amount_calculated: 0,
-    };
-
     let mut current_tick_start = current_vaild_tick_array_start_index;
 
-    // Main swap loop - continue until target price is reached
-    for loop_count in 0..MAX_SWAP_STEP_COUNT + 1 {
-        if cache.sqrt_price_x64 == sqrt_price
-            || cache.tick.clamp(tick::MIN_TICK, tick::MAX_TICK) != cache.tick
-        {
-            break;
-        }
-
-        if cache.remaining_amount == 0 {
-            break;
-        }
-
+    let mut loop_count = 0;
+    while state.remaining_amount != 0
+        && state.sqrt_price_x64 != sqrt_price_limit_x64
+        && state.tick < tick::MAX_TICK
+        && state.tick > tick::MIN_TICK
+    {
         if loop_count == MAX_SWAP_STEP_COUNT {
             return Err(SwapError::LoopCountLimit);
         }
-        // Get and process next tick
+        let sqrt_price_start_x64 = state.sqrt_price_x64;
+
         let mut next_tick_state = process_next_tick(
-            &mut tick_array_current,
+            &tick_array_current,
             &mut tick_match_current_tick_array,
-            cache.tick,
+            state.tick,
             pool_state,
             zero_for_one,
         )?;
 
-        // Handle case when tick is not initialized
         if !next_tick_state.is_initialized() {
             (tick_array_current, next_tick_state, current_tick_start) = handle_uninitialized_tick(
                 tick_arrays,
@@ -254,60 +175,57 @@ pub fn compute_swap_by_specified_sqrt_price(
             )?;
         }
 
-        // Calculate prices and execute swap step
-        let next_tick = next_tick_state.tick.clamp(tick::MIN_TICK, tick::MAX_TICK);
-        let next_sqrt_price_x64 = tick::get_sqrt_price_at_tick(next_tick)?;
+        let tick_next = next_tick_state.tick.clamp(tick::MIN_TICK, tick::MAX_TICK);
+        let sqrt_price_next_x64 = tick::get_sqrt_price_at_tick(tick_next)?;
+        let target_price =
+            calculate_target_price(zero_for_one, sqrt_price_next_x64, sqrt_price_limit_x64);
 
-        let target_price = calculate_target_price(zero_for_one, next_sqrt_price_x64, sqrt_price);
-        // Execute swap step and update state
-        let swap_result = swap_step::compute_swap_step(
-            cache.sqrt_price_x64,
+        let step = swap_step::compute_swap_step(
+            state.sqrt_price_x64,
             target_price,
-            cache.liquidity,
-            cache.remaining_amount,
+            state.liquidity,
+            state.remaining_amount,
             fee,
-            IS_BASE_INPUT,
+            is_base_input,
             zero_for_one,
         )?;
+        update_state_from_swap_step(&mut state, &step, is_base_input)?;
+
+        // Shift the tick if the next tick price was reached, crossing it if it
+        // is initialized
+        if state.sqrt_price_x64 == sqrt_price_next_x64 {
+            if next_tick_state.is_initialized() {
+                let liquidity_net = if zero_for_one {
+                    next_tick_state.liquidity_net.neg()
+                } else {
+                    next_tick_state.liquidity_net
+                };
+                state.liquidity = liquidity::add_delta(state.liquidity, liquidity_net)?;
+                state.crossed_ticks.push(tick_next);
+            }
+            state.tick = if zero_for_one { tick_next - 1 } else { tick_next };
+        } else if state.sqrt_price_x64 != sqrt_price_start_x64 {
+            state.tick = tick::get_tick_at_sqrt_price(state.sqrt_price_x64)?;
+        }
 
-        update_cache_from_swap_step(
-            &mut cache,
-            &swap_result,
-            zero_for_one,
-            &next_tick_state,
-            IS_BASE_INPUT,
-        )?;
+        loop_count += 1;
     }
 
-    let state = SwapState {
-        amount_in: cache.amount_in,
-        amount_out: cache.amount_out,
-        sqrt_price_x64: cache.sqrt_price_x64,
-        tick: cache.tick,
-        liquidity: cache.liquidity,
-        remaining_amount: cache.remaining_amount,
-        amount_calculated: cache.amount_calculated,
-    };
-    println!("state: {:?}", state);
-
     Ok((state, tick_array_indices))
 }
 
 fn check_sqrt_price_limit(sqrt_price: u128, zero_for_one: bool) -> Result<()> {
-    if zero_for_one {
-        if sqrt_price < tick::MIN_SQRT_PRICE_X64 {
-            return Err(SwapError::SqrtPriceLimitX64TooSmall);
-        }
-    } else {
-        if sqrt_price > tick::MAX_SQRT_PRICE_X64 {
-            return Err(SwapError::SqrtPriceLimitX64TooLarge);
-        }
+    if zero_for_one && sqrt_price <= tick::MIN_SQRT_PRICE_X64 {
+        return Err(SwapError::SqrtPriceLimitX64TooSmall);
+    }
+    if !zero_for_one && sqrt_price >= tick::MAX_SQRT_PRICE_X64 {
+        return Err(SwapError::SqrtPriceLimitX64TooLarge);
     }
     Ok(())
 }
 
 // Helper functions to break down the complexity
-fn process_next_tick(
+pub(super) fn process_next_tick(
     tick_array: &TickArrayState,
     tick_match_current: &mut bool,
     current_tick: i32,
@@ -339,7 +257,9 @@ fn process_next_tick(
     }
 }
 
-fn handle_uninitialized_tick(
+/// Moves to the next initialized tick array, skipping the tick arrays in
+/// between like the program does.
+pub(super) fn handle_uninitialized_tick(
     tick_arrays: &mut VecDeque<TickArrayState>,
     pool_state: &PoolState,
     bitmap_extension: &TickArrayBitmapExtension,
@@ -356,9 +276,9 @@ fn handle_uninitialized_tick(
         .map_err(|_| SwapError::PoolState)?
         .ok_or(SwapError::TickArrayStartTickIndexOutOfRangeLimit)?;
 
-    let next_array = tick_arrays.pop_front().ok_or(SwapError::NoMoreTickArraysAvailable)?;
-    if next_array.start_tick_index != next_index {
-        return Err(SwapError::TickArrayStartTickIndexDoesNotMatch);
+    let mut next_array = tick_arrays.pop_front().ok_or(SwapError::NoMoreTickArraysAvailable)?;
+    while next_array.start_tick_index != next_index {
+        next_array = tick_arrays.pop_front().ok_or(SwapError::NoMoreTickArraysAvailable)?;
     }
 
     indices.push_back(next_array.start_tick_index);
@@ -368,7 +288,11 @@ fn handle_uninitialized_tick(
     Ok((next_array, Box::new(first_tick), next_index))
 }
 
-fn calculate_target_price(zero_for_one: bool, next_price: u128, limit_price: u128) -> u128 {
+pub(super) fn calculate_target_price(
+    zero_for_one: bool,
+    next_price: u128,
+    limit_price: u128,
+) -> u128 {
     if (zero_for_one && next_price < limit_price) || (!zero_for_one && next_price > limit_price) {
         limit_price
     } else {
@@ -376,49 +300,38 @@ fn calculate_target_price(zero_for_one: bool, next_price: u128, limit_price: u12
     }
 }
 
-fn update_cache_from_swap_step(
-    cache: &mut SwapCache,
+fn update_state_from_swap_step(
+    state: &mut SwapState,
     step: &swap_step::SwapStep,
-    zero_for_one: bool,
-    next_tick: &TickState,
     is_base_input: bool,
 ) -> Result<()> {
-    cache.tick = next_tick.tick;
-    cache.initialized = next_tick.is_initialized();
-    cache.sqrt_price_x64 = step.sqrt_price_next_x64;
-
-    cache.amount_in = cache.amount_in.checked_add(step.amount_in).ok_or(SwapError::MathOverflow)?;
-    cache.amount_out =
-        cache.amount_out.checked_add(step.amount_out).ok_or(SwapError::MathOverflow)?;
-
-    cache.fee_amount =
-        cache.fee_amount.checked_add(step.fee_amount).ok_or(SwapError::MathOverflow)?;
-
-    // Update tick and liquidity
-
-    if cache.initialized {
-        let liquidity_net =
-            if zero_for_one { next_tick.liquidity_net.neg() } else { next_tick.liquidity_net };
-        cache.liquidity = liquidity::add_delta(cache.liquidity, liquidity_net)?;
-    }
-
-    if is_base_input {
-        cache.remaining_amount =
-            cache.remaining_amount.checked_sub(step.amount_in).ok_or(SwapError::MathOverflow)?;
-        cache.amount_calculated =
-            cache.amount_calculated.checked_add(step.amount_out).ok_or(SwapError::MathOverflow)?;
+    state.sqrt_price_x64 = step.sqrt_price_next_x64;
+
+    let step_amount_in =
+        step.amount_in.checked_add(step.fee_amount).ok_or(SwapError::MathOverflow)?;
+    state.amount_in = state.amount_in.checked_add(step_amount_in).ok_or(SwapError::MathOverflow)?;
+    state.amount_out =
+        state.amount_out.checked_add(step.amount_out).ok_or(SwapError::MathOverflow)?;
+    state.fee_amount =
+        state.fee_amount.checked_add(step.fee_amount).ok_or(SwapError::MathOverflow)?;
+    state.step_fee_amounts.push(step.fee_amount);
+
+    let (amount_specified, amount_calculated) = if is_base_input {
+        (step_amount_in, step.amount_out)
     } else {
-        cache.remaining_amount =
-            cache.remaining_amount.checked_sub(step.amount_out).ok_or(SwapError::MathOverflow)?;
-        cache.amount_calculated =
-            cache.amount_calculated.checked_add(step.amount_in).ok_or(SwapError::MathOverflow)?;
-    }
+        (step.amount_out, step_amount_in)
+    };
+    state.remaining_amount =
+        state.remaining_amount.checked_sub(amount_specified).ok_or(SwapError::MathOverflow)?;
+    state.amount_calculated =
+        state.amount_calculated.checked_add(amount_calculated).ok_or(SwapError::MathOverflow)?;
 
     Ok(())
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct SwapState {
+    /// The total amount swapped in, the trade fee included.
     pub amount_in: u64,
     pub amount_out: u64,
     pub sqrt_price_x64: u128,
@@ -426,28 +339,14 @@ pub struct SwapState {
     pub liquidity: u128,
     pub remaining_amount: u64,
     pub amount_calculated: u64,
-}
-
-#[derive(Default, Debug)]
-struct SwapCache {
-    // the price at the beginning of the step
-    sqrt_price_x64: u128,
-    // the tick associated with the current price
-    tick: i32,
-    // whether tick_next is initialized or not
-    initialized: bool,
-    // the current liquidity in range
-    liquidity: u128,
-    // how much is being swapped in in this step
-    amount_in: u64,
-    // how much is being swapped out
-    amount_out: u64,
-    // how much fee is being paid in
-    fee_amount: u64,
-    // the remaining amount to be swapped
-    remaining_amount: u64,
-    // the amount calculated
-    amount_calculated: u64,
+    /// The total trade fee paid in the input token, before the protocol and
+    /// fund fees are taken out of it.
+    pub fee_amount: u64,
+    /// The trade fee of every swap step. The program splits the protocol and
+    /// fund fees per step, so the split has to be computed from these.
+    pub step_fee_amounts: Vec<u64>,
+    /// The initialized ticks crossed by the swap, in crossing order.
+    pub crossed_ticks: Vec<i32>,
 }
 
 #[derive(Debug, Error)]
@@ -470,6 +369,9 @@ pub enum SwapError {
     #[error("Amount specified zero")]
     AmountSpecifiedZero,
 
+    #[error("Too small input or output amount")]
+    TooSmallInputOrOutputAmount,
+
     #[error("Sqrt price limit x64 too small")]
     SqrtPriceLimitX64TooSmall,
 
@@ -496,9 +398,316 @@ pub enum SwapError {
 
     #[error("Swap step error: {0}")]
     SwapStep(#[from] swap_step::SwapStepError),
-
-    #[error("Invalid next sqrt price x64")]
-    InvalidNextSqrtPriceX64,
 }
 
 pub type Result<T> = std::result::Result<T, SwapError>;
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::{
+        constants::{FEE_RATE_DENOMINATOR_VALUE, TICK_ARRAY_SIZE},
+        math::swap_v1,
+    };
+
+    const TICK_SPACING: u16 = 1;
+
+    /// A pool of three tick arrays starting at -60, 0 and 60 with the given
+    /// positions opened, the price sitting at `sqrt_price_x64`.
+    fn pool(
+        positions: &[(i32, i32, u128)],
+        sqrt_price_x64: u128,
+    ) -> (PoolState, TickArrayBitmapExtension, Vec<TickArrayState>) {
+        let tick_current = tick::get_tick_at_sqrt_price(sqrt_price_x64).unwrap();
+        let mut pool_state = PoolState::from_bytes(&[0; PoolState::LEN]).unwrap();
+        pool_state.tick_spacing = TICK_SPACING;
+        pool_state.sqrt_price_x64 = sqrt_price_x64;
+        pool_state.tick_current = tick_current;
+
+        let mut tick_arrays: Vec<TickArrayState> = [-TICK_ARRAY_SIZE, 0, TICK_ARRAY_SIZE]
+            .into_iter()
+            .map(|start_tick_index| {
+                let mut tick_array = TickArrayState::from_bytes(&[0; TickArrayState::LEN]).unwrap();
+                tick_array.start_tick_index = start_tick_index;
+                for (i, tick_state) in tick_array.ticks.iter_mut().enumerate() {
+                    tick_state.tick = start_tick_index + i as i32;
+                }
+                tick_array
+            })
+            .collect();
+
+        for (tick_lower, tick_upper, liquidity) in positions {
+            for (tick_index, liquidity_net) in
+                [(*tick_lower, *liquidity as i128), (*tick_upper, -(*liquidity as i128))]
+            {
+                let tick_array = tick_arrays
+                    .iter_mut()
+                    .find(|tick_array| {
+                        (tick_array.start_tick_index..tick_array.start_tick_index + TICK_ARRAY_SIZE)
+                            .contains(&tick_index)
+                    })
+                    .unwrap();
+                let tick_state =
+                    &mut tick_array.ticks[(tick_index - tick_array.start_tick_index) as usize];
+                tick_state.liquidity_gross += liquidity;
+                tick_state.liquidity_net += liquidity_net;
+            }
+            if (*tick_lower..*tick_upper).contains(&tick_current) {
+                pool_state.liquidity += liquidity;
+            }
+        }
+
+        for tick_array in &tick_arrays {
+            if tick_array.ticks.iter().any(|tick_state| tick_state.is_initialized()) {
+                let bit = (tick_array.start_tick_index / TICK_ARRAY_SIZE + 512) as usize;
+                pool_state.tick_array_bitmap[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+
+        let tickarray_bitmap_extension =
+            TickArrayBitmapExtension::from_bytes(&[0; TickArrayBitmapExtension::LEN]).unwrap();
+        (pool_state, tickarray_bitmap_extension, tick_arrays)
+    }
+
+    /// The initialized tick arrays in swap order, like the client loads them.
+    fn swap_tick_arrays(
+        pool_state: &PoolState,
+        tick_arrays: &[TickArrayState],
+        zero_for_one: bool,
+    ) -> VecDeque<TickArrayState> {
+        let mut tick_arrays: Vec<TickArrayState> = tick_arrays
+            .iter()
+            .filter(|tick_array| tick_array.ticks.iter().any(|tick| tick.is_initialized()))
+            .cloned()
+            .collect();
+        if zero_for_one {
+            tick_arrays.reverse();
+        }
+        let start_index = tick::get_array_start_index(pool_state.tick_current, TICK_SPACING);
+        tick_arrays
+            .into_iter()
+            .skip_while(|tick_array| {
+                if zero_for_one {
+                    tick_array.start_tick_index > start_index
+                } else {
+                    tick_array.start_tick_index < start_index
+                }
+            })
+            .collect()
+    }
+
+    fn swap(
+        pool_state: &PoolState,
+        tickarray_bitmap_extension: &TickArrayBitmapExtension,
+        tick_arrays: &[TickArrayState],
+        zero_for_one: bool,
+        is_base_input: bool,
+        amount: u64,
+    ) -> Result<(SwapState, VecDeque<i32>)> {
+        let (is_pool_current_tick_array, start_index) = pool_state
+            .get_first_initialized_tick_array(
+                &Some(tickarray_bitmap_extension.clone()),
+                zero_for_one,
+            )
+            .map_err(|_| SwapError::PoolState)?;
+        compute_swap(
+            zero_for_one,
+            is_base_input,
+            is_pool_current_tick_array,
+            3000,
+            amount,
+            start_index,
+            0,
+            pool_state,
+            tickarray_bitmap_extension,
+            &mut swap_tick_arrays(pool_state, tick_arrays, zero_for_one),
+        )
+    }
+
+    #[test]
+    fn test_swap_crossing_tick_down_leaves_tick_below() {
+        let sqrt_price_x64 = tick::get_sqrt_price_at_tick(5).unwrap();
+        let (pool_state, extension, tick_arrays) =
+            pool(&[(-10, 10, 1_000_000_000), (0, 10, 1_000_000_000)], sqrt_price_x64);
+
+        // Enough to cross tick 0 but not tick -10
+        let (state, _) = swap(&pool_state, &extension, &tick_arrays, true, false, 600_000).unwrap();
+        assert_eq!(state.crossed_ticks, vec![0]);
+        assert_eq!(state.liquidity, 1_000_000_000);
+        assert!(state.tick < 0 && state.tick >= -10);
+
+        // Exactly to the price of tick 0
+        let (state, _) = compute_swap_by_specified_sqrt_price(
+            true,
+            3000,
+            0,
+            tick::get_sqrt_price_at_tick(0).unwrap(),
+            &pool_state,
+            &extension,
+            &mut swap_tick_arrays(&pool_state, &tick_arrays, true),
+        )
+        .unwrap();
+        assert_eq!(state.sqrt_price_x64, tick::get_sqrt_price_at_tick(0).unwrap());
+        assert_eq!(state.tick, -1);
+        assert_eq!(state.crossed_ticks, vec![0]);
+        assert_eq!(state.liquidity, 1_000_000_000);
+    }
+
+    #[test]
+    fn test_swap_within_range_keeps_liquidity() {
+        let sqrt_price_x64 = tick::get_sqrt_price_at_tick(5).unwrap();
+        let (pool_state, extension, tick_arrays) =
+            pool(&[(-10, 10, 1_000_000_000), (0, 10, 1_000_000_000)], sqrt_price_x64);
+
+        let (state, tick_array_indices) =
+            swap(&pool_state, &extension, &tick_arrays, false, true, 1_000).unwrap();
+        assert!(state.crossed_ticks.is_empty());
+        assert_eq!(state.liquidity, 2_000_000_000);
+        assert_eq!(state.remaining_amount, 0);
+        assert_eq!(state.amount_in, 1_000);
+        assert_eq!(tick_array_indices, VecDeque::from([0]));
+    }
+
+    #[test]
+    fn test_swap_rounding_to_zero_is_rejected() {
+        let sqrt_price_x64 = tick::get_sqrt_price_at_tick(5).unwrap();
+        let (pool_state, extension, tick_arrays) =
+            pool(&[(-10, 10, 1_000_000_000)], sqrt_price_x64);
+
+        // The whole input goes to the trade fee
+        assert!(matches!(
+            swap(&pool_state, &extension, &tick_arrays, true, true, 1),
+            Err(SwapError::TooSmallInputOrOutputAmount)
+        ));
+    }
+
+    fn positions() -> impl Strategy<Value = Vec<(i32, i32, u128)>> {
+        prop::collection::vec(
+            (-TICK_ARRAY_SIZE..2 * TICK_ARRAY_SIZE - 1, 1..TICK_ARRAY_SIZE, 1u128 << 30..1 << 48),
+            1..5,
+        )
+        .prop_map(|positions| {
+            positions
+                .into_iter()
+                .map(|(tick_lower, width, liquidity)| {
+                    (tick_lower, (tick_lower + width).min(2 * TICK_ARRAY_SIZE - 1), liquidity)
+                })
+                .collect()
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn compute_swap_step_matches_legacy_engine(
+            sqrt_price_current_x64 in tick::MIN_SQRT_PRICE_X64..tick::MAX_SQRT_PRICE_X64,
+            sqrt_price_target_x64 in tick::MIN_SQRT_PRICE_X64..tick::MAX_SQRT_PRICE_X64,
+            liquidity in 1..u64::MAX as u128,
+            amount_remaining in 1..u64::MAX,
+            fee_rate in 0..FEE_RATE_DENOMINATOR_VALUE / 2,
+            is_base_input in proptest::bool::ANY,
+        ) {
+            prop_assume!(sqrt_price_current_x64 != sqrt_price_target_x64);
+            let zero_for_one = sqrt_price_current_x64 > sqrt_price_target_x64;
+
+            // The legacy engine fails where an amount overflows, the program
+            // and this engine fall back to the price reachable with the amount
+            if let Ok(expected) = swap_v1::compute_swap_step(
+                sqrt_price_current_x64,
+                sqrt_price_target_x64,
+                liquidity,
+                amount_remaining,
+                fee_rate,
+                is_base_input,
+                zero_for_one,
+            ) {
+                let step = swap_step::compute_swap_step(
+                    sqrt_price_current_x64,
+                    sqrt_price_target_x64,
+                    liquidity,
+                    amount_remaining,
+                    fee_rate,
+                    is_base_input,
+                    zero_for_one,
+                )
+                .unwrap();
+                prop_assert_eq!(step.sqrt_price_next_x64, expected.sqrt_price_next_x64);
+                prop_assert_eq!(step.amount_in, expected.amount_in);
+                prop_assert_eq!(step.amount_out, expected.amount_out);
+                prop_assert_eq!(step.fee_amount, expected.fee_amount);
+            }
+        }
+
+        #[test]
+        fn compute_swap_matches_legacy_engine(
+            positions in positions(),
+            tick_current in -TICK_ARRAY_SIZE..2 * TICK_ARRAY_SIZE,
+            price_offset in 0..u64::MAX as u128,
+            zero_for_one in proptest::bool::ANY,
+            is_base_input in proptest::bool::ANY,
+            amount in 1..1u64 << 32,
+        ) {
+            let sqrt_price_lower_x64 = tick::get_sqrt_price_at_tick(tick_current).unwrap();
+            let sqrt_price_upper_x64 = tick::get_sqrt_price_at_tick(tick_current + 1).unwrap();
+            let sqrt_price_x64 =
+                sqrt_price_lower_x64 + price_offset % (sqrt_price_upper_x64 - sqrt_price_lower_x64);
+            let (pool_state, extension, tick_arrays) = pool(&positions, sqrt_price_x64);
+
+            let Ok((is_pool_current_tick_array, start_index)) = pool_state
+                .get_first_initialized_tick_array(&Some(extension.clone()), zero_for_one)
+            else {
+                return Ok(());
+            };
+
+            let expected = swap_v1::compute_swap(
+                zero_for_one,
+                is_base_input,
+                is_pool_current_tick_array,
+                3000,
+                amount,
+                start_index,
+                0,
+                &pool_state,
+                &extension,
+                &mut swap_tick_arrays(&pool_state, &tick_arrays, zero_for_one),
+            );
+            let result = compute_swap(
+                zero_for_one,
+                is_base_input,
+                is_pool_current_tick_array,
+                3000,
+                amount,
+                start_index,
+                0,
+                &pool_state,
+                &extension,
+                &mut swap_tick_arrays(&pool_state, &tick_arrays, zero_for_one),
+            );
+
+            match (expected, result) {
+                (Ok((expected, expected_indices)), Ok((state, tick_array_indices))) => {
+                    prop_assert_eq!(state.sqrt_price_x64, expected.sqrt_price_x64);
+                    prop_assert_eq!(state.tick, expected.tick);
+                    prop_assert_eq!(state.liquidity, expected.liquidity);
+                    prop_assert_eq!(state.remaining_amount, expected.amount_specified_remaining);
+                    prop_assert_eq!(state.amount_calculated, expected.amount_calculated);
+                    prop_assert_eq!(tick_array_indices, expected_indices);
+                }
+                (Err(_), Err(_)) => {}
+                // The legacy engine does not reject swaps rounding to nothing
+                (Ok((expected, _)), Err(SwapError::TooSmallInputOrOutputAmount))
+                    if expected.amount_specified_remaining == amount
+                        || expected.amount_calculated == 0 => {}
+                (expected, result) => {
+                    prop_assert!(
+                        false,
+                        "legacy engine returned {:?}, engine returned {:?}",
+                        expected.map(|(state, _)| state),
+                        result.map(|(state, _)| state)
+                    );
+                }
+            }
+        }
+    }
+}
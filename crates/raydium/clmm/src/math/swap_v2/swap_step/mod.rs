@@ -16,7 +16,6 @@ pub fn compute_swap_step(
     is_base_input: bool,
     zero_for_one: bool,
 ) -> Result<SwapStep> {
-    // let exact_in = amount_remaining >= 0;
     if is_base_input {
         compute_swap_step_by_specified_amount_in(
             sqrt_price_current_x64,
@@ -46,67 +45,45 @@ pub fn compute_swap_step_by_specified_amount_in(
     fee_rate: u32,
     zero_for_one: bool,
 ) -> Result<SwapStep> {
-    // round up amount_in
-    // In exact input case, amount_remaining is positive
-    // TODO: handle overflow
-    let amount_remaining_without_fee =
-        calculate_amount_without_fee(amount_remaining, fee_rate, false)
-            .ok_or(SwapStepError::MathOverflow)?;
+    // In exact input case, the fee is taken out of the remaining amount first
+    let amount_remaining_less_fee = calculate_amount_without_fee(amount_remaining, fee_rate, false)
+        .ok_or(SwapStepError::MathOverflow)?;
 
-    if amount_remaining_without_fee == 0 {
-        println!("amount_remaining_without_fee == 0");
-        let fee =
-            calculate_fee(amount_remaining, fee_rate, true).ok_or(SwapStepError::MathOverflow)?;
-        return Ok(SwapStep {
-            sqrt_price_next_x64: sqrt_price_target_x64,
-            amount_in: amount_remaining,
-            amount_out: 0,
-            fee_amount: fee,
-        });
-    }
-
-    // TODO: handle overflow
     let amount_in_to_target = calculate_amount_in_range(
         sqrt_price_current_x64,
         sqrt_price_target_x64,
         liquidity,
         zero_for_one,
         true,
-    )?
-    .ok_or(SwapStepError::CalculateAmountInRangeOverflow)?;
+    )?;
 
-    let (sqrt_price_next_x64, amount_in, fee_amount) =
-        if amount_remaining_without_fee >= amount_in_to_target {
-            let fee = calculate_fee(amount_in_to_target, fee_rate, true)
-                .ok_or(SwapStepError::MathOverflow)?;
-            (sqrt_price_target_x64, amount_in_to_target + fee, fee)
-        } else {
-            (
-                sqrt_price::get_next_sqrt_price_from_input(
-                    sqrt_price_current_x64,
-                    liquidity,
-                    amount_remaining_without_fee,
-                    zero_for_one,
-                ),
-                amount_remaining,
-                u64::from(amount_remaining)
-                    .checked_sub(amount_remaining_without_fee)
-                    .ok_or(SwapStepError::MathOverflow)?,
-            )
-        };
+    let sqrt_price_next_x64 = match amount_in_to_target {
+        Some(amount_in) if amount_remaining_less_fee >= amount_in => sqrt_price_target_x64,
+        _ => sqrt_price::get_next_sqrt_price_from_input(
+            sqrt_price_current_x64,
+            liquidity,
+            amount_remaining_less_fee,
+            zero_for_one,
+        ),
+    };
 
-    let amount_out = calculate_amount_in_range(
-        sqrt_price_current_x64,
-        sqrt_price_next_x64,
-        liquidity,
-        zero_for_one,
-        false,
-    )?
-    .ok_or(SwapStepError::CalculateAmountInRangeOverflow)?;
+    let amount_in = if sqrt_price_next_x64 == sqrt_price_target_x64 {
+        amount_in_to_target.unwrap_or_default()
+    } else {
+        get_amount_in(sqrt_price_current_x64, sqrt_price_next_x64, liquidity, zero_for_one)?
+    };
+    let amount_out =
+        get_amount_out(sqrt_price_current_x64, sqrt_price_next_x64, liquidity, zero_for_one)?;
 
-    let swap_step = SwapStep { amount_in, amount_out, sqrt_price_next_x64, fee_amount };
+    let fee_amount = if sqrt_price_next_x64 != sqrt_price_target_x64 {
+        // The target was not reached, the dust left in the remaining amount is
+        // granted as fee
+        amount_remaining.checked_sub(amount_in).ok_or(SwapStepError::MathOverflow)?
+    } else {
+        calculate_fee(amount_in, fee_rate, true).ok_or(SwapStepError::MathOverflow)?
+    };
 
-    Ok(swap_step)
+    Ok(SwapStep { sqrt_price_next_x64, amount_in, amount_out, fee_amount })
 }
 
 pub fn compute_swap_step_by_specified_amount_out(
@@ -117,54 +94,43 @@ pub fn compute_swap_step_by_specified_amount_out(
     fee_rate: u32,
     zero_for_one: bool,
 ) -> Result<SwapStep> {
-    // TODO: handle overflow
     let amount_out_to_target = calculate_amount_in_range(
         sqrt_price_current_x64,
         sqrt_price_target_x64,
         liquidity,
         zero_for_one,
         false,
-    )?
-    .ok_or(SwapStepError::CalculateAmountInRangeOverflow)?;
+    )?;
 
-    // For exact output case, cap the output amount to not exceed the remaining
-    // output amount
-    let (amount_out, sqrt_price_next_x64) = if amount_out_to_target > amount_remaining {
-        (
+    let sqrt_price_next_x64 = match amount_out_to_target {
+        Some(amount_out) if amount_remaining >= amount_out => sqrt_price_target_x64,
+        _ => sqrt_price::get_next_sqrt_price_from_output(
+            sqrt_price_current_x64,
+            liquidity,
             amount_remaining,
-            sqrt_price::get_next_sqrt_price_from_output(
-                sqrt_price_current_x64,
-                liquidity,
-                amount_remaining,
-                zero_for_one,
-            ),
-        )
-    } else {
-        (amount_out_to_target, sqrt_price_target_x64)
+            zero_for_one,
+        ),
     };
 
-    let amount_in = calculate_amount_in_range(
-        sqrt_price_current_x64,
-        sqrt_price_next_x64,
-        liquidity,
-        zero_for_one,
-        true,
-    )?
-    .ok_or(SwapStepError::CalculateAmountInRangeOverflow)?;
+    let amount_out = if sqrt_price_next_x64 == sqrt_price_target_x64 {
+        amount_out_to_target.unwrap_or_default()
+    } else {
+        get_amount_out(sqrt_price_current_x64, sqrt_price_next_x64, liquidity, zero_for_one)?
+    };
+    // Cap the output amount to not exceed the remaining output amount
+    let amount_out = amount_out.min(amount_remaining);
+    let amount_in =
+        get_amount_in(sqrt_price_current_x64, sqrt_price_next_x64, liquidity, zero_for_one)?;
 
     let fee_amount = calculate_fee(amount_in, fee_rate, true).ok_or(SwapStepError::MathOverflow)?;
 
-    let swap_step = SwapStep { amount_in, amount_out, sqrt_price_next_x64, fee_amount };
-
-    Ok(swap_step)
+    Ok(SwapStep { sqrt_price_next_x64, amount_in, amount_out, fee_amount })
 }
 
-/// Pre calcumate amount_in or amount_out for the specified price range
-/// The amount maybe overflow of u64 due to the `sqrt_price_target_x64` maybe
-/// unreasonable. Therefore, this situation needs to be handled in
-/// `compute_swap_step` to recalculate the price that can be reached based on
-/// the amount.
-
+/// Pre calculate amount_in or amount_out for the specified price range.
+/// The amount may overflow u64 because `sqrt_price_target_x64` may be
+/// unreasonable, in which case `None` is returned and the reachable price is
+/// recalculated from the remaining amount instead.
 fn calculate_amount_in_range(
     sqrt_price_current_x64: u128,
     sqrt_price_target_x64: u128,
@@ -172,51 +138,76 @@ fn calculate_amount_in_range(
     zero_for_one: bool,
     is_base_input: bool,
 ) -> Result<Option<u64>> {
-    if is_base_input {
-        let result = if zero_for_one {
-            liquidity::get_delta_amount_0_unsigned(
-                sqrt_price_target_x64,
-                sqrt_price_current_x64,
-                liquidity,
-                true,
-            )
-        } else {
-            liquidity::get_delta_amount_1_unsigned(
-                sqrt_price_current_x64,
-                sqrt_price_target_x64,
-                liquidity,
-                true,
-            )
-        }?;
-
-        Ok(Some(result))
+    let result = if is_base_input {
+        get_amount_in(sqrt_price_current_x64, sqrt_price_target_x64, liquidity, zero_for_one)
     } else {
-        let result = if zero_for_one {
-            liquidity::get_delta_amount_1_unsigned(
-                sqrt_price_target_x64,
-                sqrt_price_current_x64,
-                liquidity,
-                false,
-            )
-        } else {
-            liquidity::get_delta_amount_0_unsigned(
-                sqrt_price_current_x64,
-                sqrt_price_target_x64,
-                liquidity,
-                false,
-            )
-        }?;
+        get_amount_out(sqrt_price_current_x64, sqrt_price_target_x64, liquidity, zero_for_one)
+    };
 
-        Ok(Some(result))
+    match result {
+        Ok(amount) => Ok(Some(amount)),
+        Err(SwapStepError::Liquidity(liquidity::LiquidityError::MaxTokenOverflow)) => Ok(None),
+        Err(err) => Err(err),
     }
 }
 
+/// Amount of the input token between two prices, rounded up
+fn get_amount_in(
+    sqrt_price_current_x64: u128,
+    sqrt_price_next_x64: u128,
+    liquidity: u128,
+    zero_for_one: bool,
+) -> Result<u64> {
+    let amount = if zero_for_one {
+        liquidity::get_delta_amount_0_unsigned(
+            sqrt_price_next_x64,
+            sqrt_price_current_x64,
+            liquidity,
+            true,
+        )
+    } else {
+        liquidity::get_delta_amount_1_unsigned(
+            sqrt_price_current_x64,
+            sqrt_price_next_x64,
+            liquidity,
+            true,
+        )
+    }?;
+    Ok(amount)
+}
+
+/// Amount of the output token between two prices, rounded down
+fn get_amount_out(
+    sqrt_price_current_x64: u128,
+    sqrt_price_next_x64: u128,
+    liquidity: u128,
+    zero_for_one: bool,
+) -> Result<u64> {
+    let amount = if zero_for_one {
+        liquidity::get_delta_amount_1_unsigned(
+            sqrt_price_next_x64,
+            sqrt_price_current_x64,
+            liquidity,
+            false,
+        )
+    } else {
+        liquidity::get_delta_amount_0_unsigned(
+            sqrt_price_current_x64,
+            sqrt_price_next_x64,
+            liquidity,
+            false,
+        )
+    }?;
+    Ok(amount)
+}
+
 /// Result of a swap step
 #[derive(Default, Debug)]
 pub struct SwapStep {
     /// The price after swapping the amount in/out, not to exceed the price
     /// target
     pub sqrt_price_next_x64: u128,
+    /// The amount swapped in, the fee excluded
     pub amount_in: u64,
     pub amount_out: u64,
     pub fee_amount: u64,
@@ -224,85 +215,11 @@ pub struct SwapStep {
 
 #[derive(Debug, Error)]
 pub enum SwapStepError {
-    #[error("amount_remaining_without_fee == 0")]
-    AmountRemainingWithoutFeeIsZero,
-
     #[error("Liquidity error: {0}")]
     Liquidity(#[from] liquidity::LiquidityError),
 
-    #[error("calculate_fee overflow")]
-    CalculateFeeOverflow,
-
     #[error("math overflow")]
     MathOverflow,
-
-    #[error("calculate_amount_in_range overflow")]
-    CalculateAmountInRangeOverflow,
 }
 
 pub type Result<T> = std::result::Result<T, SwapStepError>;
-
-// #[cfg(test)]
-// mod swap_math_test {
-//     use proptest::prelude::*;
-
-//     use super::*;
-//     use crate::{constants::FEE_RATE_DENOMINATOR_VALUE, math::tick};
-
-//     proptest! {
-//         #[test]
-//         fn compute_swap_step_test(
-//             sqrt_price_current_x64 in
-// tick::MIN_SQRT_PRICE_X64..tick::MAX_SQRT_PRICE_X64,
-// sqrt_price_target_x64 in tick::MIN_SQRT_PRICE_X64..tick::MAX_SQRT_PRICE_X64,
-//             liquidity in 1..u32::MAX as u128,
-//             amount_remaining in 1..u64::MAX,
-//             fee_rate in 1..FEE_RATE_DENOMINATOR_VALUE/2,
-//             is_base_input in proptest::bool::ANY,
-//         ) {
-//             prop_assume!(sqrt_price_current_x64 != sqrt_price_target_x64);
-
-//             let zero_for_one = sqrt_price_current_x64 >
-// sqrt_price_target_x64;
-
-//             let swap_step = compute_swap_step(
-//                 sqrt_price_current_x64,
-//                 sqrt_price_target_x64,
-//                 liquidity,
-//                 amount_remaining,
-//                 fee_rate,
-//                 is_base_input,
-//                 zero_for_one,
-//             ).expect("compute_swap_step failed");
-
-//             let amount_in = swap_step.amount_in;
-//             let amount_out = swap_step.amount_out;
-//             let sqrt_price_next_x64 = swap_step.sqrt_price_next_x64;
-//             let fee_amount = swap_step.fee_amount;
-
-//             let amount_used = if is_base_input {
-//                 match amount_in.checked_add(fee_amount) {
-//                     Some(amount) => amount,
-//                     None => {
-//                         println!("amount_in + fee_amount overflow");
-//                         println!("amount_in: {}", amount_in);
-//                         println!("fee_amount: {}", fee_amount);
-//                         amount_out
-//                     },
-//                 }
-//             } else {
-//                 amount_out
-//             };
-
-//             if sqrt_price_next_x64 != sqrt_price_target_x64 {
-//                 assert!(amount_used == amount_remaining);
-//             } else {
-//                 assert!(amount_used <= amount_remaining);
-//             }
-//             let price_lower =
-// sqrt_price_current_x64.min(sqrt_price_target_x64);             let
-// price_upper = sqrt_price_current_x64.max(sqrt_price_target_x64);
-// assert!(sqrt_price_next_x64 >= price_lower);
-// assert!(sqrt_price_next_x64 <= price_upper);         }
-//     }
-// }
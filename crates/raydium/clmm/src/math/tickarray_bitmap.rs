@@ -27,6 +27,40 @@ pub fn get_bitmap_tick_boundary(tick_array_start_index: i32, tick_spacing: u16)
     }
 }
 
+/// Returns true if any of the ticks lives in a tick array that can't be
+/// represented by the default tick array bitmap of the pool, in which case the
+/// tick array bitmap extension account is required.
+pub fn is_overflow_default_tickarray_bitmap(tick_spacing: u16, tick_indexs: &[i32]) -> bool {
+    let (min_tick_array_start_index_boundary, max_tick_array_index_boundary) =
+        tick_array_start_index_range(tick_spacing);
+    for tick_index in tick_indexs {
+        let tick_array_start_index = tick::get_array_start_index(*tick_index, tick_spacing);
+        if tick_array_start_index >= max_tick_array_index_boundary
+            || tick_array_start_index < min_tick_array_start_index_boundary
+        {
+            return true;
+        }
+    }
+    false
+}
+
+// the range of tick array start index that default tickarray bitmap can
+// represent if tick_spacing = 1, the result range is [-30720, 30720)
+pub fn tick_array_start_index_range(tick_spacing: u16) -> (i32, i32) {
+    // the range of ticks that default tickarrary can represent
+    let mut max_tick_boundary = max_tick_in_tickarray_bitmap(tick_spacing);
+    let mut min_tick_boundary = -max_tick_boundary;
+    if max_tick_boundary > tick::MAX_TICK {
+        max_tick_boundary = tick::get_array_start_index(tick::MAX_TICK, tick_spacing);
+        // find the next tick array start index
+        max_tick_boundary += tick::tick_count(tick_spacing);
+    }
+    if min_tick_boundary < tick::MIN_TICK {
+        min_tick_boundary = tick::get_array_start_index(tick::MIN_TICK, tick_spacing);
+    }
+    (min_tick_boundary, max_tick_boundary)
+}
+
 pub fn most_significant_bit(x: U1024) -> Option<u16> {
     if x.is_zero() {
         None
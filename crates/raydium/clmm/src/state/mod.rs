@@ -1,7 +1,9 @@
+pub mod oracle;
 pub mod pool;
 pub mod tick_array;
 pub mod tickarray_bitmap_extension;
 
+pub use oracle::*;
 pub use pool::*;
 pub use tick_array::*;
 pub use tickarray_bitmap_extension::*;
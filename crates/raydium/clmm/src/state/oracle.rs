@@ -0,0 +1,236 @@
+use thiserror::Error;
+
+use crate::{
+    constants::OBSERVATION_NUM,
+    generated::{accounts::ObservationState, types::Observation},
+    math::tick::{self, TickError},
+};
+
+impl ObservationState {
+    /// Iterate over the recorded observations from the oldest to the newest.
+    /// Slots of the ring buffer that were never written are skipped.
+    pub fn observations_chronological(&self) -> impl Iterator<Item = &Observation> {
+        let start = (usize::from(self.observation_index) + 1) % OBSERVATION_NUM;
+        self.observations[start..]
+            .iter()
+            .chain(self.observations[..start].iter())
+            .filter(|observation| observation.block_timestamp != 0)
+    }
+
+    /// The most recent observation written by the program.
+    pub fn latest_observation(&self) -> OracleResult<&Observation> {
+        if !self.initialized {
+            return Err(OracleError::NotInitialized);
+        }
+        Ok(&self.observations[usize::from(self.observation_index)])
+    }
+
+    /// The timestamp of the oldest observation still held in the ring buffer,
+    /// i.e. the furthest point in the past that can be observed.
+    pub fn oldest_timestamp(&self) -> OracleResult<u32> {
+        if !self.initialized {
+            return Err(OracleError::NotInitialized);
+        }
+        self.observations_chronological()
+            .next()
+            .map(|observation| observation.block_timestamp)
+            .ok_or(OracleError::NotInitialized)
+    }
+
+    /// Returns the tick cumulative at `target_timestamp`. Between two
+    /// observations the value is interpolated, after the latest observation
+    /// it is extrapolated with `tick_current`, the tick of the pool since the
+    /// latest observation.
+    pub fn observe(&self, target_timestamp: u32, tick_current: i32) -> OracleResult<i64> {
+        let latest = self.latest_observation()?;
+        if target_timestamp >= latest.block_timestamp {
+            let time_delta = i64::from(target_timestamp - latest.block_timestamp);
+            return Ok(latest.tick_cumulative.wrapping_add(i64::from(tick_current) * time_delta));
+        }
+
+        let oldest_timestamp = self.oldest_timestamp()?;
+        if target_timestamp < oldest_timestamp {
+            return Err(OracleError::WindowExceedsHistory { target_timestamp, oldest_timestamp });
+        }
+
+        let mut before: Option<&Observation> = None;
+        for observation in self.observations_chronological() {
+            if observation.block_timestamp == target_timestamp {
+                return Ok(observation.tick_cumulative);
+            }
+            if observation.block_timestamp > target_timestamp {
+                let before = before.ok_or(OracleError::WindowExceedsHistory {
+                    target_timestamp,
+                    oldest_timestamp,
+                })?;
+                let observation_time_delta =
+                    i128::from(observation.block_timestamp - before.block_timestamp);
+                let target_time_delta = i128::from(target_timestamp - before.block_timestamp);
+                let tick_cumulative_delta =
+                    i128::from(observation.tick_cumulative.wrapping_sub(before.tick_cumulative));
+                // The tick can change between two observations, so the delta is
+                // not always a multiple of the interval: multiply before dividing
+                let interpolated_delta = (tick_cumulative_delta * target_time_delta)
+                    .div_euclid(observation_time_delta)
+                    as i64;
+                return Ok(before.tick_cumulative.wrapping_add(interpolated_delta));
+            }
+            before = Some(observation);
+        }
+
+        Err(OracleError::WindowExceedsHistory { target_timestamp, oldest_timestamp })
+    }
+
+    /// Returns the time weighted average tick over the `window` seconds that
+    /// precede `current_timestamp`, rounded towards negative infinity.
+    pub fn get_time_weighted_average_tick(
+        &self,
+        window: u32,
+        current_timestamp: u32,
+        tick_current: i32,
+    ) -> OracleResult<i32> {
+        if window == 0 {
+            return Err(OracleError::InvalidWindow);
+        }
+        let start_timestamp =
+            current_timestamp.checked_sub(window).ok_or(OracleError::InvalidWindow)?;
+
+        let tick_cumulative_start = self.observe(start_timestamp, tick_current)?;
+        let tick_cumulative_end = self.observe(current_timestamp, tick_current)?;
+        let tick_cumulative_delta = tick_cumulative_end.wrapping_sub(tick_cumulative_start);
+
+        let average_tick = tick_cumulative_delta.div_euclid(i64::from(window));
+        i32::try_from(average_tick).map_err(|_| OracleError::TickOutOfRange)
+    }
+
+    /// Returns the sqrt price (Q64.64) at the time weighted average tick over
+    /// the `window` seconds that precede `current_timestamp`.
+    pub fn get_time_weighted_average_sqrt_price_x64(
+        &self,
+        window: u32,
+        current_timestamp: u32,
+        tick_current: i32,
+    ) -> OracleResult<u128> {
+        let average_tick =
+            self.get_time_weighted_average_tick(window, current_timestamp, tick_current)?;
+        Ok(tick::get_sqrt_price_at_tick(average_tick)?)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum OracleError {
+    #[error("Observation state not initialized")]
+    NotInitialized,
+
+    #[error("Observation window must be non-zero and start after the unix epoch")]
+    InvalidWindow,
+
+    #[error(
+        "Observation window starting at {target_timestamp} exceeds history, oldest observation is \
+         at {oldest_timestamp}"
+    )]
+    WindowExceedsHistory { target_timestamp: u32, oldest_timestamp: u32 },
+
+    #[error("Average tick out of range")]
+    TickOutOfRange,
+
+    #[error("Tick error: {0}")]
+    TickError(#[from] TickError),
+}
+
+pub type OracleResult<T> = Result<T, OracleError>;
+
+#[cfg(test)]
+mod tests {
+    use solana_program::pubkey::Pubkey;
+
+    use super::*;
+
+    fn observation_state(observations: &[(u32, i64)], observation_index: u16) -> ObservationState {
+        let mut state = ObservationState {
+            discriminator: [0; 8],
+            initialized: !observations.is_empty(),
+            recent_epoch: 0,
+            observation_index,
+            pool_id: Pubkey::default(),
+            observations: core::array::from_fn(|_| Observation {
+                block_timestamp: 0,
+                tick_cumulative: 0,
+                padding: [0; 4],
+            }),
+            padding: [0; 4],
+        };
+        for (i, (block_timestamp, tick_cumulative)) in observations.iter().enumerate() {
+            state.observations[i].block_timestamp = *block_timestamp;
+            state.observations[i].tick_cumulative = *tick_cumulative;
+        }
+        state
+    }
+
+    #[test]
+    fn test_time_weighted_average_tick() {
+        // tick 10 during [1000, 1100), tick -20 during [1100, 1200)
+        let state = observation_state(&[(1000, 0), (1100, 1000), (1200, -1000)], 2);
+
+        assert_eq!(state.oldest_timestamp().unwrap(), 1000);
+        assert_eq!(state.get_time_weighted_average_tick(200, 1200, 0).unwrap(), -5);
+        assert_eq!(state.get_time_weighted_average_tick(50, 1150, 0).unwrap(), -20);
+        // interpolation across the observation at 1100
+        assert_eq!(state.get_time_weighted_average_tick(100, 1150, 0).unwrap(), -5);
+        // extrapolation with the current tick after the latest observation
+        assert_eq!(state.get_time_weighted_average_tick(100, 1250, 30).unwrap(), 5);
+    }
+
+    #[test]
+    fn test_time_weighted_average_tick_rounds_down() {
+        let state = observation_state(&[(1000, 0), (1003, -1)], 1);
+        assert_eq!(state.get_time_weighted_average_tick(3, 1003, 0).unwrap(), -1);
+    }
+
+    #[test]
+    fn test_interpolation_across_non_multiple_delta() {
+        // tick 100 during [1000, 1010), tick 0 during [1010, 1015)
+        let state = observation_state(&[(1000, 0), (1015, 1000)], 1);
+        assert_eq!(state.observe(1010, 0).unwrap(), 666);
+        assert_eq!(state.get_time_weighted_average_tick(5, 1015, 0).unwrap(), 66);
+
+        let state = observation_state(&[(1000, 0), (1015, -1000)], 1);
+        assert_eq!(state.observe(1010, 0).unwrap(), -667);
+        assert_eq!(state.get_time_weighted_average_tick(10, 1010, 0).unwrap(), -67);
+    }
+
+    #[test]
+    fn test_window_exceeds_history() {
+        let state = observation_state(&[(1000, 0), (1100, 1000)], 1);
+        assert!(matches!(
+            state.get_time_weighted_average_tick(200, 1100, 0),
+            Err(OracleError::WindowExceedsHistory {
+                target_timestamp: 900,
+                oldest_timestamp: 1000
+            })
+        ));
+        assert!(matches!(
+            observation_state(&[], 0).get_time_weighted_average_tick(10, 1100, 0),
+            Err(OracleError::NotInitialized)
+        ));
+    }
+
+    #[test]
+    fn test_wrapped_ring_buffer() {
+        let observations: Vec<(u32, i64)> =
+            (0..OBSERVATION_NUM as u32).map(|i| (1000 + i * 15, i64::from(i) * 15)).collect();
+        let mut state = observation_state(&observations, (OBSERVATION_NUM - 1) as u16);
+        // overwrite the oldest slot with a newer observation
+        state.observations[0].block_timestamp = 1000 + OBSERVATION_NUM as u32 * 15;
+        state.observations[0].tick_cumulative = OBSERVATION_NUM as i64 * 15;
+        state.observation_index = 0;
+
+        assert_eq!(state.oldest_timestamp().unwrap(), 1015);
+        assert_eq!(
+            state
+                .get_time_weighted_average_tick(1485, 1000 + OBSERVATION_NUM as u32 * 15, 0)
+                .unwrap(),
+            1
+        );
+    }
+}
@@ -1,10 +1,18 @@
 use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
 use thiserror::Error;
 
 use crate::{
-    generated::accounts::{PoolState, TickArrayBitmapExtension},
-    libraries::big_num::U1024,
-    math::{tick, tickarray_bitmap},
+    constants::REWARD_NUM,
+    generated::{
+        accounts::{PoolState, TickArrayBitmapExtension},
+        types::{RewardInfo, RewardState},
+    },
+    libraries::{
+        big_num::{U1024, U128, U256},
+        fixed_point_64,
+    },
+    math::{full_math::MulDiv, tick, tickarray_bitmap},
 };
 
 impl PoolState {
@@ -29,13 +37,9 @@ impl PoolState {
             }
             last_tick_array_start_index = start_index;
 
-            if tickarray_bitmap_extension.is_none() {
-                return Err(PoolStateError::MissingTickArrayBitmapExtensionAccount);
-            }
-
             let (is_found, start_index) = tickarray_bitmap_extension
                 .as_ref()
-                .unwrap()
+                .ok_or(PoolStateError::MissingTickArrayBitmapExtensionAccount)?
                 .next_initialized_tick_array_from_one_bitmap(
                     last_tick_array_start_index,
                     self.tick_spacing,
@@ -64,19 +68,19 @@ impl PoolState {
             if self.is_overflow_default_tickarray_bitmap(vec![self.tick_current]) {
                 tickarray_bitmap_extension
                     .as_ref()
-                    .unwrap()
+                    .ok_or(PoolStateError::MissingTickArrayBitmapExtensionAccount)?
                     .check_tick_array_is_initialized(
                         tick::get_array_start_index(self.tick_current, self.tick_spacing),
                         self.tick_spacing,
                     )
-                    .unwrap()
+                    .map_err(|_| PoolStateError::TickArrayBitmapExtension)?
             } else {
                 tickarray_bitmap::check_current_tick_array_is_initialized(
                     U1024(self.tick_array_bitmap),
                     self.tick_current,
                     self.tick_spacing.into(),
                 )
-                .unwrap()
+                .map_err(|_| PoolStateError::InvalidTickIndex)?
             };
         if is_initialized {
             return Ok((true, start_index));
@@ -86,49 +90,99 @@ impl PoolState {
                 tickarray_bitmap_extension,
                 tick::get_array_start_index(self.tick_current, self.tick_spacing),
                 zero_for_one,
-            )
-            .unwrap();
-        // require!(
-        //     next_start_index.is_some(),
-        //     PoolStateError::InsufficientLiquidityForDirection
-        // );
-        return Ok((false, next_start_index.unwrap()));
+            )?
+            .ok_or(PoolStateError::InsufficientLiquidityForDirection)?;
+        Ok((false, next_start_index))
     }
 
     pub fn is_overflow_default_tickarray_bitmap(&self, tick_indexs: Vec<i32>) -> bool {
-        let (min_tick_array_start_index_boundary, max_tick_array_index_boundary) =
-            self.tick_array_start_index_range();
-        for tick_index in tick_indexs {
-            let tick_array_start_index = tick::get_array_start_index(tick_index, self.tick_spacing);
-            if tick_array_start_index >= max_tick_array_index_boundary
-                || tick_array_start_index < min_tick_array_start_index_boundary
-            {
-                return true;
-            }
-        }
-        false
+        tickarray_bitmap::is_overflow_default_tickarray_bitmap(self.tick_spacing, &tick_indexs)
     }
 
     // the range of tick array start index that default tickarray bitmap can
     // represent if tick_spacing = 1, the result range is [-30720, 30720)
     pub fn tick_array_start_index_range(&self) -> (i32, i32) {
-        // the range of ticks that default tickarrary can represent
-        let mut max_tick_boundary =
-            tickarray_bitmap::max_tick_in_tickarray_bitmap(self.tick_spacing);
-        let mut min_tick_boundary = -max_tick_boundary;
-        if max_tick_boundary > tick::MAX_TICK {
-            max_tick_boundary = tick::get_array_start_index(tick::MAX_TICK, self.tick_spacing);
-            // find the next tick array start index
-            max_tick_boundary = max_tick_boundary + tick::tick_count(self.tick_spacing);
-        }
-        if min_tick_boundary < tick::MIN_TICK {
-            min_tick_boundary = tick::get_array_start_index(tick::MIN_TICK, self.tick_spacing);
+        tickarray_bitmap::tick_array_start_index_range(self.tick_spacing)
+    }
+
+    /// Returns true if the operation is enabled. A set bit in `status`
+    /// disables the corresponding operation.
+    pub fn is_operation_allowed(&self, bit: PoolStatusBitIndex) -> bool {
+        let status = 1u8 << (bit as u8);
+        self.status & status == 0
+    }
+
+    /// Swaps are only accepted strictly after the pool open time.
+    pub fn is_open(&self, block_timestamp: u64) -> bool { block_timestamp > self.open_time }
+
+    /// Returns the pool reward infos as they would be after the program
+    /// updates them at `curr_timestamp`. The pool state itself is left
+    /// untouched.
+    pub fn get_updated_reward_infos(
+        &self,
+        curr_timestamp: u64,
+    ) -> PoolResult<[RewardInfo; REWARD_NUM]> {
+        let mut reward_infos = self.reward_infos.clone();
+        for reward_info in reward_infos.iter_mut() {
+            if !reward_info.initialized() {
+                continue;
+            }
+            if curr_timestamp <= reward_info.open_time {
+                continue;
+            }
+            let latest_update_timestamp = curr_timestamp.min(reward_info.end_time);
+
+            if self.liquidity != 0 {
+                let time_delta = latest_update_timestamp
+                    .checked_sub(reward_info.last_update_time)
+                    .ok_or(PoolStateError::InvalidRewardUpdateTime)?;
+
+                let reward_growth_delta = U256::from(time_delta)
+                    .mul_div_floor(
+                        U256::from(reward_info.emissions_per_second_x64),
+                        U256::from(self.liquidity),
+                    )
+                    .ok_or(PoolStateError::RewardOverflow)?;
+                reward_info.reward_growth_global_x64 = reward_info
+                    .reward_growth_global_x64
+                    .checked_add(reward_growth_delta.as_u128())
+                    .ok_or(PoolStateError::RewardOverflow)?;
+
+                let reward_emission_delta = U128::from(time_delta)
+                    .mul_div_ceil(
+                        U128::from(reward_info.emissions_per_second_x64),
+                        U128::from(fixed_point_64::Q64),
+                    )
+                    .ok_or(PoolStateError::RewardOverflow)?;
+                reward_info.reward_total_emissioned = reward_info
+                    .reward_total_emissioned
+                    .checked_add(reward_emission_delta.as_u64())
+                    .ok_or(PoolStateError::RewardOverflow)?;
+            }
+            reward_info.last_update_time = latest_update_timestamp;
+
+            if reward_info.reward_state == RewardState::Initialized as u8 {
+                if latest_update_timestamp >= reward_info.open_time
+                    && latest_update_timestamp < reward_info.end_time
+                {
+                    reward_info.reward_state = RewardState::Opening as u8;
+                }
+            } else if reward_info.reward_state == RewardState::Opening as u8
+                && latest_update_timestamp == reward_info.end_time
+            {
+                reward_info.reward_state = RewardState::Ended as u8;
+            }
         }
-        (min_tick_boundary, max_tick_boundary)
+        Ok(reward_infos)
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+impl RewardInfo {
+    /// A reward slot is initialized once a reward mint has been assigned to it.
+    pub fn initialized(&self) -> bool { self.token_mint != Pubkey::default() }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PoolStatusBitIndex {
     OpenPositionOrIncreaseLiquidity,
     DecreaseLiquidity,
@@ -151,6 +205,18 @@ pub enum PoolStateError {
 
     #[error("Tick array start tick index out of range limit")]
     TickArrayBitmapExtension,
+
+    #[error("Invalid tick index")]
+    InvalidTickIndex,
+
+    #[error("Insufficient liquidity for this direction")]
+    InsufficientLiquidityForDirection,
+
+    #[error("Reward update time is before the last update time")]
+    InvalidRewardUpdateTime,
+
+    #[error("Reward growth overflow")]
+    RewardOverflow,
 }
 
 pub type PoolResult<T> = Result<T, PoolStateError>;
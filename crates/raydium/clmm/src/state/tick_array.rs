@@ -1,8 +1,11 @@
 use thiserror::Error;
 
 use crate::{
-    constants::{TICK_ARRAY_SIZE, TICK_ARRAY_SIZE_USIZE},
-    generated::{accounts::TickArrayState, types::TickState},
+    constants::{REWARD_NUM, TICK_ARRAY_SIZE, TICK_ARRAY_SIZE_USIZE},
+    generated::{
+        accounts::TickArrayState,
+        types::{RewardInfo, TickState},
+    },
     math::tick,
 };
 
@@ -27,12 +30,6 @@ impl TickArrayState {
             (current_tick_index - self.start_tick_index) / i32::from(tick_spacing);
 
         if zero_for_one {
-            // Note: different from the original clmm, we need to check if the
-            // offset_in_array is 0
-            if offset_in_array == 0 {
-                return Ok(None);
-            }
-
             while offset_in_array >= 0 {
                 if self.ticks[offset_in_array as usize].is_initialized() {
                     return Ok(Some(self.ticks[offset_in_array as usize].clone()));
@@ -80,6 +77,92 @@ impl TickState {
     pub fn is_initialized(&self) -> bool { self.liquidity_gross != 0 }
 }
 
+/// Calculates the fee growths inside of tick_lower and tick_upper based on
+/// their positions relative to tick_current.
+pub fn get_fee_growth_inside(
+    tick_lower: &TickState,
+    tick_upper: &TickState,
+    tick_current: i32,
+    fee_growth_global_0_x64: u128,
+    fee_growth_global_1_x64: u128,
+) -> (u128, u128) {
+    // calculate fee growth below
+    let (fee_growth_below_0_x64, fee_growth_below_1_x64) = if tick_current >= tick_lower.tick {
+        (tick_lower.fee_growth_outside0_x64, tick_lower.fee_growth_outside1_x64)
+    } else {
+        (
+            fee_growth_global_0_x64.wrapping_sub(tick_lower.fee_growth_outside0_x64),
+            fee_growth_global_1_x64.wrapping_sub(tick_lower.fee_growth_outside1_x64),
+        )
+    };
+
+    // calculate fee growth above
+    let (fee_growth_above_0_x64, fee_growth_above_1_x64) = if tick_current < tick_upper.tick {
+        (tick_upper.fee_growth_outside0_x64, tick_upper.fee_growth_outside1_x64)
+    } else {
+        (
+            fee_growth_global_0_x64.wrapping_sub(tick_upper.fee_growth_outside0_x64),
+            fee_growth_global_1_x64.wrapping_sub(tick_upper.fee_growth_outside1_x64),
+        )
+    };
+
+    let fee_growth_inside_0_x64 = fee_growth_global_0_x64
+        .wrapping_sub(fee_growth_below_0_x64)
+        .wrapping_sub(fee_growth_above_0_x64);
+    let fee_growth_inside_1_x64 = fee_growth_global_1_x64
+        .wrapping_sub(fee_growth_below_1_x64)
+        .wrapping_sub(fee_growth_above_1_x64);
+
+    (fee_growth_inside_0_x64, fee_growth_inside_1_x64)
+}
+
+/// Calculates the reward growths inside of tick_lower and tick_upper based on
+/// their positions relative to tick_current. Uninitialized reward slots report
+/// zero growth.
+pub fn get_reward_growths_inside(
+    tick_lower: &TickState,
+    tick_upper: &TickState,
+    tick_current_index: i32,
+    reward_infos: &[RewardInfo; REWARD_NUM],
+) -> [u128; REWARD_NUM] {
+    let mut reward_growths_inside = [0; REWARD_NUM];
+
+    for i in 0..REWARD_NUM {
+        if !reward_infos[i].initialized() {
+            continue;
+        }
+
+        // By convention, assume all prior growth happened below the tick
+        let reward_growths_below = if tick_lower.liquidity_gross == 0 {
+            reward_infos[i].reward_growth_global_x64
+        } else if tick_current_index < tick_lower.tick {
+            reward_infos[i]
+                .reward_growth_global_x64
+                .wrapping_sub(tick_lower.reward_growths_outside_x64[i])
+        } else {
+            tick_lower.reward_growths_outside_x64[i]
+        };
+
+        // By convention, assume all prior growth happened below the tick, not above
+        let reward_growths_above = if tick_upper.liquidity_gross == 0 {
+            0
+        } else if tick_current_index < tick_upper.tick {
+            tick_upper.reward_growths_outside_x64[i]
+        } else {
+            reward_infos[i]
+                .reward_growth_global_x64
+                .wrapping_sub(tick_upper.reward_growths_outside_x64[i])
+        };
+
+        reward_growths_inside[i] = reward_infos[i]
+            .reward_growth_global_x64
+            .wrapping_sub(reward_growths_below)
+            .wrapping_sub(reward_growths_above);
+    }
+
+    reward_growths_inside
+}
+
 #[derive(Debug, Error)]
 pub enum TickStateError {
     #[error("Tick state not initialized")]
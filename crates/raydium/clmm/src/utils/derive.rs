@@ -2,8 +2,8 @@ use solana_program::pubkey::Pubkey;
 
 use crate::{
     constants::{
-        AMM_CONFIG_SEED, OBSERVATION_SEED, POOL_SEED, POOL_TICK_ARRAY_BITMAP_SEED, POOL_VAULT_SEED,
-        POSITION_SEED, TICK_ARRAY_SEED,
+        AMM_CONFIG_SEED, OBSERVATION_SEED, OPERATION_SEED, POOL_REWARD_VAULT_SEED, POOL_SEED,
+        POOL_TICK_ARRAY_BITMAP_SEED, POOL_VAULT_SEED, POSITION_SEED, TICK_ARRAY_SEED,
     },
     math::tick,
     ID,
@@ -103,6 +103,23 @@ pub fn derive_observation_pubkey(pool_state: Pubkey, program_id: Option<Pubkey>)
     Pubkey::find_program_address(&[OBSERVATION_SEED.as_bytes(), pool_state.as_ref()], &program_id)
 }
 
+pub fn derive_pool_reward_vault_pubkey(
+    pool_state: Pubkey,
+    reward_token_mint: Pubkey,
+    program_id: Option<Pubkey>,
+) -> (Pubkey, u8) {
+    let program_id = program_id.unwrap_or(ID);
+    Pubkey::find_program_address(
+        &[POOL_REWARD_VAULT_SEED.as_bytes(), pool_state.as_ref(), reward_token_mint.as_ref()],
+        &program_id,
+    )
+}
+
+pub fn derive_operation_pubkey(program_id: Option<Pubkey>) -> (Pubkey, u8) {
+    let program_id = program_id.unwrap_or(ID);
+    Pubkey::find_program_address(&[OPERATION_SEED.as_bytes()], &program_id)
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
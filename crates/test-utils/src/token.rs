@@ -13,10 +13,25 @@ use solana_sdk::{
 use spl_associated_token_account::{
     get_associated_token_address_with_program_id, instruction as associated_token_instruction,
 };
-use spl_token::instruction as token_instruction;
+use spl_token_2022::{
+    extension::{transfer_fee, transfer_hook, ExtensionType},
+    instruction as token_instruction,
+    state::Mint,
+};
 
 use crate::{account::check_account_exists, sign_and_send_transaction};
 
+/// A Token-2022 mint extension to initialize when creating a mint.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MintExtension {
+    /// A transfer fee of `fee_bps`, capped at `max_fee` per transfer.
+    TransferFee { fee_bps: u16, max_fee: u64 },
+    /// A transfer hook calling `program_id` on every transfer.
+    TransferHook { program_id: Pubkey },
+    /// A delegate allowed to move or burn the tokens of any account.
+    PermanentDelegate { delegate: Pubkey },
+}
+
 /// Creates a new token mint with the specified parameters.
 ///
 /// # Arguments
@@ -38,29 +53,100 @@ pub fn create_mint(
     mint_authority: &Pubkey,
     decimals: u8,
     token_program_id: Pubkey,
+) -> Result<(Pubkey, TransactionMetadata)> {
+    create_mint_with_program(svm, payer, mint_authority, decimals, token_program_id, &[])
+}
+
+/// Creates a new Token-2022 mint with the specified extensions. The mint
+/// authority is also the authority of every extension.
+///
+/// # Arguments
+/// * `svm` - The LiteSVM instance
+/// * `payer` - The keypair that will pay for the transaction
+/// * `mint_authority` - The public key that will have minting authority
+/// * `decimals` - The number of decimals for the token
+/// * `extensions` - The extensions to initialize
+///
+/// # Returns
+/// A tuple containing the mint's public key and transaction metadata
+///
+/// # Errors
+/// Returns an error if the transaction fails
+pub fn create_mint_with_extensions(
+    svm: &mut LiteSVM,
+    payer: &Keypair,
+    mint_authority: &Pubkey,
+    decimals: u8,
+    extensions: &[MintExtension],
+) -> Result<(Pubkey, TransactionMetadata)> {
+    create_mint_with_program(svm, payer, mint_authority, decimals, spl_token_2022::ID, extensions)
+}
+
+fn create_mint_with_program(
+    svm: &mut LiteSVM,
+    payer: &Keypair,
+    mint_authority: &Pubkey,
+    decimals: u8,
+    token_program_id: Pubkey,
+    extensions: &[MintExtension],
 ) -> Result<(Pubkey, TransactionMetadata)> {
     if decimals > 9 {
         return Err(TokenError::InvalidDecimals(decimals));
     }
 
     let mint = Keypair::new();
-    let space = 82;
-    let create_account_ix = system_instruction::create_account(
+    let extension_types = extensions
+        .iter()
+        .map(|extension| match extension {
+            MintExtension::TransferFee { .. } => ExtensionType::TransferFeeConfig,
+            MintExtension::TransferHook { .. } => ExtensionType::TransferHook,
+            MintExtension::PermanentDelegate { .. } => ExtensionType::PermanentDelegate,
+        })
+        .collect::<Vec<_>>();
+    let space = ExtensionType::try_calculate_account_len::<Mint>(&extension_types)?;
+    let mut instructions = vec![system_instruction::create_account(
         &payer.pubkey(),
         &mint.pubkey(),
         svm.minimum_balance_for_rent_exemption(space),
         space as u64,
         &token_program_id,
-    );
-    let create_mint_ix = token_instruction::initialize_mint(
+    )];
+    for extension in extensions {
+        instructions.push(match *extension {
+            MintExtension::TransferFee { fee_bps, max_fee } => {
+                transfer_fee::instruction::initialize_transfer_fee_config(
+                    &token_program_id,
+                    &mint.pubkey(),
+                    Some(mint_authority),
+                    Some(mint_authority),
+                    fee_bps,
+                    max_fee,
+                )?
+            }
+            MintExtension::TransferHook { program_id } => transfer_hook::instruction::initialize(
+                &token_program_id,
+                &mint.pubkey(),
+                Some(*mint_authority),
+                Some(program_id),
+            )?,
+            MintExtension::PermanentDelegate { delegate } => {
+                token_instruction::initialize_permanent_delegate(
+                    &token_program_id,
+                    &mint.pubkey(),
+                    &delegate,
+                )?
+            }
+        });
+    }
+    instructions.push(token_instruction::initialize_mint(
         &token_program_id,
         &mint.pubkey(),
         &mint_authority,
         None,
         decimals,
-    )?;
+    )?);
     let transaction = Transaction::new_signed_with_payer(
-        &[create_account_ix, create_mint_ix],
+        &instructions,
         Some(&payer.pubkey()),
         &[&payer, &mint],
         svm.latest_blockhash(),
@@ -123,6 +209,7 @@ pub fn mint_to(
 /// * `mint` - The mint's public key
 /// * `payer` - The payer's public key
 /// * `wallet` - The wallet's public key
+/// * `token_program_id` - The program ID of the token program owning the mint
 ///
 /// # Returns
 /// A tuple containing the instruction and the associated token account address
@@ -130,17 +217,16 @@ pub fn prepare_create_ata_instruction(
     mint: &Pubkey,
     payer: &Pubkey,
     wallet: &Pubkey,
+    token_program_id: &Pubkey,
 ) -> Result<(Instruction, Pubkey)> {
-    let token_program_id = spl_token::ID;
-
     let create_ata_ix = associated_token_instruction::create_associated_token_account(
         &payer,
         &wallet,
         &mint,
-        &token_program_id,
+        token_program_id,
     );
 
-    let ata = get_associated_token_address_with_program_id(&wallet, &mint, &token_program_id);
+    let ata = get_associated_token_address_with_program_id(wallet, mint, token_program_id);
 
     Ok((create_ata_ix, ata))
 }
@@ -174,7 +260,8 @@ pub fn get_or_create_ata(
         return Ok((ata, TransactionMetadata::default()));
     }
 
-    let (create_ata_ix, ata) = prepare_create_ata_instruction(mint, &payer.pubkey(), wallet)?;
+    let (create_ata_ix, ata) =
+        prepare_create_ata_instruction(mint, &payer.pubkey(), wallet, &token_program_id)?;
     let result = sign_and_send_transaction!(svm, &[create_ata_ix], &payer)?;
 
     Ok((ata, result))
@@ -186,7 +273,7 @@ pub enum TokenError {
     Token(#[from] spl_token::error::TokenError),
 
     #[error("LiteSVM transaction failed")]
-    LiteSVM(FailedTransactionMetadata),
+    LiteSVM(Box<FailedTransactionMetadata>),
 
     #[error("Program error: {0}")]
     Program(#[from] ProgramError),
@@ -202,7 +289,7 @@ pub enum TokenError {
 }
 
 impl From<FailedTransactionMetadata> for TokenError {
-    fn from(value: FailedTransactionMetadata) -> Self { TokenError::LiteSVM(value) }
+    fn from(value: FailedTransactionMetadata) -> Self { TokenError::LiteSVM(Box::new(value)) }
 }
 
 pub type Result<T> = std::result::Result<T, TokenError>;